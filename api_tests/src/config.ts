@@ -14,6 +14,7 @@ export interface CndConfigFile {
     data?: { dir: string };
     network: { listen: string[] };
     logging: { level: string };
+    expiry_margins: { bitcoin: number; ethereum: number };
 }
 
 export interface HttpApi {
@@ -61,6 +62,11 @@ export class E2ETestActorConfig {
             logging: {
                 level: "Trace",
             },
+            // The tests use expiries of a few seconds to refund swaps quickly
+            expiry_margins: {
+                bitcoin: 0,
+                ethereum: 0,
+            },
             ...this.createLedgerConnectors(ledgerConfig),
         };
     }
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE han_ethereum_halight_bitcoin_swaps;
+DROP TABLE han_ethereum_halight_bitcoin_finalized_swaps;
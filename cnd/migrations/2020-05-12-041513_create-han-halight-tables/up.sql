@@ -0,0 +1,27 @@
+-- Your SQL goes here
+
+CREATE TABLE han_ethereum_halight_bitcoin_swaps
+(
+    id INTEGER                  NOT NULL PRIMARY KEY,
+    local_swap_id UNIQUE        NOT NULL,
+    role                        NOT NULL,
+    counterparty_peer_id        NOT NULL,
+    counterparty_address_hint,
+    ethereum_identity           NOT NULL,
+    ethereum_absolute_expiry    NOT NULL,
+    ethereum_amount             NOT NULL,
+    lightning_identity          NOT NULL,
+    lightning_cltv_expiry       NOT NULL,
+    lightning_amount            NOT NULL
+);
+
+CREATE TABLE han_ethereum_halight_bitcoin_finalized_swaps
+(
+    id INTEGER                      NOT NULL PRIMARY KEY,
+    local_swap_id UNIQUE            NOT NULL,
+    shared_swap_id UNIQUE           NOT NULL,
+    counterparty_ethereum_identity  NOT NULL,
+    counterparty_lightning_identity NOT NULL,
+    secret_hash                     NOT NULL,
+    at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
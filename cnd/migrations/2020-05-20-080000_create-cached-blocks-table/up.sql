@@ -0,0 +1,13 @@
+-- Your SQL goes here
+
+CREATE TABLE cached_blocks
+(
+    id INTEGER          NOT NULL PRIMARY KEY,
+    item                NOT NULL,
+    hash                NOT NULL,
+    data BLOB           NOT NULL,
+    last_used BIGINT    NOT NULL,
+    UNIQUE (item, hash)
+);
+
+CREATE INDEX cached_blocks_item_last_used ON cached_blocks (item, last_used);
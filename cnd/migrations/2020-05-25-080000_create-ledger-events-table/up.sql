@@ -0,0 +1,14 @@
+-- Your SQL goes here
+
+CREATE TABLE ledger_events
+(
+    id INTEGER              NOT NULL PRIMARY KEY,
+    swap_id                 NOT NULL,
+    ledger                  NOT NULL,
+    event                   NOT NULL,
+    block_hash,
+    resume_from DATETIME,
+    seen_at DATETIME        NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX ledger_events_swap_id_ledger ON ledger_events (swap_id, ledger);
@@ -0,0 +1,16 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE rfc003_swaps RENAME TO rfc003_swaps_new;
+
+CREATE TABLE rfc003_swaps
+(
+    id INTEGER     	NOT NULL PRIMARY KEY,
+    swap_id UNIQUE 	NOT NULL,
+    role 		NOT NULL,
+    counterparty 	NOT NULL
+);
+
+INSERT INTO rfc003_swaps (id, swap_id, role, counterparty)
+SELECT id, swap_id, role, counterparty FROM rfc003_swaps_new;
+
+DROP TABLE rfc003_swaps_new;
@@ -0,0 +1,19 @@
+-- Your SQL goes here
+
+-- SQLite cannot add a column defaulting to the current time, hence we copy
+-- the swaps into a new table. Swaps created before are dated to now.
+ALTER TABLE rfc003_swaps RENAME TO rfc003_swaps_old;
+
+CREATE TABLE rfc003_swaps
+(
+    id INTEGER          NOT NULL PRIMARY KEY,
+    swap_id UNIQUE      NOT NULL,
+    role                NOT NULL,
+    counterparty        NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO rfc003_swaps (id, swap_id, role, counterparty)
+SELECT id, swap_id, role, counterparty FROM rfc003_swaps_old;
+
+DROP TABLE rfc003_swaps_old;
@@ -0,0 +1,26 @@
+-- Your SQL goes here
+
+-- SQLite cannot add a column defaulting to the current time, hence we copy
+-- the swaps into a new table. Swaps created before are dated to now.
+ALTER TABLE han_ethereum_halight_bitcoin_swaps RENAME TO han_ethereum_halight_bitcoin_swaps_old;
+
+CREATE TABLE han_ethereum_halight_bitcoin_swaps
+(
+    id INTEGER                  NOT NULL PRIMARY KEY,
+    local_swap_id UNIQUE        NOT NULL,
+    role                        NOT NULL,
+    counterparty_peer_id        NOT NULL,
+    counterparty_address_hint,
+    ethereum_identity           NOT NULL,
+    ethereum_absolute_expiry    NOT NULL,
+    ethereum_amount             NOT NULL,
+    lightning_identity          NOT NULL,
+    lightning_cltv_expiry       NOT NULL,
+    lightning_amount            NOT NULL,
+    created_at DATETIME         NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO han_ethereum_halight_bitcoin_swaps (id, local_swap_id, role, counterparty_peer_id, counterparty_address_hint, ethereum_identity, ethereum_absolute_expiry, ethereum_amount, lightning_identity, lightning_cltv_expiry, lightning_amount)
+SELECT id, local_swap_id, role, counterparty_peer_id, counterparty_address_hint, ethereum_identity, ethereum_absolute_expiry, ethereum_amount, lightning_identity, lightning_cltv_expiry, lightning_amount FROM han_ethereum_halight_bitcoin_swaps_old;
+
+DROP TABLE han_ethereum_halight_bitcoin_swaps_old;
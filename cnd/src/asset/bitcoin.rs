@@ -1,4 +1,5 @@
 use bitcoin::{util::amount::Denomination, Amount};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 
 #[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Hash)]
@@ -24,6 +25,25 @@ impl From<Bitcoin> for Amount {
     }
 }
 
+/// Serialized as a number of satoshis.
+impl Serialize for Bitcoin {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u64(self.as_sat())
+    }
+}
+
+impl<'de> Deserialize<'de> for Bitcoin {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as Deserializer<'de>>::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        u64::deserialize(deserializer).map(Bitcoin::from_sat)
+    }
+}
+
 impl fmt::Display for Bitcoin {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         let bitcoin = self.0.to_string_in(Denomination::Bitcoin);
@@ -0,0 +1,278 @@
+//! The autopilot executes redeem and refund actions of rfc003 swaps on behalf
+//! of the user.
+//!
+//! Without it, these actions are only handed out over the HTTP API and a
+//! client has to execute them in time. The autopilot periodically looks at
+//! the ledger states of all swaps and, using the keys derived from the swap
+//! seed, broadcasts the redeem transaction as soon as the secret is known and
+//! the refund transaction as soon as the expiry has passed.
+//!
+//! A transaction is broadcast again on every poll until the ledger state
+//! shows the HTLC as redeemed or refunded, in case it was dropped from the
+//! mempool before being mined.
+
+use crate::{
+    btsieve::bitcoin::{median_time_past, BroadcastSignedTransaction},
+    config,
+    db::{DetermineTypes, Retrieve},
+    http_api::action::FeeEstimates,
+    seed::Rfc003DeriveSwapSeed,
+    swap_protocols::{
+        actions::{bitcoin::SpendOutput, ethereum::CallContract, Actions},
+        rfc003::{
+            actions::{Action, ActionKind},
+            state::Get,
+            LedgerState, SwapCommunication, SwapId,
+        },
+        Rfc003Facade,
+    },
+    timestamp::Timestamp,
+};
+use async_trait::async_trait;
+use std::{collections::HashSet, time::Duration};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+pub struct Autopilot {
+    facade: Rfc003Facade,
+    settings: config::Autopilot,
+    /// Actions we already broadcast a transaction for, the node rejecting it
+    /// again is expected until the ledger state catches up.
+    executed: HashSet<(SwapId, ActionKind)>,
+    /// Actions we are not able to execute, they are left to the user.
+    unsupported: HashSet<(SwapId, ActionKind)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Execution {
+    Executed,
+    /// The action cannot be executed yet, we will try again later.
+    Pending,
+    Unsupported,
+}
+
+impl Autopilot {
+    pub fn new(facade: Rfc003Facade, settings: config::Autopilot) -> Self {
+        Self {
+            facade,
+            settings,
+            executed: HashSet::new(),
+            unsupported: HashSet::new(),
+        }
+    }
+
+    pub async fn run(mut self) {
+        tracing::info!("Starting autopilot ...");
+
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let swaps = match Retrieve::all(&self.facade).await {
+                Ok(swaps) => swaps,
+                Err(e) => {
+                    tracing::error!("autopilot failed to load swaps: {:?}", e);
+                    continue;
+                }
+            };
+
+            for swap in swaps {
+                if let Err(e) = self.execute_actions(swap.swap_id).await {
+                    tracing::warn!(
+                        "autopilot failed to execute actions of swap {}: {:?}",
+                        swap.swap_id,
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    #[allow(clippy::cognitive_complexity)]
+    async fn execute_actions(&mut self, swap_id: SwapId) -> anyhow::Result<()> {
+        let types = self.facade.determine_types(&swap_id).await?;
+
+        with_swap_types!(types, {
+            let swap_communication: Option<SwapCommunication<AL, BL, AA, BA, AI, BI>> =
+                self.facade.get(&swap_id).await?;
+            let alpha_ledger_state: Option<LedgerState<AA, AH, AT>> =
+                self.facade.alpha_ledger_states.get(&swap_id).await?;
+            let beta_ledger_state: Option<LedgerState<BA, BH, BT>> =
+                self.facade.beta_ledger_states.get(&swap_id).await?;
+
+            let (swap_communication, alpha_ledger_state, beta_ledger_state) =
+                match (swap_communication, alpha_ledger_state, beta_ledger_state) {
+                    (Some(swap_communication), Some(alpha), Some(beta)) => {
+                        (swap_communication, alpha, beta)
+                    }
+                    // The swap has not been accepted (yet), nothing to do.
+                    _ => return Ok(()),
+                };
+
+            let state = RoleState::new(
+                swap_communication,
+                alpha_ledger_state,
+                beta_ledger_state,
+                self.facade.rfc003_derive_swap_seed(swap_id),
+            );
+
+            // The redeem and refund actions are only offered until the ledger
+            // state shows the HTLC as redeemed or refunded.
+            for action in state.actions() {
+                let action_kind = ActionKind::from(&action);
+
+                if self.unsupported.contains(&(swap_id, action_kind)) {
+                    continue;
+                }
+
+                let execution = match action {
+                    Action::Redeem(action) => self.execute(action).await,
+                    Action::Refund(action) => self.execute(action).await,
+                    _ => continue,
+                };
+
+                // A failed action must not hold up the other actions of the
+                // swap, we try it again on the next poll.
+                let execution = match execution {
+                    Ok(execution) => execution,
+                    Err(e) if self.executed.contains(&(swap_id, action_kind)) => {
+                        tracing::debug!(
+                            "autopilot failed to execute {} action of swap {} again: {:#}",
+                            action_kind,
+                            swap_id,
+                            e
+                        );
+                        continue;
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            "autopilot failed to execute {} action of swap {}: {:#}",
+                            action_kind,
+                            swap_id,
+                            e
+                        );
+                        continue;
+                    }
+                };
+
+                match execution {
+                    Execution::Executed => {
+                        if self.executed.insert((swap_id, action_kind)) {
+                            tracing::info!(
+                                "autopilot executed {} action of swap {}",
+                                action_kind,
+                                swap_id
+                            );
+                        }
+                    }
+                    Execution::Pending => {}
+                    Execution::Unsupported => {
+                        tracing::warn!(
+                            "autopilot cannot execute {} action of swap {}, it has to be executed manually",
+                            action_kind,
+                            swap_id
+                        );
+                        self.unsupported.insert((swap_id, action_kind));
+                    }
+                }
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[async_trait]
+trait Execute<A> {
+    async fn execute(&self, action: A) -> anyhow::Result<Execution>;
+}
+
+#[async_trait]
+impl Execute<SpendOutput> for Autopilot {
+    async fn execute(&self, action: SpendOutput) -> anyhow::Result<Execution> {
+        let config::AutopilotBitcoin {
+            address,
+            fee_per_wu,
+        } = &self.settings.bitcoin;
+
+        let fee_per_wu = FeeEstimates::new(self.facade.bitcoin_connector.as_ref())
+            .suggested_fee_per_wu(&action)
+            .await
+            .unwrap_or(*fee_per_wu);
+
+        if address.network != action.network {
+            anyhow::bail!(
+                "configured address {} is not valid on bitcoin network {}",
+                address,
+                action.network
+            )
+        }
+
+        let transaction = action
+            .spend_to(address.clone())
+            .sign_with_rate(&*crate::SECP, fee_per_wu)
+            .map_err(|e| anyhow::anyhow!("failed to sign bitcoin transaction: {:?}", e))?;
+
+        if transaction.lock_time != 0 {
+            let median_time_past = median_time_past(self.facade.bitcoin_connector.as_ref()).await?;
+
+            if !is_final(&transaction, Timestamp::from(median_time_past)) {
+                return Ok(Execution::Pending);
+            }
+        }
+
+        self.facade
+            .bitcoin_connector
+            .broadcast_signed_transaction(transaction)
+            .await?;
+
+        Ok(Execution::Executed)
+    }
+}
+
+#[async_trait]
+impl Execute<CallContract> for Autopilot {
+    async fn execute(&self, _: CallContract) -> anyhow::Result<Execution> {
+        // cnd does not have an Ethereum wallet to pay for the gas.
+        Ok(Execution::Unsupported)
+    }
+}
+
+/// A refund transaction is time-locked to the expiry of the HTLC, bitcoind
+/// will only accept it once the median time of the last blocks is past the
+/// lock time.
+fn is_final(transaction: &bitcoin::Transaction, median_time_past: Timestamp) -> bool {
+    transaction.lock_time == 0 || median_time_past > Timestamp::from(transaction.lock_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction_with_lock_time(lock_time: u32) -> bitcoin::Transaction {
+        bitcoin::Transaction {
+            version: 2,
+            lock_time,
+            input: vec![],
+            output: vec![],
+        }
+    }
+
+    #[test]
+    fn transaction_without_lock_time_is_final() {
+        let transaction = transaction_with_lock_time(0);
+
+        assert!(is_final(&transaction, Timestamp::from(0)));
+    }
+
+    #[test]
+    fn transaction_is_final_only_after_lock_time() {
+        let transaction = transaction_with_lock_time(1_000);
+
+        assert!(!is_final(&transaction, Timestamp::from(999)));
+        assert!(!is_final(&transaction, Timestamp::from(1_000)));
+        assert!(is_final(&transaction, Timestamp::from(1_001)));
+    }
+}
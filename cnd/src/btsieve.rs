@@ -3,12 +3,32 @@
 
 pub mod bitcoin;
 pub mod ethereum;
+mod mempool;
+mod subscription;
+
+pub use self::{
+    mempool::{Mempool, MempoolSubscription, SubscribeMempool},
+    subscription::{AnnounceBlocks, LatestBlocks, SubscribeLatestBlocks, Subscription},
+};
 
 use crate::Never;
 use async_trait::async_trait;
 use chrono::NaiveDateTime;
-use genawaiter::sync::Co;
-use std::{collections::HashSet, hash::Hash};
+use futures::Future;
+use genawaiter::{
+    sync::{Co, Gen},
+    GeneratorState,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    hash::Hash,
+    time::Duration,
+};
+
+/// How often we look for new pending transactions, they are usually
+/// propagated within seconds.
+const PENDING_TRANSACTIONS_POLL_INTERVAL: Duration = Duration::from_secs(1);
 
 #[async_trait]
 pub trait LatestBlock: Send + Sync + 'static {
@@ -44,6 +64,128 @@ pub trait PreviousBlockHash {
     fn previous_block_hash(&self) -> Self::BlockHash;
 }
 
+/// Abstracts over the number of confirmations a transaction needs before we
+/// report it.
+///
+/// A transaction in the latest block has one confirmation, which is all a
+/// connector requires unless configured otherwise.
+pub trait RequiredConfirmations {
+    fn required_confirmations(&self) -> u32 {
+        1
+    }
+}
+
+/// Abstracts over the ability of checking whether a block includes a given
+/// transaction.
+pub trait ContainsTransaction {
+    type TransactionId;
+
+    fn contains_transaction(&self, id: &Self::TransactionId) -> bool;
+}
+
+/// Abstracts over the ability of looking at transactions that are not yet
+/// included in a block, e.g. the ones in the mempool of bitcoind.
+#[async_trait]
+pub trait PendingTransactions: Send + Sync + 'static {
+    type TransactionId;
+    type Transaction;
+
+    async fn pending_transaction_ids(&self) -> anyhow::Result<Vec<Self::TransactionId>>;
+
+    async fn pending_transaction(
+        &self,
+        id: Self::TransactionId,
+    ) -> anyhow::Result<Self::Transaction>;
+}
+
+/// A block whose transactions were all looked at by a watcher.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProcessedBlock {
+    pub hash: String,
+    /// No block from this one on predates this timestamp, hence watching can
+    /// resume from it without missing any of them.
+    pub resume_from: NaiveDateTime,
+}
+
+/// What `find_relevant_blocks` observes while following the blockchain.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChainEvent<B, H> {
+    /// A block that is relevant to the swap.
+    Block(B),
+    /// All blocks from the given tip back to the start of the swap have been
+    /// yielded.
+    Synced(H),
+}
+
+/// The blocks we have seen and how they build on each other.
+///
+/// Together with the latest block this tells us how deep a block is buried
+/// and whether it got orphaned by a reorg.
+#[derive(Clone, Debug)]
+pub struct ChainView<H> {
+    parents: HashMap<H, H>,
+    tip: Option<H>,
+}
+
+impl<H> Default for ChainView<H> {
+    fn default() -> Self {
+        Self {
+            parents: HashMap::new(),
+            tip: None,
+        }
+    }
+}
+
+impl<H> ChainView<H>
+where
+    H: Eq + Hash + Copy,
+{
+    pub fn insert<B>(&mut self, block: &B)
+    where
+        B: BlockHash<BlockHash = H> + PreviousBlockHash<BlockHash = H>,
+    {
+        self.parents
+            .insert(block.block_hash(), block.previous_block_hash());
+    }
+
+    pub fn set_tip(&mut self, tip: H) {
+        self.tip = Some(tip);
+    }
+
+    /// Returns the number of blocks from the tip back to and including the
+    /// given block, `None` if the block is not an ancestor of the tip.
+    pub fn confirmations(&self, block_hash: &H) -> Option<u32> {
+        self.chain()
+            .position(|hash| &hash == block_hash)
+            .map(|depth| {
+                // A chain of more than u32::MAX blocks is not going to fit in memory anyway.
+                #[allow(clippy::cast_possible_truncation)]
+                let depth = depth as u32;
+
+                depth + 1
+            })
+    }
+
+    /// Iterates over the hashes of the known blocks, from the tip back to the
+    /// oldest ancestor we have seen.
+    pub fn chain(&self) -> impl Iterator<Item = H> + '_ {
+        let mut next = self.tip;
+
+        std::iter::from_fn(move || {
+            let current = next?;
+
+            // The parent of the oldest block we have seen is not part of the view.
+            next = self
+                .parents
+                .get(&current)
+                .copied()
+                .filter(|parent| self.parents.contains_key(parent));
+
+            Some(current)
+        })
+    }
+}
+
 /// This function uses the `connector` to find blocks relevant to a swap.  To do
 /// this we must get the latest block, for each latest block we receive we must
 /// ensure that we saw its parent i.e., that we did not miss any blocks between
@@ -52,26 +194,32 @@ pub trait PreviousBlockHash {
 /// look into the past (in case any action occurred on chain while we were not
 /// watching).
 ///
-/// It yields those blocks as part of the process.
+/// It yields those blocks as part of the process, followed by
+/// `ChainEvent::Synced` once it caught up with the latest block.
 pub async fn find_relevant_blocks<C, B, H>(
     connector: &C,
-    co: Co<B>,
+    co: Co<ChainEvent<B, H>>,
     start_of_swap: NaiveDateTime,
 ) -> anyhow::Result<Never>
 where
-    C: LatestBlock<Block = B> + BlockByHash<Block = B, BlockHash = H>,
+    C: SubscribeLatestBlocks<Block = B> + BlockByHash<Block = B, BlockHash = H>,
     B: Predates + BlockHash<BlockHash = H> + PreviousBlockHash<BlockHash = H> + Clone,
     H: Eq + Hash + Copy,
 {
-    let block = connector.latest_block().await?;
+    let mut latest_blocks = connector.subscribe();
+
+    let block = latest_blocks.next().await?;
+    let tip = block.block_hash();
 
     // Look back in time until we get a block that predates start_of_swap.
     let mut seen_blocks =
         walk_back_until(predates_start_of_swap(start_of_swap), block, connector, &co).await?;
+    co.yield_(ChainEvent::Synced(tip)).await;
 
     // Look forward in time, but keep going back for missed blocks
     loop {
-        let block = connector.latest_block().await?;
+        let block = latest_blocks.next().await?;
+        let tip = block.block_hash();
 
         let missed_blocks = walk_back_until(
             seen_block_or_predates_start_of_swap(&seen_blocks, start_of_swap),
@@ -82,9 +230,7 @@ where
         .await?;
 
         seen_blocks.extend(missed_blocks);
-
-        // The duration of this timeout could/should depend on the network
-        tokio::time::delay_for(std::time::Duration::from_secs(1)).await;
+        co.yield_(ChainEvent::Synced(tip)).await;
     }
 }
 
@@ -97,7 +243,7 @@ async fn walk_back_until<C, P, B, H>(
     should_stop_here: P,
     starting_block: B,
     connector: &C,
-    co: &Co<B>,
+    co: &Co<ChainEvent<B, H>>,
 ) -> anyhow::Result<HashSet<H>>
 where
     C: BlockByHash<Block = B, BlockHash = H>,
@@ -119,7 +265,7 @@ where
         let should_stop_here = should_stop_here(&current_block);
 
         // we have to yield the block before exiting
-        co.yield_(current_block).await;
+        co.yield_(ChainEvent::Block(current_block)).await;
 
         if should_stop_here {
             return Ok(seen_blocks);
@@ -129,6 +275,191 @@ where
     }
 }
 
+/// Passes every relevant block through the `sieve` and returns the first match
+/// once its block has the required number of confirmations.
+///
+/// Matches in blocks that get orphaned before they are confirmed are dropped.
+pub async fn find_confirmed<C, B, H, S, F, M>(
+    connector: &C,
+    start_of_swap: NaiveDateTime,
+    mut sieve: S,
+) -> anyhow::Result<M>
+where
+    C: SubscribeLatestBlocks<Block = B>
+        + BlockByHash<Block = B, BlockHash = H>
+        + RequiredConfirmations,
+    B: Predates + BlockHash<BlockHash = H> + PreviousBlockHash<BlockHash = H> + Clone,
+    H: Eq + Hash + Copy + fmt::LowerHex,
+    S: FnMut(B) -> F,
+    F: Future<Output = anyhow::Result<Option<M>>>,
+{
+    let required_confirmations = connector.required_confirmations();
+    let mut block_generator =
+        Gen::new({ |co| async { find_relevant_blocks(connector, co, start_of_swap).await } });
+
+    let mut chain = ChainView::default();
+    let mut matches = HashMap::new();
+
+    loop {
+        match block_generator.async_resume().await {
+            GeneratorState::Yielded(ChainEvent::Block(block)) => {
+                let block_hash = block.block_hash();
+                chain.insert(&block);
+
+                // The latest block is yielded again every time we poll.
+                if matches.contains_key(&block_hash) {
+                    continue;
+                }
+
+                if let Some(found) = sieve(block).await? {
+                    matches.insert(block_hash, found);
+                }
+            }
+            GeneratorState::Yielded(ChainEvent::Synced(tip)) => {
+                chain.set_tip(tip);
+
+                matches.retain(|block_hash, _| {
+                    let orphaned = chain.confirmations(block_hash).is_none();
+                    if orphaned {
+                        tracing::warn!("block {:x} with a match got orphaned", block_hash);
+                    }
+
+                    !orphaned
+                });
+
+                let deepest = matches
+                    .keys()
+                    .filter_map(|block_hash| {
+                        chain
+                            .confirmations(block_hash)
+                            .map(|confirmations| (*block_hash, confirmations))
+                    })
+                    .max_by_key(|(_, confirmations)| *confirmations);
+
+                if let Some((block_hash, confirmations)) = deepest {
+                    if confirmations >= required_confirmations {
+                        return Ok(matches
+                            .remove(&block_hash)
+                            .expect("block hash was taken from the matches"));
+                    }
+
+                    tracing::trace!(
+                        "match has {} of {} confirmations",
+                        confirmations,
+                        required_confirmations
+                    );
+                }
+            }
+            GeneratorState::Complete(Err(e)) => return Err(e),
+            // By matching against the never type explicitly, we assert that the `Ok` value of the
+            // result is actually the never type and has not been changed since this line was
+            // written. The never type can never be constructed, so we can never reach this line.
+            GeneratorState::Complete(Ok(never)) => match never {},
+        }
+    }
+}
+
+/// Walks back from the latest block to the one that includes the given
+/// transaction, `None` if no block since the start of the swap does.
+pub async fn find_including_block<C, B, H, T>(
+    connector: &C,
+    start_of_swap: NaiveDateTime,
+    transaction_id: &T,
+) -> anyhow::Result<Option<B>>
+where
+    C: SubscribeLatestBlocks<Block = B> + BlockByHash<Block = B, BlockHash = H>,
+    B: Predates + PreviousBlockHash<BlockHash = H> + ContainsTransaction<TransactionId = T> + Clone,
+{
+    let mut block = connector.subscribe().next().await?;
+
+    loop {
+        if block.contains_transaction(transaction_id) {
+            return Ok(Some(block));
+        }
+
+        if block.predates(start_of_swap) {
+            return Ok(None);
+        }
+
+        block = connector.block_by_hash(block.previous_block_hash()).await?;
+    }
+}
+
+/// Resolves once the given transaction is no longer included in any block
+/// between the latest block and the start of the swap, i.e. once a reorg
+/// dropped it.
+pub async fn watch_for_retraction<C, B, H, T>(
+    connector: &C,
+    start_of_swap: NaiveDateTime,
+    transaction_id: &T,
+) -> anyhow::Result<()>
+where
+    C: SubscribeLatestBlocks<Block = B> + BlockByHash<Block = B, BlockHash = H>,
+    B: Predates
+        + BlockHash<BlockHash = H>
+        + PreviousBlockHash<BlockHash = H>
+        + ContainsTransaction<TransactionId = T>
+        + Clone,
+    H: Eq + Hash + Copy + fmt::LowerHex,
+{
+    let mut block_generator =
+        Gen::new({ |co| async { find_relevant_blocks(connector, co, start_of_swap).await } });
+
+    let mut chain = ChainView::default();
+    let mut including_blocks = HashSet::new();
+
+    loop {
+        match block_generator.async_resume().await {
+            GeneratorState::Yielded(ChainEvent::Block(block)) => {
+                chain.insert(&block);
+
+                if block.contains_transaction(transaction_id) {
+                    including_blocks.insert(block.block_hash());
+                }
+            }
+            GeneratorState::Yielded(ChainEvent::Synced(tip)) => {
+                chain.set_tip(tip);
+
+                if !chain
+                    .chain()
+                    .any(|block_hash| including_blocks.contains(&block_hash))
+                {
+                    tracing::warn!(
+                        "transaction is no longer part of the chain with tip {:x}",
+                        tip
+                    );
+                    return Ok(());
+                }
+            }
+            GeneratorState::Complete(Err(e)) => return Err(e),
+            GeneratorState::Complete(Ok(never)) => match never {},
+        }
+    }
+}
+
+/// Waits for a pending transaction the sieve matches.
+///
+/// The pending transactions are polled once per ledger and shared between
+/// all watchers, see `Mempool`.
+pub async fn find_pending<C, T, S, M>(connector: &C, mut sieve: S) -> anyhow::Result<M>
+where
+    C: SubscribeMempool<Transaction = T>,
+    S: FnMut(&T) -> Option<M>,
+{
+    let mut mempool = connector.subscribe_mempool();
+
+    loop {
+        let pending_transactions = mempool.next().await?;
+
+        if let Some(found) = pending_transactions
+            .iter()
+            .find_map(|transaction| sieve(transaction.as_ref()))
+        {
+            return Ok(found);
+        }
+    }
+}
+
 /// Constructs a predicate that returns `true` if the given block predates the
 /// start_of_swap timestamp.
 fn predates_start_of_swap<B>(start_of_swap: NaiveDateTime) -> impl Fn(&B) -> bool
@@ -155,3 +486,303 @@ where
         have_seen_block || predates_start_of_swap
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::VecDeque, sync::Mutex, time::Duration};
+
+    #[derive(Clone, Debug)]
+    struct Block {
+        hash: u32,
+        parent: u32,
+        time: i64,
+        transactions: Vec<&'static str>,
+    }
+
+    fn block(hash: u32, parent: u32, transactions: &[&'static str]) -> Block {
+        Block {
+            hash,
+            parent,
+            time: i64::from(hash),
+            transactions: transactions.to_vec(),
+        }
+    }
+
+    /// Predates the start of the swap, hence its parent is never fetched.
+    fn genesis() -> Block {
+        block(0, std::u32::MAX, &[])
+    }
+
+    impl BlockHash for Block {
+        type BlockHash = u32;
+
+        fn block_hash(&self) -> u32 {
+            self.hash
+        }
+    }
+
+    impl PreviousBlockHash for Block {
+        type BlockHash = u32;
+
+        fn previous_block_hash(&self) -> u32 {
+            self.parent
+        }
+    }
+
+    impl Predates for Block {
+        fn predates(&self, timestamp: NaiveDateTime) -> bool {
+            self.time < timestamp.timestamp()
+        }
+    }
+
+    impl ContainsTransaction for Block {
+        type TransactionId = &'static str;
+
+        fn contains_transaction(&self, id: &&'static str) -> bool {
+            self.transactions.contains(id)
+        }
+    }
+
+    struct Connector {
+        latest_blocks: Mutex<VecDeque<Block>>,
+        all_blocks: HashMap<u32, Block>,
+        confirmations: u32,
+    }
+
+    impl Connector {
+        fn new(latest_blocks: &[&Block], all_blocks: &[&Block], confirmations: u32) -> Self {
+            Self {
+                latest_blocks: Mutex::new(latest_blocks.iter().copied().cloned().collect()),
+                all_blocks: all_blocks
+                    .iter()
+                    .map(|block| (block.hash, (*block).clone()))
+                    .collect(),
+                confirmations,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl LatestBlock for Connector {
+        type Block = Block;
+
+        async fn latest_block(&self) -> anyhow::Result<Block> {
+            self.latest_blocks
+                .lock()
+                .unwrap()
+                .pop_front()
+                .ok_or_else(|| anyhow::anyhow!("out of blocks"))
+        }
+    }
+
+    #[async_trait]
+    impl BlockByHash for Connector {
+        type Block = Block;
+        type BlockHash = u32;
+
+        async fn block_by_hash(&self, block_hash: u32) -> anyhow::Result<Block> {
+            self.all_blocks
+                .get(&block_hash)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("unknown block {}", block_hash))
+        }
+    }
+
+    impl RequiredConfirmations for Connector {
+        fn required_confirmations(&self) -> u32 {
+            self.confirmations
+        }
+    }
+
+    impl SubscribeLatestBlocks for Connector {}
+
+    impl AnnounceBlocks for Connector {}
+
+    struct MempoolConnector {
+        polls: Mutex<VecDeque<Vec<&'static str>>>,
+        fetched: Mutex<Vec<&'static str>>,
+    }
+
+    impl MempoolConnector {
+        fn new(polls: Vec<Vec<&'static str>>) -> Self {
+            Self {
+                polls: Mutex::new(polls.into_iter().collect()),
+                fetched: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl PendingTransactions for MempoolConnector {
+        type TransactionId = &'static str;
+        type Transaction = &'static str;
+
+        async fn pending_transaction_ids(&self) -> anyhow::Result<Vec<&'static str>> {
+            self.polls
+                .lock()
+                .unwrap()
+                .pop_front()
+                .ok_or_else(|| anyhow::anyhow!("out of polls"))
+        }
+
+        async fn pending_transaction(&self, id: &'static str) -> anyhow::Result<&'static str> {
+            self.fetched.lock().unwrap().push(id);
+
+            Ok(id)
+        }
+    }
+
+    fn chain_view(blocks: &[Block], tip: u32) -> ChainView<u32> {
+        let mut chain = ChainView::default();
+        for block in blocks {
+            chain.insert(block);
+        }
+        chain.set_tip(tip);
+
+        chain
+    }
+
+    fn start_of_swap() -> NaiveDateTime {
+        NaiveDateTime::from_timestamp(1, 0)
+    }
+
+    async fn find_transaction(connector: &Connector, id: &'static str) -> anyhow::Result<u32> {
+        find_confirmed(connector, start_of_swap(), |block: Block| {
+            let found = if block.contains_transaction(&id) {
+                Some(block.hash)
+            } else {
+                None
+            };
+
+            futures::future::ready(Ok(found))
+        })
+        .await
+    }
+
+    #[test]
+    fn counts_confirmations_from_the_tip() {
+        let chain = chain_view(&[block(3, 2, &[]), block(1, 0, &[]), block(2, 1, &[])], 3);
+
+        assert_eq!(chain.confirmations(&3), Some(1));
+        assert_eq!(chain.confirmations(&2), Some(2));
+        assert_eq!(chain.confirmations(&1), Some(3));
+        assert_eq!(chain.confirmations(&0), None);
+    }
+
+    #[test]
+    fn orphaned_block_has_no_confirmations() {
+        let chain = chain_view(
+            &[
+                block(1, 0, &[]),
+                block(2, 1, &[]),
+                block(12, 1, &[]),
+                block(13, 12, &[]),
+            ],
+            13,
+        );
+
+        assert_eq!(chain.confirmations(&2), None);
+        assert_eq!(chain.confirmations(&1), Some(3));
+        assert_eq!(chain.chain().collect::<Vec<_>>(), vec![13, 12, 1]);
+    }
+
+    #[tokio::test]
+    async fn waits_for_required_confirmations() {
+        let genesis = genesis();
+        let block1 = block(1, 0, &["tx"]);
+        let block2 = block(2, 1, &[]);
+        let connector = Connector::new(
+            &[&block1, &block1, &block2],
+            &[&genesis, &block1, &block2],
+            2,
+        );
+
+        let found = find_transaction(&connector, "tx").await.unwrap();
+
+        assert_eq!(found, 1);
+        assert!(connector.latest_blocks.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn drops_matches_in_orphaned_blocks() {
+        let genesis = genesis();
+        let block1 = block(1, 0, &["tx"]);
+        let fork1 = block(11, 0, &[]);
+        let fork2 = block(12, 11, &["tx"]);
+        let fork3 = block(13, 12, &[]);
+        let connector = Connector::new(
+            &[&block1, &fork1, &fork2, &fork3],
+            &[&genesis, &block1, &fork1, &fork2, &fork3],
+            2,
+        );
+
+        let found = find_transaction(&connector, "tx").await.unwrap();
+
+        assert_eq!(found, 12);
+    }
+
+    #[tokio::test]
+    async fn notices_retracted_transaction() {
+        let genesis = genesis();
+        let block1 = block(1, 0, &["tx"]);
+        let block2 = block(2, 1, &[]);
+        let fork1 = block(11, 0, &[]);
+        let fork2 = block(12, 11, &[]);
+        let fork3 = block(13, 12, &[]);
+        let connector = Connector::new(
+            &[&block2, &fork3],
+            &[&genesis, &block1, &block2, &fork1, &fork2, &fork3],
+            1,
+        );
+
+        let retracted = watch_for_retraction(&connector, start_of_swap(), &"tx").await;
+
+        assert!(retracted.is_ok());
+    }
+
+    #[tokio::test]
+    async fn shares_latest_block_between_subscribers() {
+        let block1 = block(1, 0, &[]);
+        let connector = Connector::new(&[&block1], &[], 1);
+        let latest_blocks = LatestBlocks::default();
+        let mut first = latest_blocks.subscribe();
+        let mut second = latest_blocks.subscribe();
+
+        tokio::select! {
+            _ = latest_blocks.poll(&connector, Duration::from_secs(60)) => unreachable!("polling never stops"),
+            hashes = async { (first.next().await.unwrap().hash, second.next().await.unwrap().hash) } => {
+                assert_eq!(hashes, (1, 1));
+            }
+        }
+
+        assert!(connector.latest_blocks.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn fetches_each_pending_transaction_once() {
+        let connector = MempoolConnector::new(vec![vec!["tx1"], vec!["tx1", "tx2"]]);
+        let mempool = Mempool::default();
+        let matches_tx2 = |transaction: &&'static str| {
+            if *transaction == "tx2" {
+                Some(*transaction)
+            } else {
+                None
+            }
+        };
+
+        tokio::select! {
+            _ = mempool.poll(&connector, Duration::from_millis(1)) => unreachable!("polling never stops"),
+            found = async {
+                futures::try_join!(
+                    find_pending(&mempool, matches_tx2),
+                    find_pending(&mempool, matches_tx2)
+                )
+            } => {
+                assert_eq!(found.unwrap(), ("tx2", "tx2"));
+            }
+        }
+
+        assert_eq!(*connector.fetched.lock().unwrap(), vec!["tx1", "tx2"]);
+    }
+}
@@ -1,27 +1,40 @@
 mod bitcoind_connector;
 mod cache;
+mod connector;
+mod esplora_connector;
+pub mod zmq;
 
 pub use self::{
     bitcoind_connector::{BitcoindConnector, ChainInfo},
     cache::Cache,
+    connector::Connector,
+    esplora_connector::EsploraConnector,
 };
 use crate::{
     btsieve::{
-        find_relevant_blocks, BlockByHash, BlockHash, LatestBlock, Predates, PreviousBlockHash,
+        find_confirmed, find_including_block, find_pending, BlockByHash, BlockHash,
+        ContainsTransaction, LatestBlock, Predates, PreviousBlockHash, ProcessedBlock,
+        RequiredConfirmations, SubscribeLatestBlocks, SubscribeMempool,
     },
     identity,
 };
+use async_trait::async_trait;
 use bitcoin::{
     consensus::{encode::deserialize, Decodable},
+    hashes::Hash as _,
     BitcoinHash, OutPoint,
 };
 use chrono::NaiveDateTime;
-use genawaiter::{sync::Gen, GeneratorState};
+use futures::future;
 use reqwest::{Client, Url};
+use std::time::Duration;
 
 type Hash = bitcoin::BlockHash;
 type Block = bitcoin::Block;
 
+/// The number of blocks the median time past is taken over, see BIP113.
+const MEDIAN_TIME_PAST_BLOCKS: usize = 11;
+
 impl BlockHash for Block {
     type BlockHash = Hash;
 
@@ -38,26 +51,212 @@ impl PreviousBlockHash for Block {
     }
 }
 
+impl ContainsTransaction for Block {
+    type TransactionId = bitcoin::Txid;
+
+    fn contains_transaction(&self, id: &bitcoin::Txid) -> bool {
+        self.txdata
+            .iter()
+            .any(|transaction| &transaction.txid() == id)
+    }
+}
+
+/// How often we ask for the latest block of the given network.
+///
+/// Blocks come every ten minutes on the public networks, but whenever we ask
+/// for one on regtest.
+pub fn poll_interval(network: bitcoin::Network) -> Duration {
+    match network {
+        bitcoin::Network::Bitcoin | bitcoin::Network::Testnet => Duration::from_secs(10),
+        bitcoin::Network::Regtest => Duration::from_secs(1),
+    }
+}
+
+/// The node refused to broadcast a transaction, e.g. because it is invalid or
+/// not final yet.
+#[derive(Debug, Clone, thiserror::Error, PartialEq)]
+#[error("the bitcoin node rejected the transaction: {message}")]
+pub struct TransactionRejected {
+    pub message: String,
+}
+
+#[async_trait]
+pub trait BroadcastSignedTransaction: Send + Sync + 'static {
+    async fn broadcast_signed_transaction(
+        &self,
+        transaction: bitcoin::Transaction,
+    ) -> anyhow::Result<bitcoin::Txid>;
+}
+
+#[async_trait]
+pub trait EstimateFeePerWu: Send + Sync + 'static {
+    /// Whether the backend can estimate fees at all, so we do not even try if
+    /// it cannot.
+    fn can_estimate_fee(&self) -> bool;
+
+    /// The fee in satoshi per weight unit a transaction should pay to be
+    /// confirmed within the given number of blocks.
+    async fn estimate_fee_per_wu(&self, confirmation_target: u16) -> anyhow::Result<usize>;
+}
+
+/// Abstracts over backends that can look up the transactions of an address.
+///
+/// Looking up the HTLC address saves us from walking every block since the
+/// start of the swap, which is slow if every block has to be fetched over
+/// HTTP.
+#[async_trait]
+pub trait TransactionsByAddress: Send + Sync + 'static {
+    fn can_look_up_addresses(&self) -> bool {
+        false
+    }
+
+    /// The transactions paying to or spending from the given address.
+    async fn transactions_by_address(
+        &self,
+        address: &bitcoin::Address,
+    ) -> anyhow::Result<Vec<AddressTransaction>> {
+        anyhow::bail!(
+            "looking up the transactions of {} is not supported",
+            address
+        )
+    }
+}
+
+/// A transaction of an address together with how deep it is buried in the
+/// chain, zero if it is still in the mempool.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AddressTransaction {
+    pub transaction: bitcoin::Transaction,
+    pub confirmations: u32,
+}
+
+/// The median of the times of the latest blocks. The next block only includes
+/// transactions with a lock time below it.
+pub async fn median_time_past<C>(blockchain_connector: &C) -> anyhow::Result<u32>
+where
+    C: LatestBlock<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash>,
+{
+    let mut block = blockchain_connector.latest_block().await?;
+    let mut times = vec![block.header.time];
+
+    // The genesis block has no predecessor, its previous block hash is zero.
+    while times.len() < MEDIAN_TIME_PAST_BLOCKS
+        && block.header.prev_blockhash.into_inner() != [0u8; 32]
+    {
+        block = blockchain_connector
+            .block_by_hash(block.header.prev_blockhash)
+            .await?;
+        times.push(block.header.time);
+    }
+
+    times.sort();
+
+    Ok(times[times.len() / 2])
+}
+
+/// Watches for the transaction spending `from_outpoint`, which belongs to
+/// `address`, with the given identity.
 pub async fn watch_for_spent_outpoint<C>(
     blockchain_connector: &C,
     start_of_swap: NaiveDateTime,
+    address: &bitcoin::Address,
     from_outpoint: OutPoint,
     identity: identity::Bitcoin,
 ) -> anyhow::Result<(bitcoin::Transaction, bitcoin::TxIn)>
 where
-    C: LatestBlock<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash>,
+    C: SubscribeLatestBlocks<Block = Block>
+        + BlockByHash<Block = Block, BlockHash = Hash>
+        + TransactionsByAddress
+        + RequiredConfirmations,
 {
-    let (transaction, txin) = watch(blockchain_connector, start_of_swap, |transaction| {
+    let sieve = spends_outpoint(from_outpoint, identity);
+
+    let (transaction, txin) = if blockchain_connector.can_look_up_addresses() {
+        watch_address(blockchain_connector, address, sieve).await?
+    } else {
+        watch(blockchain_connector, start_of_swap, sieve).await?
+    };
+
+    Ok((transaction, txin))
+}
+
+/// Finds the block that includes the given transaction, once it is processed
+/// watching can resume from it.
+///
+/// The time of a block only has to be later than the median time of the
+/// blocks before it. Hence no block from the including one on predates the
+/// earliest time among these.
+pub async fn processed_block<C>(
+    blockchain_connector: &C,
+    start_of_swap: NaiveDateTime,
+    txid: bitcoin::Txid,
+) -> anyhow::Result<Option<ProcessedBlock>>
+where
+    C: SubscribeLatestBlocks<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash>,
+{
+    let including_block =
+        match find_including_block(blockchain_connector, start_of_swap, &txid).await? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+
+    let mut block = including_block.clone();
+    let mut earliest = block.header.time;
+
+    // The genesis block has no predecessor, its previous block hash is zero.
+    for _ in 0..MEDIAN_TIME_PAST_BLOCKS {
+        if block.header.prev_blockhash.into_inner() == [0u8; 32] {
+            break;
+        }
+
+        block = blockchain_connector
+            .block_by_hash(block.header.prev_blockhash)
+            .await?;
+        earliest = std::cmp::min(earliest, block.header.time);
+    }
+
+    Ok(Some(ProcessedBlock {
+        hash: including_block.bitcoin_hash().to_string(),
+        resume_from: NaiveDateTime::from_timestamp(i64::from(earliest), 0),
+    }))
+}
+
+/// Like `watch_for_spent_outpoint` but looks at the transactions in the
+/// mempool instead of the ones in confirmed blocks.
+pub async fn watch_mempool_for_spent_outpoint<C>(
+    blockchain_connector: &C,
+    from_outpoint: OutPoint,
+    identity: identity::Bitcoin,
+) -> anyhow::Result<(bitcoin::Transaction, bitcoin::TxIn)>
+where
+    C: SubscribeMempool<Transaction = bitcoin::Transaction>,
+{
+    let sieve = spends_outpoint(from_outpoint, identity);
+
+    find_pending(
+        blockchain_connector,
+        |transaction: &bitcoin::Transaction| {
+            let txin = sieve(transaction)?;
+            tracing::trace!("mempool transaction matched {:x}", transaction.txid());
+
+            Some((transaction.clone(), txin))
+        },
+    )
+    .await
+}
+
+fn spends_outpoint(
+    from_outpoint: OutPoint,
+    identity: identity::Bitcoin,
+) -> impl Fn(&bitcoin::Transaction) -> Option<bitcoin::TxIn> {
+    move |transaction| {
         transaction
             .input
             .iter()
             .filter(|txin| txin.previous_output == from_outpoint)
             .find(|txin| txin.witness.contains(&identity.to_bytes()))
             .cloned()
-    })
-    .await?;
-
-    Ok((transaction, txin))
+    }
 }
 
 pub async fn watch_for_created_outpoint<C>(
@@ -66,9 +265,28 @@ pub async fn watch_for_created_outpoint<C>(
     compute_address: bitcoin::Address,
 ) -> anyhow::Result<(bitcoin::Transaction, bitcoin::OutPoint)>
 where
-    C: LatestBlock<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash>,
+    C: SubscribeLatestBlocks<Block = Block>
+        + BlockByHash<Block = Block, BlockHash = Hash>
+        + TransactionsByAddress
+        + RequiredConfirmations,
 {
-    let (transaction, out_point) = watch(blockchain_connector, start_of_swap, |transaction| {
+    let sieve = creates_outpoint(&compute_address);
+
+    let (transaction, out_point) = if blockchain_connector.can_look_up_addresses() {
+        watch_address(blockchain_connector, &compute_address, sieve).await?
+    } else {
+        watch(blockchain_connector, start_of_swap, sieve).await?
+    };
+
+    Ok((transaction, out_point))
+}
+
+fn creates_outpoint(
+    address: &bitcoin::Address,
+) -> impl Fn(&bitcoin::Transaction) -> Option<OutPoint> {
+    let script_pubkey = address.script_pubkey();
+
+    move |transaction| {
         let txid = transaction.txid();
         transaction
             .output
@@ -81,12 +299,9 @@ where
                 #[allow(clippy::cast_possible_truncation)]
                 (index as u32, txout)
             })
-            .find(|(_, txout)| txout.script_pubkey == compute_address.script_pubkey())
+            .find(|(_, txout)| txout.script_pubkey == script_pubkey)
             .map(|(vout, _txout)| OutPoint { txid, vout })
-    })
-    .await?;
-
-    Ok((transaction, out_point))
+    }
 }
 
 async fn watch<C, S, M>(
@@ -95,27 +310,64 @@ async fn watch<C, S, M>(
     sieve: S,
 ) -> anyhow::Result<(bitcoin::Transaction, M)>
 where
-    C: LatestBlock<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash>,
+    C: SubscribeLatestBlocks<Block = Block>
+        + BlockByHash<Block = Block, BlockHash = Hash>
+        + RequiredConfirmations,
+    S: Fn(&bitcoin::Transaction) -> Option<M>,
+{
+    find_confirmed(connector, start_of_swap, |block: Block| {
+        let found = block.txdata.into_iter().find_map(|transaction| {
+            let result = sieve(&transaction)?;
+            tracing::trace!("transaction matched {:x}", transaction.txid());
+
+            Some((transaction, result))
+        });
+
+        future::ready(Ok(found))
+    })
+    .await
+}
+
+/// Like `watch` but looks at the transactions of the given address instead of
+/// every block since the start of the swap. Esplora for example only returns
+/// the latest transactions of an address, which is plenty for an HTLC.
+async fn watch_address<C, S, M>(
+    connector: &C,
+    address: &bitcoin::Address,
+    sieve: S,
+) -> anyhow::Result<(bitcoin::Transaction, M)>
+where
+    C: SubscribeLatestBlocks<Block = Block> + TransactionsByAddress + RequiredConfirmations,
     S: Fn(&bitcoin::Transaction) -> Option<M>,
 {
-    let mut block_generator =
-        Gen::new({ |co| async { find_relevant_blocks(connector, co, start_of_swap).await } });
+    let required_confirmations = connector.required_confirmations();
+    let mut latest_blocks = connector.subscribe();
 
     loop {
-        match block_generator.async_resume().await {
-            GeneratorState::Yielded(block) => {
-                for transaction in block.txdata.into_iter() {
-                    if let Some(result) = sieve(&transaction) {
-                        tracing::trace!("transaction matched {:x}", transaction.txid());
-                        return Ok((transaction, result));
-                    }
-                }
+        // The confirmations of a match only change with a new block.
+        latest_blocks.next().await?;
+
+        for AddressTransaction {
+            transaction,
+            confirmations,
+        } in connector.transactions_by_address(address).await?
+        {
+            let found = match sieve(&transaction) {
+                Some(found) => found,
+                None => continue,
+            };
+
+            if confirmations < required_confirmations {
+                tracing::trace!(
+                    "match has {} of {} confirmations",
+                    confirmations,
+                    required_confirmations
+                );
+                continue;
             }
-            GeneratorState::Complete(Err(e)) => return Err(e),
-            // By matching against the never type explicitly, we assert that the `Ok` value of the
-            // result is actually the never type and has not been changed since this line was
-            // written. The never type can never be constructed, so we can never reach this line.
-            GeneratorState::Complete(Ok(never)) => match never {},
+
+            tracing::trace!("transaction matched {:x}", transaction.txid());
+            return Ok((transaction, found));
         }
     }
 }
@@ -166,8 +418,80 @@ where
 mod tests {
     use super::*;
     use crate::transaction;
+    use bitcoin::{hashes::Hash as _, BlockHeader, TxMerkleNode};
     use spectral::prelude::*;
 
+    struct Chain(Vec<Block>);
+
+    impl Chain {
+        fn with_block_times(times: &[u32]) -> Self {
+            let mut blocks: Vec<Block> = Vec::new();
+
+            for time in times {
+                let prev_blockhash = blocks
+                    .last()
+                    .map(|block| block.bitcoin_hash())
+                    .unwrap_or_else(|| Hash::from_inner([0u8; 32]));
+
+                blocks.push(Block {
+                    header: BlockHeader {
+                        version: 1,
+                        prev_blockhash,
+                        merkle_root: TxMerkleNode::from_inner([0u8; 32]),
+                        time: *time,
+                        bits: 0,
+                        nonce: 0,
+                    },
+                    txdata: vec![],
+                });
+            }
+
+            Chain(blocks)
+        }
+    }
+
+    #[async_trait]
+    impl LatestBlock for Chain {
+        type Block = Block;
+
+        async fn latest_block(&self) -> anyhow::Result<Block> {
+            Ok(self.0.last().cloned().expect("chain is not empty"))
+        }
+    }
+
+    #[async_trait]
+    impl BlockByHash for Chain {
+        type Block = Block;
+        type BlockHash = Hash;
+
+        async fn block_by_hash(&self, block_hash: Hash) -> anyhow::Result<Block> {
+            self.0
+                .iter()
+                .find(|block| block.bitcoin_hash() == block_hash)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("unknown block {}", block_hash))
+        }
+    }
+
+    #[tokio::test]
+    async fn median_time_past_is_median_of_last_eleven_blocks() {
+        let times = (1..=15).map(|i| i * 100).collect::<Vec<_>>();
+        let chain = Chain::with_block_times(&times);
+
+        let median_time_past = median_time_past(&chain).await.unwrap();
+
+        assert_eq!(median_time_past, 1000);
+    }
+
+    #[tokio::test]
+    async fn median_time_past_stops_at_genesis_block() {
+        let chain = Chain::with_block_times(&[300, 100, 200]);
+
+        let median_time_past = median_time_past(&chain).await.unwrap();
+
+        assert_eq!(median_time_past, 200);
+    }
+
     #[test]
     fn can_decode_tx_from_bitcoind_http_interface() {
         // the line break here is on purpose, as it is returned like that from bitcoind
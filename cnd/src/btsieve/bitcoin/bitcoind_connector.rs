@@ -1,11 +1,21 @@
 use crate::{
-    btsieve::{bitcoin::bitcoin_http_request_for_hex_encoded_object, BlockByHash, LatestBlock},
+    btsieve::{
+        bitcoin::{
+            bitcoin_http_request_for_hex_encoded_object, decode_response, poll_interval, zmq,
+            BroadcastSignedTransaction, EstimateFeePerWu, TransactionsByAddress,
+        },
+        AnnounceBlocks, BlockByHash, LatestBlock, PendingTransactions, RequiredConfirmations,
+        SubscribeLatestBlocks,
+    },
     config::validation::FetchNetworkId,
+    jsonrpc,
 };
 use async_trait::async_trait;
-use bitcoin::{BlockHash, Network};
+use bitcoin::{Amount, BlockHash, Network, Transaction, Txid};
+use futures::stream::{BoxStream, StreamExt};
 use reqwest::{Client, Url};
 use serde::{de, export::fmt, Deserialize, Deserializer};
+use std::{convert::TryFrom, time::Duration};
 
 #[derive(Copy, Clone, Debug, Deserialize)]
 pub struct ChainInfo {
@@ -14,22 +24,47 @@ pub struct ChainInfo {
     pub chain: Network,
 }
 
+/// The response of bitcoind's `estimatesmartfee`.
+#[derive(Clone, Debug, Deserialize)]
+struct SmartFee {
+    /// In BTC per 1000 virtual bytes, missing if bitcoind has not seen enough
+    /// transactions to estimate it.
+    feerate: Option<f64>,
+    #[serde(default)]
+    errors: Vec<String>,
+}
+
 #[derive(Debug)]
 pub struct BitcoindConnector {
     chaininfo_url: Url,
     raw_block_by_hash_url: Url,
     client: Client,
+    rpc_client: jsonrpc::Client,
+    network: Network,
+    zmqpubhashblock: Option<Url>,
 }
 
 impl BitcoindConnector {
-    pub fn new(base_url: Url, _network: Network) -> anyhow::Result<Self> {
+    pub fn new(base_url: Url, network: Network) -> anyhow::Result<Self> {
         Ok(Self {
             chaininfo_url: base_url.join("rest/chaininfo.json")?,
             raw_block_by_hash_url: base_url.join("rest/block/")?,
             client: Client::new(),
+            rpc_client: jsonrpc::Client::new(base_url),
+            network,
+            zmqpubhashblock: None,
         })
     }
 
+    /// Subscribes to the blocks bitcoind publishes on its `-zmqpubhashblock`
+    /// endpoint instead of only learning about them by polling.
+    pub fn with_block_notifications(self, zmqpubhashblock: Url) -> Self {
+        Self {
+            zmqpubhashblock: Some(zmqpubhashblock),
+            ..self
+        }
+    }
+
     fn raw_block_by_hash_url(&self, block_hash: &BlockHash) -> Url {
         self.raw_block_by_hash_url
             .join(&format!("{}.hex", block_hash))
@@ -37,6 +72,25 @@ impl BitcoindConnector {
     }
 }
 
+impl RequiredConfirmations for BitcoindConnector {}
+
+impl TransactionsByAddress for BitcoindConnector {}
+
+impl SubscribeLatestBlocks for BitcoindConnector {
+    fn poll_interval(&self) -> Duration {
+        poll_interval(self.network)
+    }
+}
+
+impl AnnounceBlocks for BitcoindConnector {
+    fn block_announcements(&self) -> BoxStream<'static, ()> {
+        match &self.zmqpubhashblock {
+            Some(endpoint) => zmq::block_announcements(endpoint.clone()).boxed(),
+            None => futures::stream::pending().boxed(),
+        }
+    }
+}
+
 #[async_trait]
 impl LatestBlock for BitcoindConnector {
     type Block = bitcoin::Block;
@@ -78,6 +132,96 @@ impl BlockByHash for BitcoindConnector {
     }
 }
 
+#[async_trait]
+impl BroadcastSignedTransaction for BitcoindConnector {
+    async fn broadcast_signed_transaction(&self, transaction: Transaction) -> anyhow::Result<Txid> {
+        let txid = self
+            .rpc_client
+            .send(jsonrpc::Request::new("sendrawtransaction", vec![
+                jsonrpc::serialize(bitcoin::consensus::encode::serialize_hex(&transaction))?,
+            ]))
+            .await?;
+
+        tracing::debug!("Broadcasted transaction {} through bitcoind", txid);
+
+        Ok(txid)
+    }
+}
+
+#[async_trait]
+impl EstimateFeePerWu for BitcoindConnector {
+    fn can_estimate_fee(&self) -> bool {
+        true
+    }
+
+    async fn estimate_fee_per_wu(&self, confirmation_target: u16) -> anyhow::Result<usize> {
+        let smart_fee: SmartFee = self
+            .rpc_client
+            .send(jsonrpc::Request::new("estimatesmartfee", vec![
+                jsonrpc::serialize(confirmation_target)?,
+            ]))
+            .await?;
+
+        let feerate = smart_fee.feerate.ok_or_else(|| {
+            anyhow::anyhow!(
+                "bitcoind could not estimate a fee: {}",
+                smart_fee.errors.join(", ")
+            )
+        })?;
+        let fee_per_wu = fee_per_wu(Amount::from_btc(feerate)?)?;
+
+        tracing::debug!(
+            "Estimated {} sat/WU for confirmation within {} blocks through bitcoind",
+            fee_per_wu,
+            confirmation_target
+        );
+
+        Ok(fee_per_wu)
+    }
+}
+
+/// A virtual byte is four weight units. We round up, a transaction paying
+/// less than estimated might not be confirmed in time.
+fn fee_per_wu(fee_per_kvb: Amount) -> anyhow::Result<usize> {
+    let fee_per_wu = (fee_per_kvb.as_sat() + 3999) / 4000;
+
+    Ok(usize::try_from(fee_per_wu.max(1))?)
+}
+
+#[async_trait]
+impl PendingTransactions for BitcoindConnector {
+    type TransactionId = Txid;
+    type Transaction = Transaction;
+
+    async fn pending_transaction_ids(&self) -> anyhow::Result<Vec<Txid>> {
+        let txids: Vec<Txid> = self
+            .rpc_client
+            .send::<Vec<()>, _>(jsonrpc::Request::new("getrawmempool", vec![]))
+            .await?;
+
+        tracing::trace!(
+            "Fetched {} mempool transaction ids from bitcoind",
+            txids.len()
+        );
+
+        Ok(txids)
+    }
+
+    async fn pending_transaction(&self, txid: Txid) -> anyhow::Result<Transaction> {
+        let hex: String = self
+            .rpc_client
+            .send(jsonrpc::Request::new("getrawtransaction", vec![
+                jsonrpc::serialize(txid)?,
+            ]))
+            .await?;
+        let transaction = decode_response(hex)?;
+
+        tracing::trace!("Fetched mempool transaction {} from bitcoind", txid);
+
+        Ok(transaction)
+    }
+}
+
 #[async_trait]
 impl FetchNetworkId<Network> for BitcoindConnector {
     async fn network_id(&self) -> anyhow::Result<Network> {
@@ -131,7 +275,52 @@ mod tests {
 
     use super::*;
     use crate::quickcheck::Quickcheck;
-    use bitcoin::hashes::sha256d;
+    use bitcoin::{
+        hashes::{sha256d, Hash},
+        OutPoint, Script, TxIn,
+    };
+    use warp::Filter;
+
+    /// Serves the given JSON-RPC response body for every request, standing in
+    /// for bitcoind. Returns the url of the server and the params of the last
+    /// request.
+    fn stub_bitcoind(
+        status: warp::http::StatusCode,
+        response: serde_json::Value,
+    ) -> (
+        Url,
+        std::sync::Arc<std::sync::Mutex<Option<serde_json::Value>>>,
+    ) {
+        let received = std::sync::Arc::new(std::sync::Mutex::new(None));
+        let received_by_route = received.clone();
+
+        let route = warp::post()
+            .and(warp::path::end())
+            .and(warp::body::json())
+            .map(move |request: serde_json::Value| {
+                *received_by_route.lock().unwrap() = Some(request);
+
+                warp::reply::with_status(warp::reply::json(&response), status)
+            });
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        (format!("http://{}", addr).parse().unwrap(), received)
+    }
+
+    fn transaction() -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: Script::new(),
+                sequence: 0xFFFF_FFFF,
+                witness: vec![],
+            }],
+            output: vec![],
+        }
+    }
 
     fn base_urls() -> Vec<Url> {
         vec![
@@ -212,4 +401,128 @@ mod tests {
         let info = serde_json::from_str::<ChainInfo>(chain_info).unwrap();
         assert_eq!(info.chain, Network::Regtest);
     }
+
+    #[tokio::test]
+    async fn broadcast_signed_transaction_sends_hex_encoded_transaction() {
+        let transaction = transaction();
+        let txid = transaction.txid();
+        let (url, received) = stub_bitcoind(
+            warp::http::StatusCode::OK,
+            serde_json::json!({ "result": txid, "error": null, "id": "1" }),
+        );
+        let connector = BitcoindConnector::new(url, Network::Regtest).unwrap();
+
+        let broadcasted = connector
+            .broadcast_signed_transaction(transaction.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(broadcasted, txid);
+        let request = received.lock().unwrap().take().unwrap();
+        assert_eq!(request["method"], "sendrawtransaction");
+        assert_eq!(
+            request["params"][0],
+            bitcoin::consensus::encode::serialize_hex(&transaction)
+        );
+    }
+
+    #[tokio::test]
+    async fn broadcast_signed_transaction_fails_if_bitcoind_rejects_it() {
+        let (url, _) = stub_bitcoind(
+            warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+            serde_json::json!({
+                "result": null,
+                "error": { "code": -26, "message": "non-final (code 64)" },
+                "id": "1"
+            }),
+        );
+        let connector = BitcoindConnector::new(url, Network::Regtest).unwrap();
+
+        let error = connector
+            .broadcast_signed_transaction(transaction())
+            .await
+            .unwrap_err();
+
+        match error.downcast::<jsonrpc::Error>() {
+            Ok(jsonrpc::Error::JsonRpc { code, .. }) => assert_eq!(code, -26),
+            other => panic!("expected json-rpc error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn pending_transaction_decodes_hex_from_bitcoind() {
+        let transaction = transaction();
+        let (url, received) = stub_bitcoind(
+            warp::http::StatusCode::OK,
+            serde_json::json!({
+                "result": bitcoin::consensus::encode::serialize_hex(&transaction),
+                "error": null,
+                "id": "1"
+            }),
+        );
+        let connector = BitcoindConnector::new(url, Network::Regtest).unwrap();
+
+        let pending = connector
+            .pending_transaction(transaction.txid())
+            .await
+            .unwrap();
+
+        assert_eq!(pending, transaction);
+        let request = received.lock().unwrap().take().unwrap();
+        assert_eq!(request["method"], "getrawtransaction");
+        assert_eq!(request["params"][0], transaction.txid().to_string());
+    }
+
+    #[tokio::test]
+    async fn estimate_fee_per_wu_converts_bitcoind_feerate() {
+        let (url, received) = stub_bitcoind(
+            warp::http::StatusCode::OK,
+            serde_json::json!({
+                "result": { "feerate": 0.0002, "blocks": 6 },
+                "error": null,
+                "id": "1"
+            }),
+        );
+        let connector = BitcoindConnector::new(url, Network::Regtest).unwrap();
+
+        let fee_per_wu = connector.estimate_fee_per_wu(6).await.unwrap();
+
+        assert_eq!(fee_per_wu, 5);
+        let request = received.lock().unwrap().take().unwrap();
+        assert_eq!(request["method"], "estimatesmartfee");
+        assert_eq!(request["params"][0], 6);
+    }
+
+    #[tokio::test]
+    async fn estimate_fee_per_wu_fails_without_feerate() {
+        let (url, _) = stub_bitcoind(
+            warp::http::StatusCode::OK,
+            serde_json::json!({
+                "result": { "errors": ["Insufficient data or no feerate found"], "blocks": 0 },
+                "error": null,
+                "id": "1"
+            }),
+        );
+        let connector = BitcoindConnector::new(url, Network::Regtest).unwrap();
+
+        let result = connector.estimate_fee_per_wu(6).await;
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn fee_per_wu_rounds_up() {
+        assert_eq!(fee_per_wu(Amount::from_sat(20_000)).unwrap(), 5);
+        assert_eq!(fee_per_wu(Amount::from_sat(20_001)).unwrap(), 6);
+        assert_eq!(fee_per_wu(Amount::from_sat(0)).unwrap(), 1);
+    }
+
+    #[test]
+    fn txid_deserializes_from_bitcoind_response() {
+        let txid = Txid::from_slice(&[1u8; 32]).unwrap();
+
+        let deserialized = serde_json::from_value::<Txid>(serde_json::json!(txid.to_string()));
+
+        assert_eq!(deserialized.unwrap(), txid);
+    }
 }
@@ -1,9 +1,24 @@
-use crate::btsieve::{BlockByHash, LatestBlock};
+use crate::{
+    btsieve::{
+        bitcoin::{
+            AddressTransaction, BroadcastSignedTransaction, EstimateFeePerWu, TransactionsByAddress,
+        },
+        AnnounceBlocks, BlockByHash, LatestBlock, LatestBlocks, Mempool, MempoolSubscription,
+        PendingTransactions, RequiredConfirmations, SubscribeLatestBlocks, SubscribeMempool,
+        Subscription, PENDING_TRANSACTIONS_POLL_INTERVAL,
+    },
+    db::{BlockStore, Item},
+};
 use async_trait::async_trait;
-use bitcoin::{util::hash::BitcoinHash, Block, BlockHash as Hash, BlockHash};
+use bitcoin::{
+    consensus::encode::{deserialize, serialize},
+    util::hash::BitcoinHash,
+    Address, Block, BlockHash as Hash, BlockHash, Transaction, Txid,
+};
 use derivative::Derivative;
+use futures::stream::BoxStream;
 use lru::LruCache;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 use tokio::sync::Mutex;
 
 #[derive(Derivative, Clone)]
@@ -12,6 +27,13 @@ pub struct Cache<C> {
     pub connector: C,
     #[derivative(Debug = "ignore")]
     pub block_cache: Arc<Mutex<LruCache<BlockHash, Block>>>,
+    pub confirmations: u32,
+    pub watch_mempool: bool,
+    #[derivative(Debug = "ignore")]
+    pub latest_blocks: Arc<LatestBlocks<Block>>,
+    #[derivative(Debug = "ignore")]
+    pub mempool: Arc<Mempool<Transaction>>,
+    pub block_store: Option<BlockStore>,
 }
 
 impl<C> Cache<C> {
@@ -20,10 +42,139 @@ impl<C> Cache<C> {
         Cache {
             connector,
             block_cache,
+            confirmations: 1,
+            watch_mempool: false,
+            latest_blocks: Arc::new(LatestBlocks::default()),
+            mempool: Arc::new(Mempool::default()),
+            block_store: None,
+        }
+    }
+
+    pub fn with_confirmations(self, confirmations: u32) -> Self {
+        Self {
+            confirmations,
+            ..self
+        }
+    }
+
+    pub fn with_mempool_watching(self, watch_mempool: bool) -> Self {
+        Self {
+            watch_mempool,
+            ..self
+        }
+    }
+
+    /// Falls back to the blocks stored in the database before asking the
+    /// connector, blocks fetched from the connector are stored there too.
+    pub fn with_block_store(self, block_store: Option<BlockStore>) -> Self {
+        Self {
+            block_store,
+            ..self
+        }
+    }
+
+    async fn load_from_store(&self, block_hash: &BlockHash) -> Option<Block> {
+        let store = self.block_store.as_ref()?;
+
+        match load_block(store, block_hash).await {
+            Ok(block) => block,
+            Err(e) => {
+                tracing::warn!(
+                    "failed to load block {} from the store: {:#}",
+                    block_hash,
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    async fn save_to_store(&self, block: &Block) {
+        if let Some(store) = self.block_store.as_ref() {
+            let block_hash = block.bitcoin_hash();
+
+            if let Err(e) = store
+                .save(
+                    Item::BitcoinBlock,
+                    &block_hash.to_string(),
+                    serialize(block),
+                )
+                .await
+            {
+                tracing::warn!("failed to save block {} to the store: {:#}", block_hash, e)
+            }
         }
     }
 }
 
+async fn load_block(store: &BlockStore, block_hash: &BlockHash) -> anyhow::Result<Option<Block>> {
+    let block = match store
+        .load(Item::BitcoinBlock, &block_hash.to_string())
+        .await?
+    {
+        Some(bytes) => Some(deserialize(&bytes)?),
+        None => None,
+    };
+
+    Ok(block)
+}
+
+impl<C> Cache<C>
+where
+    C: LatestBlock<Block = Block> + AnnounceBlocks,
+{
+    /// Polls the latest block once per `interval` on behalf of all swaps
+    /// watching this ledger.
+    pub async fn poll_latest_blocks(self: Arc<Self>, interval: Duration) {
+        self.latest_blocks.poll(&*self, interval).await
+    }
+}
+
+impl<C> Cache<C>
+where
+    C: PendingTransactions<TransactionId = Txid, Transaction = Transaction>,
+{
+    /// Polls the pending transactions on behalf of all swaps watching this
+    /// ledger, only needed if mempool watching is enabled.
+    pub async fn poll_mempool(self: Arc<Self>) {
+        self.mempool
+            .poll(&self.connector, PENDING_TRANSACTIONS_POLL_INTERVAL)
+            .await
+    }
+}
+
+impl<C> SubscribeMempool for Cache<C> {
+    type Transaction = Transaction;
+
+    fn subscribe_mempool(&self) -> MempoolSubscription<Transaction> {
+        self.mempool.subscribe()
+    }
+}
+
+impl<C> AnnounceBlocks for Cache<C>
+where
+    C: AnnounceBlocks,
+{
+    fn block_announcements(&self) -> BoxStream<'static, ()> {
+        self.connector.block_announcements()
+    }
+}
+
+impl<C> RequiredConfirmations for Cache<C> {
+    fn required_confirmations(&self) -> u32 {
+        self.confirmations
+    }
+}
+
+impl<C> SubscribeLatestBlocks for Cache<C>
+where
+    C: LatestBlock<Block = Block>,
+{
+    fn subscribe(&self) -> Subscription<'_, Block> {
+        self.latest_blocks.subscribe()
+    }
+}
+
 #[async_trait]
 impl<C> LatestBlock for Cache<C>
 where
@@ -35,9 +186,18 @@ where
         let block = self.connector.latest_block().await?;
 
         let block_hash = block.bitcoin_hash();
-        let mut guard = self.block_cache.lock().await;
-        if !guard.contains(&block_hash) {
-            guard.put(block_hash, block.clone());
+        let is_new = {
+            let mut guard = self.block_cache.lock().await;
+            let is_new = !guard.contains(&block_hash);
+            if is_new {
+                guard.put(block_hash, block.clone());
+            }
+
+            is_new
+        };
+
+        if is_new {
+            self.save_to_store(&block).await;
         }
 
         Ok(block)
@@ -58,8 +218,15 @@ where
             return Ok(block.clone());
         }
 
+        if let Some(block) = self.load_from_store(&block_hash).await {
+            tracing::trace!("Found block in store: {:x}", block_hash);
+            self.block_cache.lock().await.put(block_hash, block.clone());
+            return Ok(block);
+        }
+
         let block = self.connector.block_by_hash(block_hash.clone()).await?;
         tracing::trace!("Fetched block from connector: {:x}", block_hash);
+        self.save_to_store(&block).await;
 
         // We dropped the lock so at this stage the block may have been inserted by
         // another thread, no worries, inserting the same block twice does not hurt.
@@ -69,3 +236,48 @@ where
         Ok(block)
     }
 }
+
+#[async_trait]
+impl<C> BroadcastSignedTransaction for Cache<C>
+where
+    C: BroadcastSignedTransaction,
+{
+    async fn broadcast_signed_transaction(&self, transaction: Transaction) -> anyhow::Result<Txid> {
+        self.connector
+            .broadcast_signed_transaction(transaction)
+            .await
+    }
+}
+
+#[async_trait]
+impl<C> EstimateFeePerWu for Cache<C>
+where
+    C: EstimateFeePerWu,
+{
+    fn can_estimate_fee(&self) -> bool {
+        self.connector.can_estimate_fee()
+    }
+
+    async fn estimate_fee_per_wu(&self, confirmation_target: u16) -> anyhow::Result<usize> {
+        self.connector
+            .estimate_fee_per_wu(confirmation_target)
+            .await
+    }
+}
+
+#[async_trait]
+impl<C> TransactionsByAddress for Cache<C>
+where
+    C: TransactionsByAddress,
+{
+    fn can_look_up_addresses(&self) -> bool {
+        self.connector.can_look_up_addresses()
+    }
+
+    async fn transactions_by_address(
+        &self,
+        address: &Address,
+    ) -> anyhow::Result<Vec<AddressTransaction>> {
+        self.connector.transactions_by_address(address).await
+    }
+}
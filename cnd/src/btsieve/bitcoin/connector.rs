@@ -0,0 +1,164 @@
+use crate::{
+    btsieve::{
+        bitcoin::{
+            AddressTransaction, BitcoindConnector, BroadcastSignedTransaction, EsploraConnector,
+            EstimateFeePerWu, TransactionsByAddress,
+        },
+        AnnounceBlocks, BlockByHash, LatestBlock, PendingTransactions, RequiredConfirmations,
+        SubscribeLatestBlocks,
+    },
+    config::validation::FetchNetworkId,
+};
+use async_trait::async_trait;
+use bitcoin::{Address, Block, BlockHash, Network, Transaction, Txid};
+use futures::stream::BoxStream;
+use std::time::Duration;
+
+/// The Bitcoin backend selected in the configuration.
+#[derive(Debug)]
+pub enum Connector {
+    Bitcoind(BitcoindConnector),
+    Esplora(EsploraConnector),
+}
+
+impl From<BitcoindConnector> for Connector {
+    fn from(connector: BitcoindConnector) -> Self {
+        Connector::Bitcoind(connector)
+    }
+}
+
+impl From<EsploraConnector> for Connector {
+    fn from(connector: EsploraConnector) -> Self {
+        Connector::Esplora(connector)
+    }
+}
+
+impl RequiredConfirmations for Connector {}
+
+impl SubscribeLatestBlocks for Connector {
+    fn poll_interval(&self) -> Duration {
+        match self {
+            Connector::Bitcoind(connector) => connector.poll_interval(),
+            Connector::Esplora(connector) => connector.poll_interval(),
+        }
+    }
+}
+
+impl AnnounceBlocks for Connector {
+    fn block_announcements(&self) -> BoxStream<'static, ()> {
+        match self {
+            Connector::Bitcoind(connector) => connector.block_announcements(),
+            Connector::Esplora(connector) => connector.block_announcements(),
+        }
+    }
+}
+
+#[async_trait]
+impl LatestBlock for Connector {
+    type Block = Block;
+
+    async fn latest_block(&self) -> anyhow::Result<Self::Block> {
+        match self {
+            Connector::Bitcoind(connector) => connector.latest_block().await,
+            Connector::Esplora(connector) => connector.latest_block().await,
+        }
+    }
+}
+
+#[async_trait]
+impl BlockByHash for Connector {
+    type Block = Block;
+    type BlockHash = BlockHash;
+
+    async fn block_by_hash(&self, block_hash: Self::BlockHash) -> anyhow::Result<Self::Block> {
+        match self {
+            Connector::Bitcoind(connector) => connector.block_by_hash(block_hash).await,
+            Connector::Esplora(connector) => connector.block_by_hash(block_hash).await,
+        }
+    }
+}
+
+#[async_trait]
+impl BroadcastSignedTransaction for Connector {
+    async fn broadcast_signed_transaction(&self, transaction: Transaction) -> anyhow::Result<Txid> {
+        match self {
+            Connector::Bitcoind(connector) => {
+                connector.broadcast_signed_transaction(transaction).await
+            }
+            Connector::Esplora(connector) => {
+                connector.broadcast_signed_transaction(transaction).await
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl EstimateFeePerWu for Connector {
+    fn can_estimate_fee(&self) -> bool {
+        match self {
+            Connector::Bitcoind(connector) => connector.can_estimate_fee(),
+            Connector::Esplora(connector) => connector.can_estimate_fee(),
+        }
+    }
+
+    async fn estimate_fee_per_wu(&self, confirmation_target: u16) -> anyhow::Result<usize> {
+        match self {
+            Connector::Bitcoind(connector) => {
+                connector.estimate_fee_per_wu(confirmation_target).await
+            }
+            Connector::Esplora(connector) => {
+                connector.estimate_fee_per_wu(confirmation_target).await
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionsByAddress for Connector {
+    fn can_look_up_addresses(&self) -> bool {
+        match self {
+            Connector::Bitcoind(connector) => connector.can_look_up_addresses(),
+            Connector::Esplora(connector) => connector.can_look_up_addresses(),
+        }
+    }
+
+    async fn transactions_by_address(
+        &self,
+        address: &Address,
+    ) -> anyhow::Result<Vec<AddressTransaction>> {
+        match self {
+            Connector::Bitcoind(connector) => connector.transactions_by_address(address).await,
+            Connector::Esplora(connector) => connector.transactions_by_address(address).await,
+        }
+    }
+}
+
+#[async_trait]
+impl PendingTransactions for Connector {
+    type TransactionId = Txid;
+    type Transaction = Transaction;
+
+    async fn pending_transaction_ids(&self) -> anyhow::Result<Vec<Txid>> {
+        match self {
+            Connector::Bitcoind(connector) => connector.pending_transaction_ids().await,
+            Connector::Esplora(connector) => connector.pending_transaction_ids().await,
+        }
+    }
+
+    async fn pending_transaction(&self, txid: Txid) -> anyhow::Result<Transaction> {
+        match self {
+            Connector::Bitcoind(connector) => connector.pending_transaction(txid).await,
+            Connector::Esplora(connector) => connector.pending_transaction(txid).await,
+        }
+    }
+}
+
+#[async_trait]
+impl FetchNetworkId<Network> for Connector {
+    async fn network_id(&self) -> anyhow::Result<Network> {
+        match self {
+            Connector::Bitcoind(connector) => connector.network_id().await,
+            Connector::Esplora(connector) => connector.network_id().await,
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use crate::{
+    btsieve::{
+        bitcoin::{
+            AddressTransaction, BroadcastSignedTransaction, EstimateFeePerWu, TransactionRejected,
+            TransactionsByAddress,
+        },
+        AnnounceBlocks, BlockByHash, LatestBlock, PendingTransactions, RequiredConfirmations,
+        SubscribeLatestBlocks,
+    },
+    config::validation::FetchNetworkId,
+};
+use async_trait::async_trait;
+use bitcoin::{
+    blockdata::constants::genesis_block, consensus::encode::deserialize, Address, BitcoinHash,
+    BlockHash, Network, Transaction, Txid,
+};
+use reqwest::{Client, Url};
+use serde::Deserialize;
+
+/// Connects to an Esplora-compatible HTTP API, e.g. one provided by
+/// electrs, instead of a full bitcoind.
+///
+/// See https://github.com/Blockstream/esplora/blob/master/API.md
+#[derive(Debug)]
+pub struct EsploraConnector {
+    base_url: Url,
+    client: Client,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+struct TransactionSummary {
+    txid: Txid,
+    status: TransactionStatus,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+struct TransactionStatus {
+    block_height: Option<u32>,
+}
+
+impl EsploraConnector {
+    pub fn new(base_url: Url) -> Self {
+        Self {
+            base_url: with_trailing_slash(base_url),
+            client: Client::new(),
+        }
+    }
+
+    fn url(&self, path: &str) -> Url {
+        self.base_url.join(path).expect("building url should work")
+    }
+
+    async fn get_text(&self, path: &str) -> anyhow::Result<String> {
+        let text = self
+            .client
+            .get(self.url(path))
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        Ok(text)
+    }
+
+    async fn get_raw<T>(&self, path: &str) -> anyhow::Result<T>
+    where
+        T: bitcoin::consensus::Decodable,
+    {
+        let bytes = self
+            .client
+            .get(self.url(path))
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+
+        Ok(deserialize(&bytes)?)
+    }
+}
+
+/// Url::join replaces the last path segment if the url doesn't end with a
+/// slash, e.g. the `api` of `https://blockstream.info/api`.
+fn with_trailing_slash(mut url: Url) -> Url {
+    if !url.path().ends_with('/') {
+        let path = format!("{}/", url.path());
+        url.set_path(&path);
+    }
+
+    url
+}
+
+impl RequiredConfirmations for EsploraConnector {}
+
+impl SubscribeLatestBlocks for EsploraConnector {}
+
+impl AnnounceBlocks for EsploraConnector {}
+
+#[async_trait]
+impl LatestBlock for EsploraConnector {
+    type Block = bitcoin::Block;
+
+    async fn latest_block(&self) -> anyhow::Result<Self::Block> {
+        let block_hash = self.get_text("blocks/tip/hash").await?.trim().parse()?;
+
+        let block = self.block_by_hash(block_hash).await?;
+
+        Ok(block)
+    }
+}
+
+#[async_trait]
+impl BlockByHash for EsploraConnector {
+    type Block = bitcoin::Block;
+    type BlockHash = bitcoin::BlockHash;
+
+    async fn block_by_hash(&self, block_hash: Self::BlockHash) -> anyhow::Result<Self::Block> {
+        let block: Self::Block = self.get_raw(&format!("block/{}/raw", block_hash)).await?;
+
+        tracing::debug!(
+            "Fetched block {} with {} transactions from esplora",
+            block_hash,
+            block.txdata.len()
+        );
+
+        Ok(block)
+    }
+}
+
+#[async_trait]
+impl BroadcastSignedTransaction for EsploraConnector {
+    async fn broadcast_signed_transaction(&self, transaction: Transaction) -> anyhow::Result<Txid> {
+        let response = self
+            .client
+            .post(self.url("tx"))
+            .body(bitcoin::consensus::encode::serialize_hex(&transaction))
+            .send()
+            .await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if status.is_client_error() {
+            return Err(anyhow::Error::from(TransactionRejected { message: text }));
+        }
+        if !status.is_success() {
+            anyhow::bail!("esplora responded with {}: {}", status, text)
+        }
+
+        let txid = text.trim().parse()?;
+
+        tracing::debug!("Broadcasted transaction {} through esplora", txid);
+
+        Ok(txid)
+    }
+}
+
+#[async_trait]
+impl EstimateFeePerWu for EsploraConnector {
+    fn can_estimate_fee(&self) -> bool {
+        false
+    }
+
+    async fn estimate_fee_per_wu(&self, _: u16) -> anyhow::Result<usize> {
+        anyhow::bail!("estimating fees through esplora is not supported")
+    }
+}
+
+#[async_trait]
+impl TransactionsByAddress for EsploraConnector {
+    fn can_look_up_addresses(&self) -> bool {
+        true
+    }
+
+    /// Esplora only returns the most recent transactions of an address, at
+    /// most 50 from the mempool and 25 confirmed ones.
+    async fn transactions_by_address(
+        &self,
+        address: &Address,
+    ) -> anyhow::Result<Vec<AddressTransaction>> {
+        let summaries = self
+            .client
+            .get(self.url(&format!("address/{}/txs", address)))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Vec<TransactionSummary>>()
+            .await?;
+        let tip_height: u32 = self.get_text("blocks/tip/height").await?.trim().parse()?;
+
+        let mut transactions = Vec::with_capacity(summaries.len());
+        for TransactionSummary { txid, status } in summaries {
+            transactions.push(AddressTransaction {
+                transaction: self.get_raw(&format!("tx/{}/raw", txid)).await?,
+                confirmations: confirmations(tip_height, status.block_height),
+            });
+        }
+
+        tracing::debug!(
+            "Fetched {} transactions of address {} from esplora",
+            transactions.len(),
+            address
+        );
+
+        Ok(transactions)
+    }
+}
+
+/// Transactions in the mempool have no block height yet. A block mined after
+/// we asked for the tip is counted as the tip.
+fn confirmations(tip_height: u32, block_height: Option<u32>) -> u32 {
+    match block_height {
+        Some(block_height) => tip_height.saturating_sub(block_height).saturating_add(1),
+        None => 0,
+    }
+}
+
+#[async_trait]
+impl PendingTransactions for EsploraConnector {
+    type TransactionId = Txid;
+    type Transaction = Transaction;
+
+    async fn pending_transaction_ids(&self) -> anyhow::Result<Vec<Txid>> {
+        let txids = self
+            .client
+            .get(self.url("mempool/txids"))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Vec<Txid>>()
+            .await?;
+
+        tracing::trace!(
+            "Fetched {} mempool transaction ids from esplora",
+            txids.len()
+        );
+
+        Ok(txids)
+    }
+
+    async fn pending_transaction(&self, txid: Txid) -> anyhow::Result<Transaction> {
+        let transaction = self.get_raw(&format!("tx/{}/raw", txid)).await?;
+
+        tracing::trace!("Fetched mempool transaction {} from esplora", txid);
+
+        Ok(transaction)
+    }
+}
+
+#[async_trait]
+impl FetchNetworkId<Network> for EsploraConnector {
+    async fn network_id(&self) -> anyhow::Result<Network> {
+        let genesis_hash: BlockHash = self.get_text("block-height/0").await?.trim().parse()?;
+
+        let network = network_of_genesis_block(genesis_hash).ok_or_else(|| {
+            anyhow::anyhow!("unknown genesis block {} from esplora", genesis_hash)
+        })?;
+
+        tracing::debug!("Fetched network {} from esplora", network);
+
+        Ok(network)
+    }
+}
+
+fn network_of_genesis_block(genesis_hash: BlockHash) -> Option<Network> {
+    vec![Network::Bitcoin, Network::Testnet, Network::Regtest]
+        .into_iter()
+        .find(|network| genesis_block(*network).bitcoin_hash() == genesis_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::{OutPoint, Script, TxIn};
+    use warp::Filter;
+
+    /// Serves the given response for every request, standing in for an
+    /// Esplora instance.
+    fn stub_esplora(status: warp::http::StatusCode, response: &'static str) -> Url {
+        let route = warp::any().map(move || warp::reply::with_status(response, status));
+        let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        format!("http://{}/api", addr).parse().unwrap()
+    }
+
+    fn transaction() -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: Script::new(),
+                sequence: 0xFFFF_FFFF,
+                witness: vec![],
+            }],
+            output: vec![],
+        }
+    }
+
+    #[test]
+    fn appends_trailing_slash_to_base_url() {
+        let connector = EsploraConnector::new("https://blockstream.info/api".parse().unwrap());
+
+        assert_eq!(
+            connector.url("blocks/tip/hash").as_str(),
+            "https://blockstream.info/api/blocks/tip/hash"
+        );
+    }
+
+    #[test]
+    fn recognizes_networks_by_genesis_block() {
+        for network in vec![Network::Bitcoin, Network::Testnet, Network::Regtest] {
+            let genesis_hash = genesis_block(network).bitcoin_hash();
+
+            assert_eq!(network_of_genesis_block(genesis_hash), Some(network));
+        }
+    }
+
+    #[test]
+    fn counts_confirmations_from_the_tip() {
+        assert_eq!(confirmations(100, None), 0);
+        assert_eq!(confirmations(100, Some(100)), 1);
+        assert_eq!(confirmations(100, Some(95)), 6);
+        assert_eq!(confirmations(100, Some(101)), 1);
+    }
+
+    #[tokio::test]
+    async fn broadcast_returns_txid_from_esplora() {
+        let url = stub_esplora(
+            warp::http::StatusCode::OK,
+            "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b",
+        );
+        let connector = EsploraConnector::new(url);
+
+        let txid = connector
+            .broadcast_signed_transaction(transaction())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            txid,
+            "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"
+                .parse()
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn broadcast_reports_rejected_transaction() {
+        let url = stub_esplora(
+            warp::http::StatusCode::BAD_REQUEST,
+            "sendrawtransaction RPC error: {\"code\":-26,\"message\":\"non-final\"}",
+        );
+        let connector = EsploraConnector::new(url);
+
+        let error = connector
+            .broadcast_signed_transaction(transaction())
+            .await
+            .unwrap_err();
+
+        let rejected = error.downcast::<TransactionRejected>().unwrap();
+        assert!(rejected.message.contains("non-final"));
+    }
+}
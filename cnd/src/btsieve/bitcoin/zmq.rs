@@ -0,0 +1,305 @@
+//! A subscriber for the block notifications bitcoind publishes over ZeroMQ
+//! if started with `-zmqpubhashblock`.
+//!
+//! All we ever receive are `hashblock` messages, hence speaking just enough
+//! ZMTP 3.0 over a TCP stream saves us from linking against libzmq.
+//!
+//! See https://rfc.zeromq.org/spec/23/ and
+//! https://github.com/bitcoin/bitcoin/blob/master/doc/zmq.md
+
+use bitcoin::{hashes::Hash, BlockHash};
+use futures::{stream, Stream};
+use reqwest::Url;
+use std::{convert::TryFrom, time::Duration};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+};
+
+const TOPIC: &[u8] = b"hashblock";
+
+/// How long we wait before reconnecting, polling keeps us up to date in the
+/// meantime.
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+/// Notifications are tiny, anything bigger means we are out of sync with the
+/// stream.
+const MAX_FRAME_SIZE: u64 = 1024;
+
+const FLAG_MORE: u8 = 0x01;
+const FLAG_LONG: u8 = 0x02;
+const FLAG_COMMAND: u8 = 0x04;
+
+/// A SUB socket subscribed to the `hashblock` topic.
+#[derive(Debug)]
+pub struct HashBlockSubscription<S> {
+    stream: S,
+}
+
+impl HashBlockSubscription<TcpStream> {
+    pub async fn connect(endpoint: &Url) -> anyhow::Result<Self> {
+        if endpoint.scheme() != "tcp" {
+            anyhow::bail!(
+                "unsupported zmq transport {}, only tcp is",
+                endpoint.scheme()
+            )
+        }
+        let host = endpoint
+            .host_str()
+            .ok_or_else(|| anyhow::anyhow!("zmq endpoint {} has no host", endpoint))?;
+        let port = endpoint
+            .port()
+            .ok_or_else(|| anyhow::anyhow!("zmq endpoint {} has no port", endpoint))?;
+
+        let stream = TcpStream::connect((host, port)).await?;
+
+        Self::handshake(stream).await
+    }
+}
+
+impl<S> HashBlockSubscription<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    async fn handshake(mut stream: S) -> anyhow::Result<Self> {
+        stream.write_all(&greeting()).await?;
+
+        let mut peer_greeting = [0u8; 64];
+        stream.read_exact(&mut peer_greeting).await?;
+        if peer_greeting[0] != 0xFF || peer_greeting[9] != 0x7F {
+            anyhow::bail!("peer does not speak ZMTP")
+        }
+        if peer_greeting[10] < 3 {
+            anyhow::bail!("peer speaks ZMTP {}, we need 3", peer_greeting[10])
+        }
+
+        write_frame(&mut stream, FLAG_COMMAND, &ready_command()).await?;
+        let (flags, _) = read_frame(&mut stream).await?;
+        if flags & FLAG_COMMAND == 0 {
+            anyhow::bail!("expected the READY command of the peer")
+        }
+
+        // ZMTP 3.0 subscribes through a message starting with 0x01.
+        let mut subscribe = vec![0x01];
+        subscribe.extend_from_slice(TOPIC);
+        write_frame(&mut stream, 0, &subscribe).await?;
+
+        Ok(Self { stream })
+    }
+
+    /// Waits for the next block bitcoind announces.
+    pub async fn next(&mut self) -> anyhow::Result<BlockHash> {
+        loop {
+            // The message is the topic, the hash and a sequence number.
+            if let [topic, hash, ..] = read_message(&mut self.stream).await?.as_slice() {
+                if topic.as_slice() != TOPIC {
+                    continue;
+                }
+
+                // bitcoind publishes the hash the way it is displayed, i.e.
+                // reversed.
+                let mut hash = hash.clone();
+                hash.reverse();
+
+                return Ok(BlockHash::from_slice(&hash)?);
+            }
+        }
+    }
+}
+
+/// Yields whenever bitcoind announces a new block.
+///
+/// Connection errors are logged and followed by a reconnect, hence the
+/// stream never ends.
+pub fn block_announcements(endpoint: Url) -> impl Stream<Item = ()> {
+    stream::unfold(
+        None::<HashBlockSubscription<TcpStream>>,
+        move |subscription| {
+            let endpoint = endpoint.clone();
+
+            async move {
+                let mut subscription = subscription;
+
+                loop {
+                    let mut current = match subscription.take() {
+                        Some(current) => current,
+                        None => match HashBlockSubscription::connect(&endpoint).await {
+                            Ok(current) => {
+                                tracing::info!("subscribed to block notifications at {}", endpoint);
+                                current
+                            }
+                            Err(e) => {
+                                tracing::warn!(
+                                    "failed to subscribe to block notifications at {}: {:#}",
+                                    endpoint,
+                                    e
+                                );
+                                tokio::time::delay_for(RECONNECT_DELAY).await;
+                                continue;
+                            }
+                        },
+                    };
+
+                    match current.next().await {
+                        Ok(block_hash) => {
+                            tracing::debug!("bitcoind announced block {}", block_hash);
+                            return Some(((), Some(current)));
+                        }
+                        Err(e) => {
+                            tracing::warn!("lost block notifications from {}: {:#}", endpoint, e)
+                        }
+                    }
+                }
+            }
+        },
+    )
+}
+
+fn greeting() -> [u8; 64] {
+    let mut greeting = [0u8; 64];
+    greeting[0] = 0xFF;
+    greeting[9] = 0x7F;
+    // Version 3.0
+    greeting[10] = 3;
+    greeting[11] = 0;
+    greeting[12..16].copy_from_slice(b"NULL");
+
+    greeting
+}
+
+fn ready_command() -> Vec<u8> {
+    let mut command = vec![5];
+    command.extend_from_slice(b"READY");
+    command.push(11);
+    command.extend_from_slice(b"Socket-Type");
+    command.extend_from_slice(&3u32.to_be_bytes());
+    command.extend_from_slice(b"SUB");
+
+    command
+}
+
+async fn write_frame<S>(stream: &mut S, flags: u8, body: &[u8]) -> anyhow::Result<()>
+where
+    S: AsyncWrite + Unpin,
+{
+    match u8::try_from(body.len()) {
+        Ok(size) => {
+            stream.write_u8(flags).await?;
+            stream.write_u8(size).await?;
+        }
+        Err(_) => {
+            stream.write_u8(flags | FLAG_LONG).await?;
+            stream.write_u64(body.len() as u64).await?;
+        }
+    }
+    stream.write_all(body).await?;
+
+    Ok(())
+}
+
+async fn read_frame<S>(stream: &mut S) -> anyhow::Result<(u8, Vec<u8>)>
+where
+    S: AsyncRead + Unpin,
+{
+    let flags = stream.read_u8().await?;
+    let size = if flags & FLAG_LONG != 0 {
+        stream.read_u64().await?
+    } else {
+        u64::from(stream.read_u8().await?)
+    };
+    if size > MAX_FRAME_SIZE {
+        anyhow::bail!(
+            "frame of {} bytes exceeds the maximum of {}",
+            size,
+            MAX_FRAME_SIZE
+        )
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    let mut body = vec![0u8; size as usize];
+    stream.read_exact(&mut body).await?;
+
+    Ok((flags, body))
+}
+
+/// Reads the frames of the next message, skipping commands like PING.
+async fn read_message<S>(stream: &mut S) -> anyhow::Result<Vec<Vec<u8>>>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut frames = Vec::new();
+
+    loop {
+        let (flags, body) = read_frame(stream).await?;
+        if flags & FLAG_COMMAND != 0 {
+            continue;
+        }
+
+        frames.push(body);
+
+        if flags & FLAG_MORE == 0 {
+            return Ok(frames);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// Accepts a single subscriber and publishes the given block hashes to it,
+    /// standing in for bitcoind.
+    async fn stub_publisher(block_hashes: Vec<[u8; 32]>) -> Url {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+
+            let mut subscriber_greeting = [0u8; 64];
+            stream.read_exact(&mut subscriber_greeting).await.unwrap();
+            stream.write_all(&greeting()).await.unwrap();
+
+            let (_, ready) = read_frame(&mut stream).await.unwrap();
+            assert!(ready.starts_with(b"\x05READY"));
+            write_frame(&mut stream, FLAG_COMMAND, b"\x05READY")
+                .await
+                .unwrap();
+
+            let subscription = read_message(&mut stream).await.unwrap();
+            assert_eq!(subscription, vec![b"\x01hashblock".to_vec()]);
+
+            for (sequence, hash) in (0u32..).zip(block_hashes.iter()) {
+                write_frame(&mut stream, FLAG_MORE, TOPIC).await.unwrap();
+                write_frame(&mut stream, FLAG_MORE, hash).await.unwrap();
+                write_frame(&mut stream, 0, &sequence.to_le_bytes())
+                    .await
+                    .unwrap();
+            }
+        });
+
+        format!("tcp://{}", addr).parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn receives_announced_block_hashes() {
+        let mut announced = [0u8; 32];
+        announced[0] = 0xAB;
+        let endpoint = stub_publisher(vec![announced]).await;
+
+        let mut subscription = HashBlockSubscription::connect(&endpoint).await.unwrap();
+        let block_hash = subscription.next().await.unwrap();
+
+        assert_eq!(block_hash.to_string(), format!("ab{}", "00".repeat(31)));
+    }
+
+    #[tokio::test]
+    async fn refuses_non_tcp_endpoints() {
+        let endpoint = "ipc:///tmp/bitcoind".parse().unwrap();
+
+        let result = HashBlockSubscription::connect(&endpoint).await;
+
+        assert!(result.is_err());
+    }
+}
@@ -4,19 +4,92 @@ mod web3_connector;
 pub use self::{cache::Cache, web3_connector::Web3Connector};
 use crate::{
     btsieve::{
-        find_relevant_blocks, BlockByHash, BlockHash, LatestBlock, Predates, PreviousBlockHash,
+        find_confirmed, find_including_block, find_pending, BlockByHash, BlockHash,
+        ContainsTransaction, Predates, PreviousBlockHash, ProcessedBlock, RequiredConfirmations,
+        SubscribeLatestBlocks, SubscribeMempool,
     },
     ethereum::{Address, Block, Bytes, Hash, Input, Log, Transaction, TransactionReceipt, U256},
+    jsonrpc,
+    swap_protocols::ledger::ethereum::ChainId,
 };
 use async_trait::async_trait;
 use chrono::NaiveDateTime;
-use genawaiter::{sync::Gen, GeneratorState};
+use serde::{Deserialize, Serialize};
+use std::{cmp, time::Duration};
+
+/// The maximum number of blocks we ask for in a single `eth_getLogs` call.
+/// Public nodes refuse to serve queries that span too many blocks.
+const MAX_BLOCK_RANGE: u64 = 1_000;
+
+/// The JSON-RPC error code nodes reply with if they don't support a method.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// How often we ask for the latest block of the given chain.
+///
+/// Public chains produce a block every 15 seconds or so, development chains
+/// as soon as a transaction comes in.
+pub fn poll_interval(chain_id: ChainId) -> Duration {
+    if chain_id == ChainId::regtest() {
+        Duration::from_secs(1)
+    } else {
+        Duration::from_secs(5)
+    }
+}
 
 #[async_trait]
 pub trait ReceiptByHash: Send + Sync + 'static {
     async fn receipt_by_hash(&self, transaction_hash: Hash) -> anyhow::Result<TransactionReceipt>;
 }
 
+#[async_trait]
+pub trait TransactionByHash: Send + Sync + 'static {
+    async fn transaction_by_hash(&self, transaction_hash: Hash) -> anyhow::Result<Transaction>;
+}
+
+#[async_trait]
+pub trait BlockByNumber: Send + Sync + 'static {
+    async fn block_by_number(&self, number: U256) -> anyhow::Result<Block>;
+}
+
+#[async_trait]
+pub trait LogsByFilter: Send + Sync + 'static {
+    async fn logs_by_filter(&self, filter: &LogFilter) -> anyhow::Result<Vec<Log>>;
+}
+
+/// Abstracts over the ability of a connector to tell us how it wants events to
+/// be matched.
+pub trait EventMatchingStrategy {
+    fn event_matching(&self) -> EventMatching;
+}
+
+/// The strategy used to find the logs emitted by an HTLC.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventMatching {
+    /// Fetch every block since the start of the swap and the receipts of all
+    /// transactions in blocks whose bloom filter matches the event.
+    BlockWalking,
+    /// Ask the node for matching logs using `eth_getLogs`. Falls back to
+    /// block walking if the node does not support it.
+    GetLogs,
+}
+
+impl Default for EventMatching {
+    fn default() -> Self {
+        EventMatching::BlockWalking
+    }
+}
+
+/// The filter object of `eth_getLogs`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogFilter {
+    pub from_block: U256,
+    pub to_block: U256,
+    pub address: Address,
+    pub topics: Vec<Option<Topic>>,
+}
+
 impl BlockHash for Block {
     type BlockHash = Hash;
 
@@ -34,13 +107,26 @@ impl PreviousBlockHash for Block {
     }
 }
 
+impl ContainsTransaction for Block {
+    type TransactionId = Hash;
+
+    fn contains_transaction(&self, id: &Hash) -> bool {
+        self.transactions
+            .iter()
+            .any(|transaction| &transaction.hash == id)
+    }
+}
+
 pub async fn watch_for_contract_creation<C>(
     blockchain_connector: &C,
     start_of_swap: NaiveDateTime,
     bytecode: &Bytes,
 ) -> anyhow::Result<(Transaction, Address)>
 where
-    C: LatestBlock<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash> + ReceiptByHash,
+    C: SubscribeLatestBlocks<Block = Block>
+        + BlockByHash<Block = Block, BlockHash = Hash>
+        + ReceiptByHash
+        + RequiredConfirmations,
 {
     let (transaction, receipt) =
         matching_transaction_and_receipt(blockchain_connector, start_of_swap, |transaction| {
@@ -87,8 +173,27 @@ pub async fn watch_for_event<C>(
     event: Event,
 ) -> anyhow::Result<(Transaction, Log)>
 where
-    C: LatestBlock<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash> + ReceiptByHash,
+    C: SubscribeLatestBlocks<Block = Block>
+        + BlockByHash<Block = Block, BlockHash = Hash>
+        + BlockByNumber
+        + ReceiptByHash
+        + TransactionByHash
+        + LogsByFilter
+        + EventMatchingStrategy
+        + RequiredConfirmations,
 {
+    if blockchain_connector.event_matching() == EventMatching::GetLogs {
+        match matching_transaction_and_log_by_filter(blockchain_connector, start_of_swap, &event)
+            .await
+        {
+            Err(e) if is_unsupported_method(&e) => tracing::warn!(
+                "node does not support eth_getLogs, falling back to block walking: {:#}",
+                e
+            ),
+            result => return result,
+        }
+    }
+
     matching_transaction_and_log(
         blockchain_connector,
         start_of_swap,
@@ -98,6 +203,63 @@ where
     .await
 }
 
+/// Watches the pending transactions for a call to the given contract whose
+/// input matches the sieve.
+///
+/// Pending transactions have no receipt yet, hence we can only look at their
+/// input and not at the events they are going to emit.
+pub async fn watch_for_pending_call<C, S, M>(
+    blockchain_connector: &C,
+    contract: Address,
+    sieve: S,
+) -> anyhow::Result<(Transaction, M)>
+where
+    C: SubscribeMempool<Transaction = Transaction>,
+    S: Fn(&[u8]) -> Option<M>,
+{
+    find_pending(blockchain_connector, |transaction: &Transaction| {
+        if transaction.to != Some(contract) {
+            return None;
+        }
+
+        let found = sieve(&transaction.input.0)?;
+        tracing::trace!("pending transaction matched {:x}", transaction.hash);
+
+        Some((transaction.clone(), found))
+    })
+    .await
+}
+
+/// Finds the block that includes the given transaction, once it is processed
+/// watching can resume from it.
+///
+/// Every block is later than its parent, hence no block from the including
+/// one on predates its time.
+pub async fn processed_block<C>(
+    blockchain_connector: &C,
+    start_of_swap: NaiveDateTime,
+    transaction_hash: Hash,
+) -> anyhow::Result<Option<ProcessedBlock>>
+where
+    C: SubscribeLatestBlocks<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash>,
+{
+    let block =
+        match find_including_block(blockchain_connector, start_of_swap, &transaction_hash).await? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+
+    // Block times are seconds since the epoch, they are not going to overflow
+    // an i64.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    let resume_from = NaiveDateTime::from_timestamp(block.timestamp.low_u64() as i64, 0);
+
+    Ok(Some(ProcessedBlock {
+        hash: format!("{:x}", block.block_hash()),
+        resume_from,
+    }))
+}
+
 /// Fetch receipt from connector using transaction hash.
 async fn fetch_receipt<C>(
     blockchain_connector: &C,
@@ -111,78 +273,94 @@ where
 }
 
 fn find_log_for_event_in_receipt(event: &Event, receipt: TransactionReceipt) -> Option<Log> {
-    match event {
-        Event { topics, .. } if topics.is_empty() => None,
-        Event { address, topics } => receipt.logs.into_iter().find(|log| {
-            if address != &log.address {
-                return false;
-            }
+    receipt
+        .logs
+        .into_iter()
+        .find(|log| log_matches_event(event, log))
+}
 
-            if log.topics.len() != topics.len() {
-                return false;
-            }
+fn log_matches_event(event: &Event, log: &Log) -> bool {
+    let Event { address, topics } = event;
 
-            log.topics.iter().enumerate().all(|(index, tx_topic)| {
-                let topic = &topics[index];
-                topic.as_ref().map_or(true, |topic| tx_topic == &topic.0)
-            })
-        }),
+    if topics.is_empty() {
+        return false;
+    }
+
+    if address != &log.address {
+        return false;
+    }
+
+    if log.topics.len() != topics.len() {
+        return false;
+    }
+
+    log.topics.iter().enumerate().all(|(index, tx_topic)| {
+        let topic = &topics[index];
+        topic.as_ref().map_or(true, |topic| tx_topic == &topic.0)
+    })
+}
+
+fn is_unsupported_method(e: &anyhow::Error) -> bool {
+    match e.downcast_ref::<jsonrpc::Error>() {
+        Some(jsonrpc::Error::JsonRpc { code, .. }) => *code == METHOD_NOT_FOUND,
+        _ => false,
     }
 }
 
+fn block_number(block: &Block) -> anyhow::Result<U256> {
+    block
+        .number
+        .ok_or_else(|| anyhow::anyhow!("block without number"))
+}
+
 pub async fn matching_transaction_and_receipt<C, F>(
     connector: &C,
     start_of_swap: NaiveDateTime,
     matcher: F,
 ) -> anyhow::Result<(Transaction, TransactionReceipt)>
 where
-    C: LatestBlock<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash> + ReceiptByHash,
+    C: SubscribeLatestBlocks<Block = Block>
+        + BlockByHash<Block = Block, BlockHash = Hash>
+        + ReceiptByHash
+        + RequiredConfirmations,
     F: Fn(&Transaction) -> bool,
 {
-    let mut block_generator =
-        Gen::new({ |co| async { find_relevant_blocks(connector, co, start_of_swap).await } });
-
-    loop {
-        match block_generator.async_resume().await {
-            GeneratorState::Yielded(block) => {
-                let block_hash = block
-                    .hash
-                    .ok_or_else(|| anyhow::anyhow!("block without hash"))?;
-
-                let span =
-                    tracing::trace_span!("new_block", blockhash = format_args!("{:x}", block_hash));
-                let _enter = span.enter();
-
-                tracing::trace!("checking {} transactions", block.transactions.len());
-
-                for transaction in block.transactions.into_iter() {
-                    let tx_hash = transaction.hash;
-                    let span = tracing::trace_span!(
-                        "matching_transaction",
-                        txhash = format_args!("{:x}", tx_hash)
-                    );
-                    let _enter = span.enter();
-
-                    if matcher(&transaction) {
-                        let receipt = fetch_receipt(connector, tx_hash).await?;
-                        if !receipt.is_status_ok() {
-                            // This can be caused by a failed attempt to complete an action,
-                            // for example, sending a transaction with low gas.
-                            tracing::warn!("transaction matched but status was NOT OK");
-                            continue;
-                        }
-                        tracing::info!("transaction matched");
-                        return Ok((transaction, receipt));
-                    }
+    let matcher = &matcher;
+
+    find_confirmed(connector, start_of_swap, move |block: Block| async move {
+        let block_hash = block
+            .hash
+            .ok_or_else(|| anyhow::anyhow!("block without hash"))?;
+
+        let span = tracing::trace_span!("new_block", blockhash = format_args!("{:x}", block_hash));
+        let _enter = span.enter();
+
+        tracing::trace!("checking {} transactions", block.transactions.len());
+
+        for transaction in block.transactions.into_iter() {
+            let tx_hash = transaction.hash;
+            let span = tracing::trace_span!(
+                "matching_transaction",
+                txhash = format_args!("{:x}", tx_hash)
+            );
+            let _enter = span.enter();
+
+            if matcher(&transaction) {
+                let receipt = fetch_receipt(connector, tx_hash).await?;
+                if !receipt.is_status_ok() {
+                    // This can be caused by a failed attempt to complete an action,
+                    // for example, sending a transaction with low gas.
+                    tracing::warn!("transaction matched but status was NOT OK");
+                    continue;
                 }
+                tracing::info!("transaction matched");
+                return Ok(Some((transaction, receipt)));
             }
-            GeneratorState::Complete(Err(e)) => return Err(e),
-            // By matching against the never type explicitly, we assert that the `Ok` value of the
-            // result is actually the never type and has not been changed since this line was
-            // written. The never type can never be constructed, so we can never reach this line.
-            GeneratorState::Complete(Ok(never)) => match never {},
         }
-    }
+
+        Ok(None)
+    })
+    .await
 }
 
 async fn matching_transaction_and_log<C, F>(
@@ -192,74 +370,208 @@ async fn matching_transaction_and_log<C, F>(
     matcher: F,
 ) -> anyhow::Result<(Transaction, Log)>
 where
-    C: LatestBlock<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash> + ReceiptByHash,
+    C: SubscribeLatestBlocks<Block = Block>
+        + BlockByHash<Block = Block, BlockHash = Hash>
+        + ReceiptByHash
+        + RequiredConfirmations,
     F: Fn(TransactionReceipt) -> Option<Log>,
 {
-    let mut block_generator =
-        Gen::new({ |co| async { find_relevant_blocks(connector, co, start_of_swap).await } });
+    let topics = &topics;
+    let matcher = &matcher;
+
+    find_confirmed(connector, start_of_swap, move |block: Block| async move {
+        let block_hash = block
+            .hash
+            .ok_or_else(|| anyhow::anyhow!("block without hash"))?;
+
+        let span = tracing::trace_span!("new_block", blockhash = format_args!("{:x}", block_hash));
+        let _enter = span.enter();
+
+        let maybe_contains_transaction = topics.iter().all(|topic| {
+            topic.as_ref().map_or(true, |topic| {
+                block
+                    .logs_bloom
+                    .contains_input(Input::Raw(&topic.0.as_bytes()))
+            })
+        });
+        if !maybe_contains_transaction {
+            tracing::trace!(
+                "bloom filter indicates that this block will not contain an instance of the event"
+            );
+            return Ok(None);
+        } else {
+            tracing::trace!(
+                "bloom filter indicates that this block might contain an instance of the event"
+            );
+        }
+
+        tracing::trace!("checking {} transactions", block.transactions.len());
+
+        for transaction in block.transactions.into_iter() {
+            let tx_hash = transaction.hash;
+
+            let span = tracing::trace_span!(
+                "matching_transaction",
+                txhash = format_args!("{:x}", tx_hash)
+            );
+            let _enter = span.enter();
+
+            let receipt = fetch_receipt(connector, tx_hash).await?;
+            let status_is_ok = receipt.is_status_ok();
+            if let Some(log) = matcher(receipt) {
+                if !status_is_ok {
+                    // This can be caused by a failed attempt to complete an action,
+                    // for example, sending a transaction with low gas.
+                    tracing::warn!("transaction matched but status was NOT OK");
+                    continue;
+                }
+                tracing::info!("transaction matched");
+                return Ok(Some((transaction, log)));
+            }
+        }
+
+        Ok(None)
+    })
+    .await
+}
+
+/// Queries the node for logs matching the event instead of looking at every
+/// transaction ourselves.
+///
+/// We start at the last block that predates the start of the swap and then
+/// keep polling for logs in the blocks that got mined since the last query.
+/// A matching log is returned once its block has the required number of
+/// confirmations and is still part of the chain.
+async fn matching_transaction_and_log_by_filter<C>(
+    connector: &C,
+    start_of_swap: NaiveDateTime,
+    event: &Event,
+) -> anyhow::Result<(Transaction, Log)>
+where
+    C: SubscribeLatestBlocks<Block = Block>
+        + BlockByNumber
+        + ReceiptByHash
+        + TransactionByHash
+        + LogsByFilter
+        + RequiredConfirmations,
+{
+    let required_confirmations = U256::from(connector.required_confirmations());
+    let mut latest_blocks = connector.subscribe();
+
+    let mut latest_block = block_number(&latest_blocks.next().await?)?;
+    let mut from_block = last_block_predating(connector, start_of_swap, latest_block).await?;
+    let mut matches = Vec::new();
 
     loop {
-        match block_generator.async_resume().await {
-            GeneratorState::Yielded(block) => {
-                let block_hash = block
-                    .hash
-                    .ok_or_else(|| anyhow::anyhow!("block without hash"))?;
-
-                let span =
-                    tracing::trace_span!("new_block", blockhash = format_args!("{:x}", block_hash));
-                let _enter = span.enter();
-
-                let maybe_contains_transaction = topics.iter().all(|topic| {
-                    topic.as_ref().map_or(true, |topic| {
-                        block
-                            .logs_bloom
-                            .contains_input(Input::Raw(&topic.0.as_bytes()))
-                    })
-                });
-                if !maybe_contains_transaction {
-                    tracing::trace!(
-                    "bloom filter indicates that this block will not contain an instance of the event"
-
-                    );
+        while from_block <= latest_block {
+            let to_block = cmp::min(from_block + MAX_BLOCK_RANGE - 1, latest_block);
+
+            tracing::trace!("fetching logs from block {} to {}", from_block, to_block);
+
+            let logs = connector
+                .logs_by_filter(&LogFilter {
+                    from_block,
+                    to_block,
+                    address: event.address,
+                    topics: event.topics.clone(),
+                })
+                .await?;
+
+            tracing::trace!("checking {} logs", logs.len());
+
+            for log in logs.into_iter().filter(|log| log_matches_event(event, log)) {
+                let tx_hash = log_transaction_hash(&log)?;
+
+                let receipt = connector.receipt_by_hash(tx_hash).await?;
+                if !receipt.is_status_ok() {
+                    // This can be caused by a failed attempt to complete an action,
+                    // for example, sending a transaction with low gas.
+                    tracing::warn!("transaction matched but status was NOT OK");
                     continue;
-                } else {
-                    tracing::trace!(
-                        "bloom filter indicates that this block might contain an instance of the event"
-                    );
                 }
 
-                tracing::trace!("checking {} transactions", block.transactions.len());
-
-                for transaction in block.transactions.into_iter() {
-                    let tx_hash = transaction.hash;
-
-                    let span = tracing::trace_span!(
-                        "matching_transaction",
-                        txhash = format_args!("{:x}", tx_hash)
-                    );
-                    let _enter = span.enter();
-
-                    let receipt = fetch_receipt(connector, tx_hash).await?;
-                    let status_is_ok = receipt.is_status_ok();
-                    if let Some(log) = matcher(receipt) {
-                        if !status_is_ok {
-                            // This can be caused by a failed attempt to complete an action,
-                            // for example, sending a transaction with low gas.
-                            tracing::warn!("transaction matched but status was NOT OK");
-                            continue;
-                        }
-                        tracing::info!("transaction matched");
-                        return Ok((transaction, log));
-                    }
+                if !matches.contains(&log) {
+                    matches.push(log);
                 }
             }
-            GeneratorState::Complete(Err(e)) => return Err(e),
-            // By matching against the never type explicitly, we assert that the `Ok` value of the
-            // result is actually the never type and has not been changed since this line was
-            // written. The never type can never be constructed, so we can never reach this line.
-            GeneratorState::Complete(Ok(never)) => match never {},
+
+            from_block = to_block + 1;
+        }
+
+        for log in std::mem::replace(&mut matches, Vec::new()) {
+            let (number, hash) = match (log.block_number, log.block_hash) {
+                (Some(number), Some(hash)) => (number, hash),
+                _ => anyhow::bail!("log without block"),
+            };
+
+            let confirmations = if number > latest_block {
+                U256::zero()
+            } else {
+                latest_block - number + 1
+            };
+
+            if confirmations < required_confirmations {
+                tracing::trace!(
+                    "match has {} of {} confirmations",
+                    confirmations,
+                    required_confirmations
+                );
+                matches.push(log);
+                continue;
+            }
+
+            if connector.block_by_number(number).await?.hash != Some(hash) {
+                tracing::warn!("block {:x} with a match got orphaned", hash);
+
+                // The transaction might have been included in a later block.
+                from_block = cmp::min(from_block, number);
+                continue;
+            }
+
+            let transaction = connector
+                .transaction_by_hash(log_transaction_hash(&log)?)
+                .await?;
+            tracing::info!("transaction matched");
+            return Ok((transaction, log));
+        }
+
+        latest_block = block_number(&latest_blocks.next().await?)?;
+    }
+}
+
+fn log_transaction_hash(log: &Log) -> anyhow::Result<Hash> {
+    log.transaction_hash
+        .ok_or_else(|| anyhow::anyhow!("log without transaction hash"))
+}
+
+/// Binary searches for the last block that predates the start of the swap,
+/// falling back to the genesis block if there is none.
+async fn last_block_predating<C>(
+    connector: &C,
+    start_of_swap: NaiveDateTime,
+    latest_block: U256,
+) -> anyhow::Result<U256>
+where
+    C: BlockByNumber,
+{
+    let mut low = U256::zero();
+    let mut high = latest_block;
+
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+
+        if connector
+            .block_by_number(mid)
+            .await?
+            .predates(start_of_swap)
+        {
+            low = mid;
+        } else {
+            high = mid - 1;
         }
     }
+
+    Ok(low)
 }
 
 impl Predates for Block {
@@ -305,3 +617,107 @@ pub struct Event {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub topics: Vec<Option<Topic>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Blocks(Vec<Block>);
+
+    #[async_trait]
+    impl BlockByNumber for Blocks {
+        async fn block_by_number(&self, number: U256) -> anyhow::Result<Block> {
+            Ok(self.0[number.as_usize()].clone())
+        }
+    }
+
+    fn blocks_with_timestamps(timestamps: &[u64]) -> Blocks {
+        Blocks(
+            timestamps
+                .iter()
+                .map(|timestamp| Block {
+                    timestamp: U256::from(*timestamp),
+                    ..Block::default()
+                })
+                .collect(),
+        )
+    }
+
+    fn start_of_swap(timestamp: i64) -> NaiveDateTime {
+        NaiveDateTime::from_timestamp(timestamp, 0)
+    }
+
+    #[tokio::test]
+    async fn finds_last_block_predating_start_of_swap() {
+        let blocks = blocks_with_timestamps(&[10, 20, 30, 40, 50, 60]);
+
+        let number = last_block_predating(&blocks, start_of_swap(35), U256::from(5))
+            .await
+            .unwrap();
+
+        assert_eq!(number, U256::from(2));
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_genesis_if_no_block_predates_start_of_swap() {
+        let blocks = blocks_with_timestamps(&[10, 20, 30]);
+
+        let number = last_block_predating(&blocks, start_of_swap(5), U256::from(2))
+            .await
+            .unwrap();
+
+        assert_eq!(number, U256::zero());
+    }
+
+    #[tokio::test]
+    async fn starts_at_latest_block_if_all_blocks_predate_start_of_swap() {
+        let blocks = blocks_with_timestamps(&[10, 20, 30]);
+
+        let number = last_block_predating(&blocks, start_of_swap(100), U256::from(2))
+            .await
+            .unwrap();
+
+        assert_eq!(number, U256::from(2));
+    }
+
+    #[test]
+    fn serialize_log_filter() {
+        let filter = LogFilter {
+            from_block: U256::from(16),
+            to_block: U256::from(1015),
+            address: "e46fb33e4db653de84cb0e0e8b810a6c4cd39d59".parse().unwrap(),
+            topics: vec![None, Some(Topic(Hash::from([1u8; 32])))],
+        };
+
+        let json = serde_json::to_value(&filter).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "fromBlock": "0x10",
+                "toBlock": "0x3f7",
+                "address": "0xe46fb33e4db653de84cb0e0e8b810a6c4cd39d59",
+                "topics": [
+                    null,
+                    "0x0101010101010101010101010101010101010101010101010101010101010101"
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn only_method_not_found_is_unsupported() {
+        let method_not_found = anyhow::Error::from(jsonrpc::Error::JsonRpc {
+            code: -32601,
+            message: "Method not found".to_owned(),
+        });
+        let invalid_params = anyhow::Error::from(jsonrpc::Error::JsonRpc {
+            code: -32602,
+            message: "Invalid params".to_owned(),
+        });
+
+        assert!(is_unsupported_method(&method_not_found));
+        assert!(!is_unsupported_method(&invalid_params));
+        assert!(!is_unsupported_method(&anyhow::anyhow!("other")));
+    }
+}
@@ -1,14 +1,22 @@
 use crate::{
     btsieve::{
-        ethereum::{self, Hash, ReceiptByHash},
-        BlockByHash, LatestBlock,
+        ethereum::{
+            self, BlockByNumber, EventMatching, EventMatchingStrategy, Hash, LogFilter,
+            LogsByFilter, ReceiptByHash, TransactionByHash,
+        },
+        AnnounceBlocks, BlockByHash, LatestBlock, LatestBlocks, Mempool, MempoolSubscription,
+        PendingTransactions, RequiredConfirmations, SubscribeLatestBlocks, SubscribeMempool,
+        Subscription, PENDING_TRANSACTIONS_POLL_INTERVAL,
     },
-    ethereum::TransactionReceipt,
+    db::{BlockStore, Item},
+    ethereum::{Log, Transaction, TransactionReceipt, U256},
 };
 use async_trait::async_trait;
 use derivative::Derivative;
+use futures::stream::BoxStream;
 use lru::LruCache;
-use std::sync::Arc;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{sync::Arc, time::Duration};
 use tokio::sync::Mutex;
 
 // This makes it a bit obscure that we have an option, the compile will point it
@@ -23,6 +31,13 @@ pub struct Cache<C> {
     pub block_cache: Arc<Mutex<LruCache<Hash, Block>>>,
     #[derivative(Debug = "ignore")]
     pub receipt_cache: Arc<Mutex<LruCache<Hash, TransactionReceipt>>>,
+    pub confirmations: u32,
+    pub watch_mempool: bool,
+    #[derivative(Debug = "ignore")]
+    pub latest_blocks: Arc<LatestBlocks<Block>>,
+    #[derivative(Debug = "ignore")]
+    pub mempool: Arc<Mempool<Transaction>>,
+    pub block_store: Option<BlockStore>,
 }
 
 impl<C> Cache<C> {
@@ -37,8 +52,136 @@ impl<C> Cache<C> {
             connector,
             block_cache,
             receipt_cache,
+            confirmations: 1,
+            watch_mempool: false,
+            latest_blocks: Arc::new(LatestBlocks::default()),
+            mempool: Arc::new(Mempool::default()),
+            block_store: None,
+        }
+    }
+
+    pub fn with_confirmations(self, confirmations: u32) -> Self {
+        Self {
+            confirmations,
+            ..self
+        }
+    }
+
+    pub fn with_mempool_watching(self, watch_mempool: bool) -> Self {
+        Self {
+            watch_mempool,
+            ..self
+        }
+    }
+
+    /// Falls back to the blocks and receipts stored in the database before
+    /// asking the connector, the ones fetched from the connector are stored
+    /// there too.
+    pub fn with_block_store(self, block_store: Option<BlockStore>) -> Self {
+        Self {
+            block_store,
+            ..self
         }
     }
+
+    async fn load_from_store<T>(&self, item: Item, hash: Hash) -> Option<T>
+    where
+        T: DeserializeOwned,
+    {
+        let store = self.block_store.as_ref()?;
+
+        match load(store, item, hash).await {
+            Ok(value) => value,
+            Err(e) => {
+                tracing::warn!("failed to load {} {:x} from the store: {:#}", item, hash, e);
+                None
+            }
+        }
+    }
+
+    async fn save_to_store<T>(&self, item: Item, hash: Hash, value: &T)
+    where
+        T: Serialize,
+    {
+        if let Some(store) = self.block_store.as_ref() {
+            let result = match serde_json::to_vec(value) {
+                Ok(json) => store.save(item, &format!("{:x}", hash), json).await,
+                Err(e) => Err(e.into()),
+            };
+
+            if let Err(e) = result {
+                tracing::warn!("failed to save {} {:x} to the store: {:#}", item, hash, e)
+            }
+        }
+    }
+}
+
+async fn load<T>(store: &BlockStore, item: Item, hash: Hash) -> anyhow::Result<Option<T>>
+where
+    T: DeserializeOwned,
+{
+    let value = match store.load(item, &format!("{:x}", hash)).await? {
+        Some(json) => Some(serde_json::from_slice(&json)?),
+        None => None,
+    };
+
+    Ok(value)
+}
+
+impl<C> Cache<C>
+where
+    C: LatestBlock<Block = Block> + AnnounceBlocks,
+{
+    /// Polls the latest block once per `interval` on behalf of all swaps
+    /// watching this ledger.
+    pub async fn poll_latest_blocks(self: Arc<Self>, interval: Duration) {
+        self.latest_blocks.poll(&*self, interval).await
+    }
+}
+
+impl<C> Cache<C>
+where
+    C: PendingTransactions<TransactionId = Hash, Transaction = Transaction>,
+{
+    /// Polls the pending transactions on behalf of all swaps watching this
+    /// ledger, only needed if mempool watching is enabled.
+    pub async fn poll_mempool(self: Arc<Self>) {
+        self.mempool
+            .poll(&self.connector, PENDING_TRANSACTIONS_POLL_INTERVAL)
+            .await
+    }
+}
+
+impl<C> SubscribeMempool for Cache<C> {
+    type Transaction = Transaction;
+
+    fn subscribe_mempool(&self) -> MempoolSubscription<Transaction> {
+        self.mempool.subscribe()
+    }
+}
+
+impl<C> AnnounceBlocks for Cache<C>
+where
+    C: AnnounceBlocks,
+{
+    fn block_announcements(&self) -> BoxStream<'static, ()> {
+        self.connector.block_announcements()
+    }
+}
+
+impl<C> RequiredConfirmations for Cache<C> {
+    fn required_confirmations(&self) -> u32 {
+        self.confirmations
+    }
+}
+
+impl<C> SubscribeLatestBlocks for Cache<C>
+where
+    C: LatestBlock<Block = Block>,
+{
+    fn subscribe(&self) -> Subscription<'_, Block> {
+        self.latest_blocks.subscribe()
+    }
 }
 
 #[async_trait]
@@ -52,9 +195,19 @@ where
         let block = self.connector.latest_block().await?;
 
         let block_hash = block.hash.expect("no blocks without hash");
-        let mut guard = self.block_cache.lock().await;
-        if !guard.contains(&block_hash) {
-            guard.put(block_hash, block.clone());
+        let is_new = {
+            let mut guard = self.block_cache.lock().await;
+            let is_new = !guard.contains(&block_hash);
+            if is_new {
+                guard.put(block_hash, block.clone());
+            }
+
+            is_new
+        };
+
+        if is_new {
+            self.save_to_store(Item::EthereumBlock, block_hash, &block)
+                .await;
         }
 
         Ok(block)
@@ -75,8 +228,19 @@ where
             return Ok(block.clone());
         }
 
+        if let Some(block) = self
+            .load_from_store::<Block>(Item::EthereumBlock, block_hash)
+            .await
+        {
+            tracing::trace!("Found block in store: {:x}", block_hash);
+            self.block_cache.lock().await.put(block_hash, block.clone());
+            return Ok(block);
+        }
+
         let block = self.connector.block_by_hash(block_hash.clone()).await?;
         tracing::trace!("Fetched block from connector: {:x}", block_hash);
+        self.save_to_store(Item::EthereumBlock, block_hash, &block)
+            .await;
 
         // We dropped the lock so at this stage the block may have been inserted by
         // another thread, no worries, inserting the same block twice does not hurt.
@@ -98,12 +262,26 @@ where
             return Ok(receipt.clone());
         }
 
+        if let Some(receipt) = self
+            .load_from_store::<TransactionReceipt>(Item::EthereumReceipt, transaction_hash)
+            .await
+        {
+            tracing::trace!("Found receipt in store: {:x}", transaction_hash);
+            self.receipt_cache
+                .lock()
+                .await
+                .put(transaction_hash, receipt.clone());
+            return Ok(receipt);
+        }
+
         let receipt = self
             .connector
             .receipt_by_hash(transaction_hash.clone())
             .await?;
 
         tracing::trace!("Fetched receipt from connector: {:x}", transaction_hash);
+        self.save_to_store(Item::EthereumReceipt, transaction_hash, &receipt)
+            .await;
 
         // We dropped the lock so at this stage the receipt may have been inserted by
         // another thread, no worries, inserting the same receipt twice does not hurt.
@@ -113,3 +291,52 @@ where
         Ok(receipt)
     }
 }
+
+#[async_trait]
+impl<C> TransactionByHash for Cache<C>
+where
+    C: TransactionByHash,
+{
+    async fn transaction_by_hash(&self, transaction_hash: Hash) -> anyhow::Result<Transaction> {
+        self.connector.transaction_by_hash(transaction_hash).await
+    }
+}
+
+#[async_trait]
+impl<C> BlockByNumber for Cache<C>
+where
+    C: BlockByNumber,
+{
+    async fn block_by_number(&self, number: U256) -> anyhow::Result<Block> {
+        // Block numbers are not unique in the presence of reorgs, hence we
+        // don't cache by number.
+        let block = self.connector.block_by_number(number).await?;
+
+        if let Some(block_hash) = block.hash {
+            self.block_cache.lock().await.put(block_hash, block.clone());
+            self.save_to_store(Item::EthereumBlock, block_hash, &block)
+                .await;
+        }
+
+        Ok(block)
+    }
+}
+
+#[async_trait]
+impl<C> LogsByFilter for Cache<C>
+where
+    C: LogsByFilter,
+{
+    async fn logs_by_filter(&self, filter: &LogFilter) -> anyhow::Result<Vec<Log>> {
+        self.connector.logs_by_filter(filter).await
+    }
+}
+
+impl<C> EventMatchingStrategy for Cache<C>
+where
+    C: EventMatchingStrategy,
+{
+    fn event_matching(&self) -> EventMatching {
+        self.connector.event_matching()
+    }
+}
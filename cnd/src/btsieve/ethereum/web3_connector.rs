@@ -1,25 +1,139 @@
 use crate::{
-    btsieve::{ethereum::ReceiptByHash, BlockByHash, LatestBlock},
+    btsieve::{
+        ethereum::{
+            BlockByNumber, EventMatching, EventMatchingStrategy, LogFilter, LogsByFilter,
+            ReceiptByHash, TransactionByHash,
+        },
+        AnnounceBlocks, BlockByHash, LatestBlock, PendingTransactions, RequiredConfirmations,
+        SubscribeLatestBlocks,
+    },
     config::validation::FetchNetworkId,
-    ethereum::{Hash, TransactionReceipt},
+    ethereum::{Block, Hash, Log, Transaction, TransactionReceipt, U256},
     jsonrpc,
     swap_protocols::ledger::ethereum::ChainId,
 };
 use async_trait::async_trait;
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+
+/// How long we wait before subscribing to new heads again, polling keeps us up
+/// to date in the meantime.
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(10);
+
+/// The pending block as returned without full transaction objects.
+#[derive(Debug, Deserialize)]
+struct PendingBlock {
+    transactions: Vec<Hash>,
+}
+
+#[derive(Debug)]
+enum Transport {
+    Http(jsonrpc::Client),
+    WebSocket(Arc<jsonrpc::ws::Client>),
+}
+
+impl Transport {
+    async fn send<Req, Res>(&self, request: jsonrpc::Request<Req>) -> Result<Res, jsonrpc::Error>
+    where
+        Req: Serialize,
+        Res: DeserializeOwned,
+    {
+        match self {
+            Transport::Http(client) => client.send(request).await,
+            Transport::WebSocket(client) => client.send(request).await,
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct Web3Connector {
-    client: jsonrpc::Client,
+    client: Transport,
+    event_matching: EventMatching,
 }
 
 impl Web3Connector {
+    /// Talks to the node over a websocket if the url is a `ws://` or `wss://`
+    /// one and over HTTP otherwise.
     pub fn new(node_url: reqwest::Url) -> Self {
+        let client = match node_url.scheme() {
+            "ws" | "wss" => Transport::WebSocket(Arc::new(jsonrpc::ws::Client::new(node_url))),
+            _ => Transport::Http(jsonrpc::Client::new(node_url)),
+        };
+
         Self {
-            client: jsonrpc::Client::new(node_url),
+            client,
+            event_matching: EventMatching::default(),
+        }
+    }
+
+    pub fn with_event_matching(self, event_matching: EventMatching) -> Self {
+        Self {
+            event_matching,
+            ..self
+        }
+    }
+}
+
+impl RequiredConfirmations for Web3Connector {}
+
+impl SubscribeLatestBlocks for Web3Connector {}
+
+/// Over a websocket the node pushes every new head to us, over HTTP we can
+/// only poll.
+impl AnnounceBlocks for Web3Connector {
+    fn block_announcements(&self) -> BoxStream<'static, ()> {
+        match &self.client {
+            Transport::Http(_) => stream::pending().boxed(),
+            Transport::WebSocket(client) => new_heads(Arc::clone(client)).boxed(),
         }
     }
 }
 
+/// Yields whenever the node announces a new head.
+///
+/// A rejected or lost subscription is logged and followed by a new one, hence
+/// the stream never ends.
+fn new_heads(client: Arc<jsonrpc::ws::Client>) -> impl futures::Stream<Item = ()> {
+    stream::unfold(
+        (client, None::<BoxStream<'static, serde_json::Value>>),
+        |(client, heads)| async move {
+            let mut heads = heads;
+
+            loop {
+                let mut current = match heads.take() {
+                    Some(current) => current,
+                    None => match client
+                        .subscribe(jsonrpc::Request::new("eth_subscribe", vec!["newHeads"]))
+                        .await
+                    {
+                        Ok(current) => current,
+                        Err(e) => {
+                            tracing::warn!("failed to subscribe to new heads: {}", e);
+                            tokio::time::delay_for(RESUBSCRIBE_DELAY).await;
+                            continue;
+                        }
+                    },
+                };
+
+                match current.next().await {
+                    Some(head) => {
+                        tracing::debug!("node announced block {}", head["number"]);
+                        return Some(((), (client, Some(current))));
+                    }
+                    None => {
+                        tracing::warn!("lost the subscription to new heads");
+                        tokio::time::delay_for(RESUBSCRIBE_DELAY).await;
+                    }
+                }
+            }
+        },
+    )
+}
+
 #[async_trait]
 impl LatestBlock for Web3Connector {
     type Block = crate::ethereum::Block;
@@ -78,6 +192,93 @@ impl ReceiptByHash for Web3Connector {
     }
 }
 
+#[async_trait]
+impl TransactionByHash for Web3Connector {
+    async fn transaction_by_hash(&self, transaction_hash: Hash) -> anyhow::Result<Transaction> {
+        let transaction = self
+            .client
+            .send(jsonrpc::Request::new("eth_getTransactionByHash", vec![
+                jsonrpc::serialize(transaction_hash)?,
+            ]))
+            .await?;
+
+        tracing::trace!("Fetched transaction from web3: {:x}", transaction_hash);
+
+        Ok(transaction)
+    }
+}
+
+/// Looks at the pending block, the node only exposes its transaction pool
+/// through non-standard methods.
+///
+/// The pending block only holds the transactions the node would include next,
+/// a transaction with a low gas price may not show up before it is mined.
+#[async_trait]
+impl PendingTransactions for Web3Connector {
+    type TransactionId = Hash;
+    type Transaction = Transaction;
+
+    async fn pending_transaction_ids(&self) -> anyhow::Result<Vec<Hash>> {
+        let block: PendingBlock = self
+            .client
+            .send(jsonrpc::Request::new("eth_getBlockByNumber", vec![
+                jsonrpc::serialize("pending")?,
+                jsonrpc::serialize(false)?,
+            ]))
+            .await?;
+
+        tracing::trace!(
+            "Fetched {} pending transaction hashes from web3",
+            block.transactions.len()
+        );
+
+        Ok(block.transactions)
+    }
+
+    async fn pending_transaction(&self, transaction_hash: Hash) -> anyhow::Result<Transaction> {
+        self.transaction_by_hash(transaction_hash).await
+    }
+}
+
+#[async_trait]
+impl BlockByNumber for Web3Connector {
+    async fn block_by_number(&self, number: U256) -> anyhow::Result<Block> {
+        let block = self
+            .client
+            .send(jsonrpc::Request::new("eth_getBlockByNumber", vec![
+                jsonrpc::serialize(number)?,
+                jsonrpc::serialize(true)?,
+            ]))
+            .await?;
+
+        tracing::trace!("Fetched block from web3: {}", number);
+
+        Ok(block)
+    }
+}
+
+#[async_trait]
+impl LogsByFilter for Web3Connector {
+    async fn logs_by_filter(&self, filter: &LogFilter) -> anyhow::Result<Vec<Log>> {
+        let logs: Vec<Log> = self
+            .client
+            .send(jsonrpc::Request::new("eth_getLogs", vec![
+                jsonrpc::serialize(filter)?,
+            ]))
+            .await?;
+
+        tracing::trace!("Fetched {} logs from web3", logs.len());
+
+        Ok(logs)
+    }
+}
+
+impl EventMatchingStrategy for Web3Connector {
+    fn event_matching(&self) -> EventMatching {
+        self.event_matching
+    }
+}
+
 #[async_trait]
 impl FetchNetworkId<ChainId> for Web3Connector {
     async fn network_id(&self) -> anyhow::Result<ChainId> {
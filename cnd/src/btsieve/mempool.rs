@@ -0,0 +1,117 @@
+use crate::btsieve::PendingTransactions;
+use std::{collections::HashMap, fmt, hash::Hash, sync::Arc, time::Duration};
+use tokio::sync::watch;
+
+/// The transactions that were pending when the node was last polled.
+pub type PendingTransactionsSnapshot<T> = Arc<Vec<Arc<T>>>;
+
+/// Abstracts over how a watcher learns about pending transactions.
+pub trait SubscribeMempool {
+    type Transaction;
+
+    fn subscribe_mempool(&self) -> MempoolSubscription<Self::Transaction>;
+}
+
+/// The pending transactions of a ledger as seen by a single watcher.
+#[derive(Debug)]
+pub struct MempoolSubscription<T>(watch::Receiver<PendingTransactionsSnapshot<T>>);
+
+impl<T> MempoolSubscription<T> {
+    /// Returns the current pending transactions on the first call and waits
+    /// for them to change on every further call.
+    pub async fn next(&mut self) -> anyhow::Result<PendingTransactionsSnapshot<T>> {
+        self.0
+            .recv()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("pending transactions are no longer published"))
+    }
+}
+
+/// Publishes the pending transactions of a ledger to all subscribed watchers.
+///
+/// Like `LatestBlocks`, this way the node is polled once per interval no
+/// matter how many swaps we are watching. Each pending transaction is only
+/// fetched once, no matter for how many polls it stays pending.
+#[derive(Debug)]
+pub struct Mempool<T> {
+    sender: watch::Sender<PendingTransactionsSnapshot<T>>,
+    receiver: watch::Receiver<PendingTransactionsSnapshot<T>>,
+}
+
+impl<T> Default for Mempool<T> {
+    fn default() -> Self {
+        let (sender, receiver) = watch::channel(Arc::new(Vec::new()));
+
+        Self { sender, receiver }
+    }
+}
+
+impl<T> SubscribeMempool for Mempool<T> {
+    type Transaction = T;
+
+    fn subscribe_mempool(&self) -> MempoolSubscription<T> {
+        self.subscribe()
+    }
+}
+
+impl<T> Mempool<T> {
+    pub fn subscribe(&self) -> MempoolSubscription<T> {
+        MempoolSubscription(self.receiver.clone())
+    }
+
+    /// Polls the pending transactions of the connector and publishes them
+    /// whenever they changed.
+    pub async fn poll<C, I>(&self, connector: &C, interval: Duration)
+    where
+        C: PendingTransactions<TransactionId = I, Transaction = T>,
+        I: Eq + Hash + Clone + fmt::Debug,
+    {
+        let mut pending = HashMap::new();
+
+        loop {
+            match connector.pending_transaction_ids().await {
+                Ok(ids) => {
+                    let mut still_pending = HashMap::with_capacity(ids.len());
+                    let mut changed = false;
+
+                    for id in ids {
+                        let transaction = match pending.remove(&id) {
+                            Some(transaction) => transaction,
+                            // The transaction may have been mined or dropped since it was listed.
+                            None => match connector.pending_transaction(id.clone()).await {
+                                Ok(transaction) => {
+                                    changed = true;
+                                    Arc::new(transaction)
+                                }
+                                Err(e) => {
+                                    tracing::debug!(
+                                        "failed to fetch pending transaction {:?}: {:#}",
+                                        id,
+                                        e
+                                    );
+                                    continue;
+                                }
+                            },
+                        };
+
+                        still_pending.insert(id, transaction);
+                    }
+
+                    // Whatever is left in `pending` is no longer in the pool.
+                    changed |= !pending.is_empty();
+                    pending = still_pending;
+
+                    if changed {
+                        let snapshot = pending.values().cloned().collect();
+
+                        // We hold a receiver ourselves, hence this never fails.
+                        let _ = self.sender.broadcast(Arc::new(snapshot));
+                    }
+                }
+                Err(e) => tracing::warn!("failed to fetch pending transactions: {:#}", e),
+            }
+
+            tokio::time::delay_for(interval).await;
+        }
+    }
+}
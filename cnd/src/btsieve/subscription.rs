@@ -0,0 +1,153 @@
+use crate::btsieve::{BlockHash, LatestBlock};
+use futures::{
+    future::{self, Either},
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Abstracts over how a watcher learns about new latest blocks.
+///
+/// By default every watcher polls the connector itself. Connectors that are
+/// shared between all swaps of a ledger poll once and fan the latest block
+/// out to every subscriber instead.
+pub trait SubscribeLatestBlocks: LatestBlock + Sized + Sync {
+    fn subscribe(&self) -> Subscription<'_, Self::Block> {
+        Subscription::Poll {
+            connector: self,
+            interval: self.poll_interval(),
+            polled: false,
+        }
+    }
+
+    /// How long a polling subscriber waits before asking for the latest
+    /// block again.
+    fn poll_interval(&self) -> Duration {
+        Duration::from_secs(1)
+    }
+}
+
+/// Abstracts over nodes that announce new blocks as they see them.
+///
+/// An announcement lets us fetch the new latest block right away instead of
+/// on the next poll. Nodes that do not announce blocks are only polled.
+pub trait AnnounceBlocks {
+    fn block_announcements(&self) -> BoxStream<'static, ()> {
+        stream::pending().boxed()
+    }
+}
+
+/// The latest blocks of a ledger as seen by a single watcher.
+pub enum Subscription<'c, B> {
+    Poll {
+        connector: &'c (dyn LatestBlock<Block = B> + Sync),
+        interval: Duration,
+        polled: bool,
+    },
+    Shared(watch::Receiver<Option<B>>),
+}
+
+impl<'c, B> Subscription<'c, B>
+where
+    B: Clone,
+{
+    /// Returns the current latest block on the first call and waits for the
+    /// next one on every further call.
+    pub async fn next(&mut self) -> anyhow::Result<B> {
+        match self {
+            Subscription::Poll {
+                connector,
+                interval,
+                polled,
+            } => {
+                if *polled {
+                    tokio::time::delay_for(*interval).await;
+                }
+                *polled = true;
+
+                connector.latest_block().await
+            }
+            Subscription::Shared(receiver) => loop {
+                match receiver.recv().await {
+                    Some(Some(block)) => return Ok(block),
+                    // Nothing has been polled yet.
+                    Some(None) => continue,
+                    None => anyhow::bail!("latest blocks are no longer published"),
+                }
+            },
+        }
+    }
+}
+
+/// Publishes the latest block of a ledger to all subscribed watchers.
+///
+/// This way the node is polled once per interval, no matter how many swaps
+/// we are watching.
+#[derive(Debug)]
+pub struct LatestBlocks<B> {
+    sender: watch::Sender<Option<B>>,
+    receiver: watch::Receiver<Option<B>>,
+}
+
+impl<B> Default for LatestBlocks<B>
+where
+    B: Clone,
+{
+    fn default() -> Self {
+        let (sender, receiver) = watch::channel(None);
+
+        Self { sender, receiver }
+    }
+}
+
+impl<B, H> LatestBlocks<B>
+where
+    B: BlockHash<BlockHash = H> + Clone,
+    H: PartialEq,
+{
+    pub fn subscribe(&self) -> Subscription<'static, B> {
+        Subscription::Shared(self.receiver.clone())
+    }
+
+    /// Polls the latest block of the connector and publishes it whenever the
+    /// tip of the chain changed.
+    ///
+    /// Blocks announced by the node are fetched right away, without waiting
+    /// for the next poll.
+    pub async fn poll<C>(&self, connector: &C, interval: Duration)
+    where
+        C: LatestBlock<Block = B> + AnnounceBlocks,
+    {
+        let mut announcements = Some(connector.block_announcements());
+
+        loop {
+            match connector.latest_block().await {
+                Ok(block) => {
+                    let is_new_tip = self
+                        .receiver
+                        .borrow()
+                        .as_ref()
+                        .map_or(true, |tip| tip.block_hash() != block.block_hash());
+
+                    if is_new_tip {
+                        // We hold a receiver ourselves, hence this never fails.
+                        let _ = self.sender.broadcast(Some(block));
+                    }
+                }
+                Err(e) => tracing::warn!("failed to fetch the latest block: {:#}", e),
+            }
+
+            let delay = tokio::time::delay_for(interval);
+            match announcements.as_mut() {
+                Some(stream) => {
+                    if let Either::Right((None, _)) = future::select(delay, stream.next()).await {
+                        tracing::warn!("block announcements ended, only polling from now on");
+                        announcements = None;
+                    }
+                }
+                None => delay.await,
+            }
+        }
+    }
+}
@@ -10,6 +10,22 @@ pub struct Options {
     #[structopt(long = "dump-config")]
     pub dump_config: bool,
 
+    /// Path to a file containing the passphrase of the encrypted seed file
+    #[structopt(long = "seed-passphrase-file", parse(from_os_str))]
+    pub seed_passphrase_file: Option<PathBuf>,
+
+    /// Encrypt the seed file with a passphrase and exit
+    #[structopt(long = "encrypt-seed")]
+    pub encrypt_seed: bool,
+
+    /// Print the mnemonic of the seed, generated if there is none yet, and exit
+    #[structopt(long = "show-mnemonic")]
+    pub show_mnemonic: bool,
+
+    /// Recreate the seed from its mnemonic, read from stdin, and exit
+    #[structopt(long = "restore-from-mnemonic")]
+    pub restore_from_mnemonic: bool,
+
     /// Display the current version
     #[structopt(short = "V", long = "version")]
     pub version: bool,
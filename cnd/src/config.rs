@@ -1,10 +1,14 @@
 pub mod file;
 mod serde_bitcoin_network;
+mod serde_peer_ids;
 pub mod settings;
 pub mod validation;
 
-use crate::swap_protocols::ledger::ethereum;
-use libp2p::Multiaddr;
+use crate::{
+    asset, btsieve::ethereum::EventMatching, db::Eviction, identity,
+    swap_protocols::ledger::ethereum,
+};
+use libp2p::{Multiaddr, PeerId};
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -29,31 +33,56 @@ pub struct Network {
 pub struct Bitcoin {
     #[serde(with = "crate::config::serde_bitcoin_network")]
     pub network: bitcoin::Network,
-    pub bitcoind: Bitcoind,
+    pub backend: BitcoinBackend,
+}
+
+/// The source of blocks for the Bitcoin ledger.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BitcoinBackend {
+    Bitcoind(Bitcoind),
+    Esplora(Esplora),
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Bitcoind {
     pub node_url: Url,
+    /// The `-zmqpubhashblock` endpoint of bitcoind, e.g.
+    /// `tcp://127.0.0.1:28332`. Without it, we only learn about new blocks by
+    /// polling.
+    pub zmqpubhashblock: Option<Url>,
+}
+
+/// An Esplora-compatible HTTP API, e.g. `https://blockstream.info/api`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Esplora {
+    pub url: Url,
 }
 
 impl Default for Bitcoin {
     fn default() -> Self {
         Self {
             network: bitcoin::Network::Regtest,
-            bitcoind: Bitcoind {
+            backend: BitcoinBackend::Bitcoind(Bitcoind {
                 node_url: Url::parse("http://localhost:18443")
                     .expect("static string to be a valid url"),
-            },
+                zmqpubhashblock: None,
+            }),
         }
     }
 }
 
 impl From<Bitcoin> for file::Bitcoin {
     fn from(bitcoin: Bitcoin) -> Self {
+        let (bitcoind, esplora) = match bitcoin.backend {
+            BitcoinBackend::Bitcoind(bitcoind) => (Some(bitcoind), None),
+            BitcoinBackend::Esplora(esplora) => (None, Some(esplora)),
+        };
+
         file::Bitcoin {
             network: bitcoin.network,
-            bitcoind: Some(bitcoin.bitcoind),
+            bitcoind,
+            esplora,
         }
     }
 }
@@ -77,17 +106,199 @@ impl Default for Ethereum {
     fn default() -> Self {
         Self {
             chain_id: ethereum::ChainId::regtest(),
-            parity: Parity {
-                node_url: Url::parse("http://localhost:8545")
-                    .expect("static string to be a valid url"),
-            },
+            parity: Parity::default(),
         }
     }
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Parity {
+    /// A `ws://` url lets the node push new blocks to us instead of us polling
+    /// for them.
     pub node_url: Url,
+    /// How the logs of HTLCs are found, `eth_getLogs` is a lot faster but not
+    /// supported by all nodes.
+    #[serde(default)]
+    pub event_matching: EventMatching,
+}
+
+impl Default for Parity {
+    fn default() -> Self {
+        Self {
+            node_url: Url::parse("http://localhost:8545").expect("static string to be a valid url"),
+            event_matching: EventMatching::default(),
+        }
+    }
+}
+
+/// The number of seconds an expiry has to be ahead of the latest block time
+/// of its ledger, for a transaction to reliably confirm before the HTLC
+/// expires.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ExpiryMargins {
+    pub bitcoin: u32,
+    pub ethereum: u32,
+}
+
+impl Default for ExpiryMargins {
+    fn default() -> Self {
+        Self {
+            bitcoin: 2 * 60 * 60,
+            ethereum: 60 * 60,
+        }
+    }
+}
+
+/// The number of blocks, including the one it is in, a transaction has to be
+/// buried under before we act on it.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Confirmations {
+    pub bitcoin: u32,
+    pub ethereum: u32,
+}
+
+impl Default for Confirmations {
+    fn default() -> Self {
+        Self {
+            bitcoin: 1,
+            ethereum: 1,
+        }
+    }
+}
+
+/// Whether to watch unconfirmed transactions of a ledger.
+///
+/// This reveals the secret of a redeemed HTLC as soon as the redeem
+/// transaction is broadcast instead of once it is confirmed. The HTLC is only
+/// considered redeemed once the transaction is confirmed.
+///
+/// On Ethereum we look at the pending block of the node, which only holds the
+/// transactions it would include next and not its whole transaction pool.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Mempool {
+    pub bitcoin: bool,
+    pub ethereum: bool,
+}
+
+/// Keeps the blocks and receipts fetched from the ledger nodes in the
+/// database, so swaps resumed after a restart do not fetch them again.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct BlockCache {
+    pub persistent: bool,
+    /// The number of blocks and receipts of each ledger kept in the database.
+    pub capacity: u32,
+    pub eviction: Eviction,
+}
+
+impl Default for BlockCache {
+    fn default() -> Self {
+        Self {
+            persistent: false,
+            capacity: 10_000,
+            eviction: Eviction::default(),
+        }
+    }
+}
+
+/// Requires every request to the HTTP API to carry one of these tokens as a
+/// bearer token.
+///
+/// Tokens which are not configured are generated once and stored in the data
+/// directory.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Auth {
+    /// Grants access to all routes, including those creating swaps and
+    /// returning their actions.
+    pub admin_token: Option<String>,
+    /// Grants access to the routes which only read swaps, peers and offers.
+    pub read_only_token: Option<String>,
+}
+
+/// Serves the HTTP API over TLS.
+///
+/// Without a certificate and key, a self-signed certificate is generated once
+/// and stored in the data directory.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Tls {
+    /// A PEM encoded certificate chain.
+    pub cert: Option<PathBuf>,
+    /// The PEM encoded PKCS#8 or RSA private key of the certificate.
+    pub key: Option<PathBuf>,
+    /// Domains a generated certificate is valid for, besides `localhost`.
+    #[serde(default)]
+    pub extra_domains: Vec<String>,
+}
+
+/// Configures the autopilot which redeems and refunds rfc003 swaps without
+/// the need for a client to execute the actions.
+///
+/// Only Bitcoin HTLCs are redeemed and refunded. cnd has no Ethereum wallet to
+/// pay for gas, hence redeeming and refunding Ethereum HTLCs still needs a
+/// client.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Autopilot {
+    pub bitcoin: AutopilotBitcoin,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AutopilotBitcoin {
+    /// The address redeemed and refunded bitcoin is sent to.
+    pub address: bitcoin::Address,
+    /// The fee rate for redeem and refund transactions in satoshi per weight
+    /// unit, only used if the Bitcoin backend cannot estimate one.
+    pub fee_per_wu: usize,
+}
+
+/// Configures the policy which accepts or declines incoming rfc003 swap
+/// requests without the need for a client to do so.
+///
+/// Requests on ledgers that are not configured here are declined. Requests
+/// involving ERC20 tokens cannot be configured and are left for the user to
+/// decide on.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Policy {
+    /// The address used to redeem or refund ether of accepted swaps.
+    pub ethereum_identity: identity::Ethereum,
+    /// Only requests of these peers are accepted, requests of all peers are
+    /// considered if empty.
+    #[serde(default, with = "crate::config::serde_peer_ids")]
+    pub allowed_peers: Vec<PeerId>,
+    /// The minimum number of seconds between the expiry of the beta and the
+    /// alpha HTLC.
+    pub min_expiry_gap: u32,
+    pub bitcoin: Option<PolicyBitcoin>,
+    pub ethereum: Option<PolicyEthereum>,
+    pub rates: Rates,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PolicyBitcoin {
+    #[serde(with = "crate::config::serde_bitcoin_network")]
+    pub network: bitcoin::Network,
+    /// The smallest amount in satoshi we are willing to swap.
+    pub min_amount: u64,
+    /// The largest amount in satoshi we are willing to swap.
+    pub max_amount: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PolicyEthereum {
+    pub chain_id: ethereum::ChainId,
+    /// The smallest amount in wei we are willing to swap.
+    pub min_amount: asset::Ether,
+    /// The largest amount in wei we are willing to swap.
+    pub max_amount: asset::Ether,
+}
+
+/// The prices, in wei per bitcoin, at which we are willing to swap.
+///
+/// We do not swap in a direction whose rate is not configured.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Rates {
+    /// The lowest price at which we sell bitcoin for ether.
+    pub sell_bitcoin: Option<asset::Ether>,
+    /// The highest price at which we buy bitcoin with ether.
+    pub buy_bitcoin: Option<asset::Ether>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize)]
@@ -167,6 +378,7 @@ fn default_lnd_readonly_macaroon_path(lnd_dir: PathBuf, network: bitcoin::Networ
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::asset::ethereum::FromWei;
 
     #[test]
     fn network_deserializes_correctly() {
@@ -238,4 +450,87 @@ mod tests {
 
         assert_eq!(actual, Ok(expected));
     }
+
+    #[test]
+    fn autopilot_deserializes_correctly() {
+        let actual = toml::from_str(
+            r#"
+            [bitcoin]
+            address = "bcrt1qq65dn87xjvxm7cuv5qesc5ayxkmre92pmfxn26"
+            fee_per_wu = 10
+            "#,
+        );
+
+        let expected = Autopilot {
+            bitcoin: AutopilotBitcoin {
+                address: "bcrt1qq65dn87xjvxm7cuv5qesc5ayxkmre92pmfxn26"
+                    .parse()
+                    .unwrap(),
+                fee_per_wu: 10,
+            },
+        };
+
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn policy_deserializes_correctly() {
+        let actual = toml::from_str(
+            r#"
+            ethereum_identity = "0x00a329c0648769a73afac7f9381e08fb43dbea72"
+            allowed_peers = ["QmXfGiwNESAFWUvDVJ4NLaKYYVopYdV5HbpDSgz5TSypkb"]
+            min_expiry_gap = 3600
+            [bitcoin]
+            network = "regtest"
+            min_amount = 10000
+            max_amount = 100000000
+            [ethereum]
+            chain_id = 1337
+            min_amount = "1000000000000000"
+            max_amount = "10000000000000000000"
+            [rates]
+            sell_bitcoin = "40000000000000000000"
+            "#,
+        );
+
+        let expected = Policy {
+            ethereum_identity: "0x00a329c0648769a73afac7f9381e08fb43dbea72"
+                .parse()
+                .unwrap(),
+            allowed_peers: vec!["QmXfGiwNESAFWUvDVJ4NLaKYYVopYdV5HbpDSgz5TSypkb"
+                .parse()
+                .unwrap()],
+            min_expiry_gap: 3600,
+            bitcoin: Some(PolicyBitcoin {
+                network: bitcoin::Network::Regtest,
+                min_amount: 10_000,
+                max_amount: 100_000_000,
+            }),
+            ethereum: Some(PolicyEthereum {
+                chain_id: ethereum::ChainId::regtest(),
+                min_amount: asset::Ether::from_wei(1_000_000_000_000_000u64),
+                max_amount: asset::Ether::from_wei(10_000_000_000_000_000_000u128),
+            }),
+            rates: Rates {
+                sell_bitcoin: Some(asset::Ether::from_wei(40_000_000_000_000_000_000u128)),
+                buy_bitcoin: None,
+            },
+        };
+
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn policy_with_invalid_allowed_peer_fails_to_deserialize() {
+        let actual = toml::from_str::<Policy>(
+            r#"
+            ethereum_identity = "0x00a329c0648769a73afac7f9381e08fb43dbea72"
+            allowed_peers = ["not-a-peer-id"]
+            min_expiry_gap = 3600
+            [rates]
+            "#,
+        );
+
+        assert!(actual.is_err());
+    }
 }
@@ -1,5 +1,8 @@
 use crate::{
-    config::{Bitcoind, Data, Network, Parity},
+    config::{
+        Auth, Autopilot, Bitcoind, BlockCache, Confirmations, Data, Esplora, ExpiryMargins,
+        Mempool, Network, Parity, Policy,
+    },
     swap_protocols::ledger::ethereum,
 };
 use config as config_rs;
@@ -25,6 +28,12 @@ pub struct File {
     pub bitcoin: Option<Bitcoin>,
     pub ethereum: Option<Ethereum>,
     pub lightning: Option<Lightning>,
+    pub expiry_margins: Option<ExpiryMargins>,
+    pub confirmations: Option<Confirmations>,
+    pub mempool: Option<Mempool>,
+    pub block_cache: Option<BlockCache>,
+    pub autopilot: Option<Autopilot>,
+    pub policy: Option<Policy>,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -32,6 +41,7 @@ pub struct Bitcoin {
     #[serde(with = "crate::config::serde_bitcoin_network")]
     pub network: bitcoin::Network,
     pub bitcoind: Option<Bitcoind>,
+    pub esplora: Option<Esplora>,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -62,6 +72,12 @@ impl File {
             bitcoin: Option::None,
             ethereum: Option::None,
             lightning: Option::None,
+            expiry_margins: Option::None,
+            confirmations: Option::None,
+            mempool: Option::None,
+            block_cache: Option::None,
+            autopilot: Option::None,
+            policy: Option::None,
         }
     }
 
@@ -120,6 +136,8 @@ impl From<Level> for LevelFilter {
 pub struct HttpApi {
     pub socket: SocketAddr,
     pub cors: Option<Cors>,
+    pub auth: Option<Auth>,
+    pub tls: Option<Tls>,
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
@@ -151,7 +169,9 @@ pub enum None {
 mod tests {
     use super::*;
     use crate::{
-        config::{Bitcoind, Parity, Settings},
+        btsieve::ethereum::EventMatching,
+        config::{AutopilotBitcoin, Bitcoind, Parity, Settings},
+        db::Eviction,
         swap_protocols::ledger::ethereum,
     };
     use reqwest::Url;
@@ -216,6 +236,12 @@ socket = "127.0.0.1:8000"
 [http_api.cors]
 allowed_origins = "all"
 
+[http_api.auth]
+read_only_token = "7f3a9c"
+
+[http_api.tls]
+extra_domains = ["cnd.example.com"]
+
 [data]
 dir = "/tmp/comit/"
 
@@ -227,12 +253,14 @@ network = "regtest"
 
 [bitcoin.bitcoind]
 node_url = "http://localhost:18443/"
+zmqpubhashblock = "tcp://127.0.0.1:28332"
 
 [ethereum]
 chain_id = 1337
 
 [ethereum.parity]
 node_url = "http://localhost:8545/"
+event_matching = "get_logs"
 
 [lightning]
 network = "regtest"
@@ -240,6 +268,27 @@ network = "regtest"
 [lightning.lnd]
 rest_api_url = "https://localhost:8080"
 dir = "/foo/bar"
+
+[expiry_margins]
+bitcoin = 7200
+ethereum = 3600
+
+[confirmations]
+bitcoin = 6
+ethereum = 12
+
+[mempool]
+bitcoin = true
+ethereum = false
+
+[block_cache]
+persistent = true
+capacity = 5000
+eviction = "oldest"
+
+[autopilot.bitcoin]
+address = "bcrt1qq65dn87xjvxm7cuv5qesc5ayxkmre92pmfxn26"
+fee_per_wu = 10
 "#;
         let file = File {
             network: Some(Network {
@@ -250,6 +299,15 @@ dir = "/foo/bar"
                 cors: Some(Cors {
                     allowed_origins: AllowedOrigins::All(All::All),
                 }),
+                auth: Some(Auth {
+                    admin_token: None,
+                    read_only_token: Some("7f3a9c".to_owned()),
+                }),
+                tls: Some(Tls {
+                    cert: None,
+                    key: None,
+                    extra_domains: vec!["cnd.example.com".to_owned()],
+                }),
             }),
             data: Some(Data {
                 dir: PathBuf::from("/tmp/comit/"),
@@ -261,12 +319,15 @@ dir = "/foo/bar"
                 network: bitcoin::Network::Regtest,
                 bitcoind: Some(Bitcoind {
                     node_url: "http://localhost:18443".parse().unwrap(),
+                    zmqpubhashblock: Some("tcp://127.0.0.1:28332".parse().unwrap()),
                 }),
+                esplora: None,
             }),
             ethereum: Some(Ethereum {
                 chain_id: ethereum::ChainId::regtest(),
                 parity: Some(Parity {
                     node_url: "http://localhost:8545".parse().unwrap(),
+                    event_matching: EventMatching::GetLogs,
                 }),
             }),
             lightning: Some(Lightning {
@@ -276,6 +337,32 @@ dir = "/foo/bar"
                     dir: PathBuf::from("/foo/bar"),
                 }),
             }),
+            expiry_margins: Some(ExpiryMargins {
+                bitcoin: 7200,
+                ethereum: 3600,
+            }),
+            confirmations: Some(Confirmations {
+                bitcoin: 6,
+                ethereum: 12,
+            }),
+            mempool: Some(Mempool {
+                bitcoin: true,
+                ethereum: false,
+            }),
+            block_cache: Some(BlockCache {
+                persistent: true,
+                capacity: 5000,
+                eviction: Eviction::Oldest,
+            }),
+            autopilot: Some(Autopilot {
+                bitcoin: AutopilotBitcoin {
+                    address: "bcrt1qq65dn87xjvxm7cuv5qesc5ayxkmre92pmfxn26"
+                        .parse()
+                        .unwrap(),
+                    fee_per_wu: 10,
+                },
+            }),
+            policy: None,
         };
 
         let config = toml::from_str::<File>(contents);
@@ -317,6 +404,11 @@ dir = "/foo/bar"
             [bitcoind]
             node_url = "http://example.com:18443"
             "#,
+            r#"
+            network = "mainnet"
+            [esplora]
+            url = "https://blockstream.info/api"
+            "#,
         ];
 
         let expected = vec![
@@ -324,18 +416,31 @@ dir = "/foo/bar"
                 network: bitcoin::Network::Bitcoin,
                 bitcoind: Some(Bitcoind {
                     node_url: Url::parse("http://example.com:8332").unwrap(),
+                    zmqpubhashblock: None,
                 }),
+                esplora: None,
             },
             Bitcoin {
                 network: bitcoin::Network::Testnet,
                 bitcoind: Some(Bitcoind {
                     node_url: Url::parse("http://example.com:18332").unwrap(),
+                    zmqpubhashblock: None,
                 }),
+                esplora: None,
             },
             Bitcoin {
                 network: bitcoin::Network::Regtest,
                 bitcoind: Some(Bitcoind {
                     node_url: Url::parse("http://example.com:18443").unwrap(),
+                    zmqpubhashblock: None,
+                }),
+                esplora: None,
+            },
+            Bitcoin {
+                network: bitcoin::Network::Bitcoin,
+                bitcoind: None,
+                esplora: Some(Esplora {
+                    url: Url::parse("https://blockstream.info/api").unwrap(),
                 }),
             },
         ];
@@ -374,18 +479,21 @@ dir = "/foo/bar"
                 chain_id: ethereum::ChainId::regtest(),
                 parity: Some(Parity {
                     node_url: Url::parse("http://example.com:8545").unwrap(),
+                    event_matching: EventMatching::BlockWalking,
                 }),
             },
             Ethereum {
                 chain_id: ethereum::ChainId::ropsten(),
                 parity: Some(Parity {
                     node_url: Url::parse("http://example.com:8545").unwrap(),
+                    event_matching: EventMatching::BlockWalking,
                 }),
             },
             Ethereum {
                 chain_id: ethereum::ChainId::mainnet(),
                 parity: Some(Parity {
                     node_url: Url::parse("http://example.com:8545").unwrap(),
+                    event_matching: EventMatching::BlockWalking,
                 }),
             },
         ];
@@ -0,0 +1,25 @@
+use libp2p::PeerId;
+use serde::{de, Deserialize, Deserializer, Serializer};
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<PeerId>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .into_iter()
+        .map(|peer_id| {
+            peer_id
+                .parse()
+                .map_err(|_| de::Error::custom(format!("invalid peer id {}", peer_id)))
+        })
+        .collect()
+}
+
+// This is the API serde expects, can't do much about the `&Vec` :(
+#[allow(clippy::ptr_arg)]
+pub fn serialize<S>(value: &Vec<PeerId>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.collect_seq(value.iter().map(PeerId::to_base58))
+}
@@ -1,6 +1,7 @@
 use crate::config::{
-    default_lnd_cert_path, default_lnd_readonly_macaroon_path, file, Bitcoin, Bitcoind, Data,
-    Ethereum, File, Lightning, Lnd, Network, Parity,
+    default_lnd_cert_path, default_lnd_readonly_macaroon_path, file, Auth, Autopilot, Bitcoin,
+    BitcoinBackend, Bitcoind, BlockCache, Confirmations, Data, Ethereum, ExpiryMargins, File,
+    Lightning, Lnd, Mempool, Network, Policy, Tls,
 };
 use anyhow::Context;
 use log::LevelFilter;
@@ -22,50 +23,70 @@ pub struct Settings {
     pub bitcoin: Bitcoin,
     pub ethereum: Ethereum,
     pub lightning: Lightning,
+    pub expiry_margins: ExpiryMargins,
+    pub confirmations: Confirmations,
+    pub mempool: Mempool,
+    pub block_cache: BlockCache,
+    pub autopilot: Option<Autopilot>,
+    pub policy: Option<Policy>,
 }
 
-fn derive_url_bitcoin(bitcoin: Option<file::Bitcoin>) -> Bitcoin {
-    match bitcoin {
+fn derive_url_bitcoin(bitcoin: Option<file::Bitcoin>) -> anyhow::Result<Bitcoin> {
+    let bitcoin = match bitcoin {
         None => Bitcoin::default(),
+        Some(file::Bitcoin {
+            network,
+            bitcoind: Some(_),
+            esplora: Some(_),
+        }) => anyhow::bail!(
+            "only one of bitcoind and esplora can be configured for {}",
+            network
+        ),
+        Some(file::Bitcoin {
+            network,
+            esplora: Some(esplora),
+            ..
+        }) => Bitcoin {
+            network,
+            backend: BitcoinBackend::Esplora(esplora),
+        },
         Some(bitcoin) => {
-            let node_url = match bitcoin.bitcoind {
-                Some(bitcoind) => bitcoind.node_url,
-                None => match bitcoin.network {
-                    bitcoin::Network::Bitcoin => "http://localhost:8332"
-                        .parse()
-                        .expect("to be valid static string"),
-                    bitcoin::Network::Testnet => "http://localhost:18332"
-                        .parse()
-                        .expect("to be valid static string"),
-                    bitcoin::Network::Regtest => "http://localhost:18443"
-                        .parse()
-                        .expect("to be valid static string"),
+            let bitcoind = match bitcoin.bitcoind {
+                Some(bitcoind) => bitcoind,
+                None => Bitcoind {
+                    node_url: match bitcoin.network {
+                        bitcoin::Network::Bitcoin => "http://localhost:8332"
+                            .parse()
+                            .expect("to be valid static string"),
+                        bitcoin::Network::Testnet => "http://localhost:18332"
+                            .parse()
+                            .expect("to be valid static string"),
+                        bitcoin::Network::Regtest => "http://localhost:18443"
+                            .parse()
+                            .expect("to be valid static string"),
+                    },
+                    zmqpubhashblock: None,
                 },
             };
             Bitcoin {
                 network: bitcoin.network,
-                bitcoind: Bitcoind { node_url },
+                backend: BitcoinBackend::Bitcoind(bitcoind),
             }
         }
-    }
+    };
+
+    Ok(bitcoin)
 }
 
 fn derive_url_ethereum(ethereum: Option<file::Ethereum>) -> Ethereum {
     match ethereum {
         None => Ethereum::default(),
         Some(ethereum) => {
-            let node_url = match ethereum.parity {
-                None => {
-                    // default is always localhost:8545
-                    "http://localhost:8545"
-                        .parse()
-                        .expect("to be valid static string")
-                }
-                Some(parity) => parity.node_url,
-            };
+            // default is always localhost:8545
+            let parity = ethereum.parity.unwrap_or_default();
             Ethereum {
                 chain_id: ethereum.chain_id,
-                parity: Parity { node_url },
+                parity,
             }
         }
     }
@@ -83,12 +104,24 @@ impl From<Settings> for File {
     fn from(settings: Settings) -> Self {
         let Settings {
             network,
-            http_api: HttpApi { socket, cors },
+            http_api:
+                HttpApi {
+                    socket,
+                    cors,
+                    auth,
+                    tls,
+                },
             data,
             logging: Logging { level },
             bitcoin,
             ethereum,
             lightning,
+            expiry_margins,
+            confirmations,
+            mempool,
+            block_cache,
+            autopilot,
+            policy,
         } = settings;
 
         File {
@@ -102,6 +135,8 @@ impl From<Settings> for File {
                         AllowedOrigins::Some(origins) => file::AllowedOrigins::Some(origins),
                     },
                 }),
+                auth,
+                tls,
             }),
             data: Some(data),
             logging: Some(file::Logging {
@@ -110,6 +145,12 @@ impl From<Settings> for File {
             bitcoin: Some(bitcoin.into()),
             ethereum: Some(ethereum.into()),
             lightning: Some(lightning.into()),
+            expiry_margins: Some(expiry_margins),
+            confirmations: Some(confirmations),
+            mempool: Some(mempool),
+            block_cache: Some(block_cache),
+            autopilot,
+            policy,
         }
     }
 }
@@ -118,6 +159,10 @@ impl From<Settings> for File {
 pub struct HttpApi {
     pub socket: SocketAddr,
     pub cors: Cors,
+    /// Requests are not authenticated if absent.
+    pub auth: Option<Auth>,
+    /// Plain HTTP is served if absent.
+    pub tls: Option<Tls>,
 }
 
 impl Default for HttpApi {
@@ -125,6 +170,8 @@ impl Default for HttpApi {
         Self {
             socket: SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 8000),
             cors: Cors::default(),
+            auth: None,
+            tls: None,
         }
     }
 }
@@ -166,6 +213,12 @@ impl Settings {
             bitcoin,
             ethereum,
             lightning,
+            expiry_margins,
+            confirmations,
+            mempool,
+            block_cache,
+            autopilot,
+            policy,
         } = config_file;
 
         Ok(Self {
@@ -179,23 +232,35 @@ impl Settings {
                 }
             }),
             http_api: http_api
-                .map(|file::HttpApi { socket, cors }| {
-                    let cors = cors
-                        .map(|cors| {
-                            let allowed_origins = match cors.allowed_origins {
-                                file::AllowedOrigins::All(_) => AllowedOrigins::All,
-                                file::AllowedOrigins::None(_) => AllowedOrigins::None,
-                                file::AllowedOrigins::Some(origins) => {
-                                    AllowedOrigins::Some(origins)
-                                }
-                            };
-
-                            Cors { allowed_origins }
-                        })
-                        .unwrap_or_default();
-
-                    HttpApi { socket, cors }
-                })
+                .map(
+                    |file::HttpApi {
+                         socket,
+                         cors,
+                         auth,
+                         tls,
+                     }| {
+                        let cors = cors
+                            .map(|cors| {
+                                let allowed_origins = match cors.allowed_origins {
+                                    file::AllowedOrigins::All(_) => AllowedOrigins::All,
+                                    file::AllowedOrigins::None(_) => AllowedOrigins::None,
+                                    file::AllowedOrigins::Some(origins) => {
+                                        AllowedOrigins::Some(origins)
+                                    }
+                                };
+
+                                Cors { allowed_origins }
+                            })
+                            .unwrap_or_default();
+
+                        HttpApi {
+                            socket,
+                            cors,
+                            auth,
+                            tls,
+                        }
+                    },
+                )
                 .unwrap_or_default(),
             data: {
                 let default_data_dir =
@@ -216,7 +281,7 @@ impl Settings {
                     },
                 }
             },
-            bitcoin: derive_url_bitcoin(bitcoin),
+            bitcoin: derive_url_bitcoin(bitcoin)?,
             ethereum: derive_url_ethereum(ethereum),
             lightning: match lightning {
                 None => Lightning::default(),
@@ -236,6 +301,12 @@ impl Settings {
                     },
                 },
             },
+            expiry_margins: expiry_margins.unwrap_or_default(),
+            confirmations: confirmations.unwrap_or_default(),
+            mempool: mempool.unwrap_or_default(),
+            block_cache: block_cache.unwrap_or_default(),
+            autopilot,
+            policy,
         })
     }
 }
@@ -244,7 +315,11 @@ impl Settings {
 mod tests {
 
     use super::*;
-    use crate::{config::file, swap_protocols::ledger::ethereum};
+    use crate::{
+        btsieve::ethereum::EventMatching,
+        config::{file, Esplora, Parity},
+        swap_protocols::ledger::ethereum,
+    };
     use spectral::prelude::*;
     use std::net::IpAddr;
 
@@ -271,6 +346,8 @@ mod tests {
             http_api: Some(file::HttpApi {
                 socket: SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 8000),
                 cors: None,
+                auth: None,
+                tls: None,
             }),
             ..File::default()
         };
@@ -302,6 +379,8 @@ mod tests {
                 cors: Cors {
                     allowed_origins: AllowedOrigins::None,
                 },
+                auth: None,
+                tls: None,
             })
     }
 
@@ -333,9 +412,10 @@ mod tests {
             .map(|settings| &settings.bitcoin)
             .is_equal_to(Bitcoin {
                 network: bitcoin::Network::Regtest,
-                bitcoind: Bitcoind {
+                backend: BitcoinBackend::Bitcoind(Bitcoind {
                     node_url: "http://localhost:18443".parse().unwrap(),
-                },
+                    zmqpubhashblock: None,
+                }),
             })
     }
 
@@ -352,6 +432,7 @@ mod tests {
                 bitcoin: Some(file::Bitcoin {
                     network,
                     bitcoind: None,
+                    esplora: None,
                 }),
                 ..File::default()
             };
@@ -363,13 +444,61 @@ mod tests {
                 .map(|settings| &settings.bitcoin)
                 .is_equal_to(Bitcoin {
                     network,
-                    bitcoind: Bitcoind {
+                    backend: BitcoinBackend::Bitcoind(Bitcoind {
                         node_url: url.parse().unwrap(),
-                    },
+                        zmqpubhashblock: None,
+                    }),
                 })
         }
     }
 
+    #[test]
+    fn bitcoin_esplora_backend() {
+        let config_file = File {
+            bitcoin: Some(file::Bitcoin {
+                network: bitcoin::Network::Bitcoin,
+                bitcoind: None,
+                esplora: Some(Esplora {
+                    url: "https://blockstream.info/api".parse().unwrap(),
+                }),
+            }),
+            ..File::default()
+        };
+
+        let settings = Settings::from_config_file_and_defaults(config_file);
+
+        assert_that(&settings)
+            .is_ok()
+            .map(|settings| &settings.bitcoin)
+            .is_equal_to(Bitcoin {
+                network: bitcoin::Network::Bitcoin,
+                backend: BitcoinBackend::Esplora(Esplora {
+                    url: "https://blockstream.info/api".parse().unwrap(),
+                }),
+            })
+    }
+
+    #[test]
+    fn bitcoind_and_esplora_are_mutually_exclusive() {
+        let config_file = File {
+            bitcoin: Some(file::Bitcoin {
+                network: bitcoin::Network::Bitcoin,
+                bitcoind: Some(Bitcoind {
+                    node_url: "http://localhost:8332".parse().unwrap(),
+                    zmqpubhashblock: None,
+                }),
+                esplora: Some(Esplora {
+                    url: "https://blockstream.info/api".parse().unwrap(),
+                }),
+            }),
+            ..File::default()
+        };
+
+        let settings = Settings::from_config_file_and_defaults(config_file);
+
+        assert_that(&settings).is_err();
+    }
+
     #[test]
     fn ethereum_defaults() {
         let config_file = File { ..File::default() };
@@ -383,6 +512,7 @@ mod tests {
                 chain_id: ethereum::ChainId::regtest(),
                 parity: Parity {
                     node_url: "http://localhost:8545".parse().unwrap(),
+                    event_matching: EventMatching::BlockWalking,
                 },
             })
     }
@@ -414,6 +544,7 @@ mod tests {
                     chain_id,
                     parity: Parity {
                         node_url: url.parse().unwrap(),
+                        event_matching: EventMatching::BlockWalking,
                     },
                 })
         }
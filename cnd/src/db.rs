@@ -1,5 +1,7 @@
+mod block_store;
 #[cfg(test)]
 mod integration_tests;
+mod ledger_events;
 mod load_swaps;
 mod save;
 mod schema;
@@ -8,15 +10,22 @@ mod wrapper_types;
 mod swap;
 #[macro_use]
 mod swap_types;
+mod swaps_page;
 #[macro_use]
 pub mod with_swap_types;
 embed_migrations!("./migrations");
 
 pub use self::{
-    load_swaps::{AcceptedSwap, LoadAcceptedSwap},
+    block_store::{BlockStore, Eviction, Item},
+    ledger_events::{LedgerEvent, LedgerSide, LoadLedgerEvents, RecordedEvent},
+    load_swaps::{
+        AcceptedSwap, HanEtherereumHalightBitcoinSwap, LoadAcceptedSwap,
+        LoadHanEtherereumHalightBitcoinSwaps,
+    },
     save::*,
     swap::*,
     swap_types::*,
+    swaps_page::{LoadSwapsPage, PageOrder, PagePosition, SwapsPageQuery},
 };
 
 use crate::{
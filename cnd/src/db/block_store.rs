@@ -0,0 +1,240 @@
+//! Keeps the blocks and receipts btsieve fetched from the ledger nodes, so
+//! resuming old swaps after a restart does not fetch them all over again.
+
+use crate::db::{schema::cached_blocks, Sqlite};
+use diesel::{
+    dsl::{count_star, max},
+    prelude::*,
+    sql_types::{BigInt, Text},
+    sqlite::SqliteConnection,
+};
+use serde::{Deserialize, Serialize};
+
+/// The kinds of entries in the store, each of them is evicted on its own.
+#[derive(Clone, Copy, Debug, PartialEq, strum_macros::Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum Item {
+    BitcoinBlock,
+    EthereumBlock,
+    EthereumReceipt,
+}
+
+/// Which entries are dropped once the store is full.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Eviction {
+    /// Drop the entries that have not been loaded for the longest time.
+    LeastRecentlyUsed,
+    /// Drop the entries that were stored first.
+    Oldest,
+}
+
+impl Default for Eviction {
+    fn default() -> Self {
+        Eviction::LeastRecentlyUsed
+    }
+}
+
+/// A persistent cache of encoded blocks and receipts, keyed by their hash.
+#[derive(Clone, Debug)]
+pub struct BlockStore {
+    db: Sqlite,
+    capacity: u32,
+    eviction: Eviction,
+}
+
+impl BlockStore {
+    /// Keeps at most `capacity` entries of every item.
+    pub fn new(db: Sqlite, capacity: u32, eviction: Eviction) -> Self {
+        Self {
+            db,
+            capacity,
+            eviction,
+        }
+    }
+
+    pub async fn load(&self, item: Item, hash: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let item = item.to_string();
+        let touch = self.eviction == Eviction::LeastRecentlyUsed;
+
+        let data = self
+            .db
+            .do_in_transaction(|connection| load(connection, &item, hash, touch))
+            .await?;
+
+        Ok(data)
+    }
+
+    /// Stores an entry unless it is already there, evicting others if the
+    /// store is full.
+    pub async fn save(&self, item: Item, hash: &str, data: Vec<u8>) -> anyhow::Result<()> {
+        let item = item.to_string();
+
+        self.db
+            .do_in_transaction(|connection| {
+                if save(connection, &item, hash, &data)? {
+                    evict(connection, &item, self.capacity, self.eviction)?;
+                }
+
+                Ok(())
+            })
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn load(
+    connection: &SqliteConnection,
+    item: &str,
+    hash: &str,
+    touch: bool,
+) -> QueryResult<Option<Vec<u8>>> {
+    let data = cached_blocks::table
+        .filter(cached_blocks::item.eq(item))
+        .filter(cached_blocks::hash.eq(hash))
+        .select(cached_blocks::data)
+        .first(connection)
+        .optional()?;
+
+    if data.is_some() && touch {
+        diesel::update(
+            cached_blocks::table
+                .filter(cached_blocks::item.eq(item))
+                .filter(cached_blocks::hash.eq(hash)),
+        )
+        .set(cached_blocks::last_used.eq(next_use(connection, item)?))
+        .execute(connection)?;
+    }
+
+    Ok(data)
+}
+
+/// Returns whether the entry was inserted, `false` if it was already there.
+fn save(connection: &SqliteConnection, item: &str, hash: &str, data: &[u8]) -> QueryResult<bool> {
+    let inserted = diesel::insert_or_ignore_into(cached_blocks::table)
+        .values((
+            cached_blocks::item.eq(item),
+            cached_blocks::hash.eq(hash),
+            cached_blocks::data.eq(data),
+            cached_blocks::last_used.eq(next_use(connection, item)?),
+        ))
+        .execute(connection)?;
+
+    Ok(inserted > 0)
+}
+
+fn evict(
+    connection: &SqliteConnection,
+    item: &str,
+    capacity: u32,
+    eviction: Eviction,
+) -> QueryResult<()> {
+    let entries = cached_blocks::table
+        .filter(cached_blocks::item.eq(item))
+        .select(count_star())
+        .first::<i64>(connection)?;
+
+    let excess = entries - i64::from(capacity);
+    if excess <= 0 {
+        return Ok(());
+    }
+
+    // Ids increase with every insert, hence ordering by them drops the oldest.
+    let order = match eviction {
+        Eviction::LeastRecentlyUsed => "last_used",
+        Eviction::Oldest => "id",
+    };
+
+    diesel::sql_query(format!(
+        "DELETE FROM cached_blocks WHERE id IN \
+         (SELECT id FROM cached_blocks WHERE item = ? ORDER BY {} ASC LIMIT ?)",
+        order
+    ))
+    .bind::<Text, _>(item)
+    .bind::<BigInt, _>(excess)
+    .execute(connection)?;
+
+    Ok(())
+}
+
+/// A counter rather than a timestamp, so entries used within the same
+/// instant are still ordered.
+///
+/// Entries are only ever compared to the ones of the same item, which lets
+/// us look up the maximum through the `(item, last_used)` index.
+fn next_use(connection: &SqliteConnection, item: &str) -> QueryResult<i64> {
+    let last_used = cached_blocks::table
+        .filter(cached_blocks::item.eq(item))
+        .select(max(cached_blocks::last_used))
+        .first::<Option<i64>>(connection)?;
+
+    Ok(last_used.unwrap_or(0) + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn store(capacity: u32, eviction: Eviction) -> (BlockStore, TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Sqlite::new_in_dir(dir.path()).unwrap();
+
+        (BlockStore::new(db, capacity, eviction), dir)
+    }
+
+    #[tokio::test]
+    async fn evicts_least_recently_used_entry() {
+        let (store, _dir) = store(2, Eviction::LeastRecentlyUsed);
+
+        store
+            .save(Item::BitcoinBlock, "a", b"1".to_vec())
+            .await
+            .unwrap();
+        store
+            .save(Item::BitcoinBlock, "b", b"2".to_vec())
+            .await
+            .unwrap();
+        store.load(Item::BitcoinBlock, "a").await.unwrap();
+        store
+            .save(Item::BitcoinBlock, "c", b"3".to_vec())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.load(Item::BitcoinBlock, "a").await.unwrap(),
+            Some(b"1".to_vec())
+        );
+        assert_eq!(store.load(Item::BitcoinBlock, "b").await.unwrap(), None);
+        assert_eq!(
+            store.load(Item::BitcoinBlock, "c").await.unwrap(),
+            Some(b"3".to_vec())
+        );
+    }
+
+    #[tokio::test]
+    async fn evicts_oldest_entry_of_the_same_item_only() {
+        let (store, _dir) = store(1, Eviction::Oldest);
+
+        store
+            .save(Item::EthereumBlock, "a", b"1".to_vec())
+            .await
+            .unwrap();
+        store
+            .save(Item::EthereumReceipt, "a", b"2".to_vec())
+            .await
+            .unwrap();
+        store.load(Item::EthereumBlock, "a").await.unwrap();
+        store
+            .save(Item::EthereumBlock, "b", b"3".to_vec())
+            .await
+            .unwrap();
+
+        assert_eq!(store.load(Item::EthereumBlock, "a").await.unwrap(), None);
+        assert_eq!(
+            store.load(Item::EthereumReceipt, "a").await.unwrap(),
+            Some(b"2".to_vec())
+        );
+    }
+}
@@ -1,17 +1,22 @@
 use crate::{
-    asset::{Bitcoin as BitcoinAsset, Erc20, Ether},
+    asset::{self, ethereum::FromWei, Bitcoin as BitcoinAsset, Erc20, Ether},
     db::{
         load_swaps::LoadAcceptedSwap,
         swap_types::{DetermineTypes, SwapTypes},
-        AssetKind, BitcoinLedgerKind, LedgerKind, Retrieve, Save, Sqlite, Swap,
+        AssetKind, BitcoinLedgerKind, CreatedSwap, FinalizedSwapParams, LedgerKind,
+        LoadHanEtherereumHalightBitcoinSwaps, Retrieve, Save, Sqlite, Swap,
     },
-    identity,
+    identity, lightning,
+    network::DialInformation,
     quickcheck::Quickcheck,
     swap_protocols::{
         ledger::Ethereum,
-        rfc003::{Accept, Request},
+        rfc003::{Accept, Request, SecretHash},
+        HanEtherereumHalightBitcoinCreateSwapParams, LocalSwapId, Role, SharedSwapId,
     },
+    timestamp::Timestamp,
 };
+use libp2p::PeerId;
 use std::path::Path;
 
 use crate::swap_protocols::ledger::bitcoin::{Mainnet, Regtest, Testnet};
@@ -27,7 +32,7 @@ macro_rules! db_roundtrip_test {
                 ) -> anyhow::Result<bool> {
 
                     // unpack the swap from the generic newtype
-                    let Swap { swap_id, role, counterparty } = swap.0;
+                    let Swap { swap_id, role, counterparty, created_at } = swap.0;
 
                     // construct the expected swap types from the function we get passed in order to enrich it with the role
                     let expected_swap_types = ($expected_swap_types_fn)(role);
@@ -37,7 +42,8 @@ macro_rules! db_roundtrip_test {
                     let saved_swap = Swap {
                         swap_id,
                         role,
-                        counterparty
+                        counterparty,
+                        created_at
                     };
                     let saved_request = Request {
                         swap_id,
@@ -189,3 +195,47 @@ db_roundtrip_test!(
         }
     }
 );
+
+#[tokio::test]
+async fn roundtrip_han_ethereum_halight_bitcoin_swap() -> anyhow::Result<()> {
+    let db = Sqlite::new(&Path::new(":memory:"))?;
+
+    let created = CreatedSwap {
+        swap_id: LocalSwapId::default(),
+        params: HanEtherereumHalightBitcoinCreateSwapParams {
+            role: Role::Alice,
+            peer: DialInformation {
+                peer_id: PeerId::random(),
+                address_hint: Some("/ip4/127.0.0.1/tcp/9939".parse()?),
+            },
+            ethereum_identity: identity::Ethereum::random().into(),
+            ethereum_absolute_expiry: Timestamp::from(100),
+            ethereum_amount: asset::Ether::from_wei(9_001_000_000_000_000_000u128),
+            lightning_identity: lightning::PublicKey::random(),
+            lightning_cltv_expiry: Timestamp::from(200),
+            lightning_amount: asset::Bitcoin::from_sat(42),
+        },
+    };
+    let finalized = FinalizedSwapParams {
+        swap_id: created.swap_id,
+        shared_swap_id: SharedSwapId::default(),
+        counterparty_ethereum_identity: identity::Ethereum::random(),
+        counterparty_lightning_identity: lightning::PublicKey::random(),
+        secret_hash: SecretHash::from([1u8; 32]),
+    };
+
+    db.save(created.clone()).await?;
+    db.save(finalized).await?;
+
+    let loaded = db.load_han_etherereum_halight_bitcoin_swaps().await?;
+
+    assert_eq!(loaded.len(), 1);
+    let (loaded_created, _created_at, loaded_finalized) = &loaded[0];
+    assert_eq!(loaded_created, &created);
+    assert_eq!(
+        loaded_finalized.map(|(finalized, _at)| finalized),
+        Some(finalized)
+    );
+
+    Ok(())
+}
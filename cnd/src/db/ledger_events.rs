@@ -0,0 +1,180 @@
+//! The events observed on the ledgers of rfc003 swaps, so a restart can pick
+//! up where we left off instead of looking for all of them again.
+
+use crate::{
+    btsieve::ProcessedBlock,
+    db::{schema::ledger_events, wrapper_types::custom_sql_types::Text, Save, Sqlite},
+    swap_protocols::rfc003::SwapId,
+};
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use diesel::{self, prelude::*, RunQueryDsl};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Whether an event happened on the alpha or the beta ledger of a swap.
+#[derive(Clone, Copy, Debug, PartialEq, strum_macros::Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum LedgerSide {
+    Alpha,
+    Beta,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LedgerEvent<E> {
+    pub swap_id: SwapId,
+    pub side: LedgerSide,
+    pub recorded: RecordedEvent<E>,
+}
+
+/// An event together with the block of its transaction, if it was included in
+/// one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedEvent<E> {
+    pub event: E,
+    pub processed_block: Option<ProcessedBlock>,
+}
+
+/// Loads the events of a ledger in the order they happened.
+#[async_trait]
+pub trait LoadLedgerEvents<E> {
+    async fn load_ledger_events(
+        &self,
+        swap_id: &SwapId,
+        side: LedgerSide,
+    ) -> anyhow::Result<Vec<RecordedEvent<E>>>;
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[table_name = "ledger_events"]
+struct InsertableLedgerEvent {
+    swap_id: Text<SwapId>,
+    ledger: Text<LedgerSide>,
+    event: String,
+    block_hash: Option<String>,
+    resume_from: Option<NaiveDateTime>,
+}
+
+#[async_trait]
+impl<E> Save<LedgerEvent<E>> for Sqlite
+where
+    E: Serialize + Send + 'static,
+{
+    async fn save(&self, ledger_event: LedgerEvent<E>) -> anyhow::Result<()> {
+        let RecordedEvent {
+            event,
+            processed_block,
+        } = ledger_event.recorded;
+        let (block_hash, resume_from) = match processed_block {
+            Some(ProcessedBlock { hash, resume_from }) => (Some(hash), Some(resume_from)),
+            None => (None, None),
+        };
+
+        let insertable = InsertableLedgerEvent {
+            swap_id: Text(ledger_event.swap_id),
+            ledger: Text(ledger_event.side),
+            event: serde_json::to_string(&event)?,
+            block_hash,
+            resume_from,
+        };
+
+        self.do_in_transaction(|connection| {
+            diesel::insert_into(ledger_events::table)
+                .values(&insertable)
+                .execute(&*connection)
+        })
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<E> LoadLedgerEvents<E> for Sqlite
+where
+    E: DeserializeOwned + Send + 'static,
+{
+    async fn load_ledger_events(
+        &self,
+        swap_id: &SwapId,
+        side: LedgerSide,
+    ) -> anyhow::Result<Vec<RecordedEvent<E>>> {
+        let records: Vec<(String, Option<String>, Option<NaiveDateTime>)> = self
+            .do_in_transaction(|connection| {
+                ledger_events::table
+                    .filter(ledger_events::swap_id.eq(Text(swap_id)))
+                    .filter(ledger_events::ledger.eq(Text(side)))
+                    .order(ledger_events::id.asc())
+                    .select((
+                        ledger_events::event,
+                        ledger_events::block_hash,
+                        ledger_events::resume_from,
+                    ))
+                    .load(connection)
+            })
+            .await?;
+
+        records
+            .into_iter()
+            .map(
+                |(event, block_hash, resume_from)| -> anyhow::Result<RecordedEvent<E>> {
+                    let processed_block = match (block_hash, resume_from) {
+                        (Some(hash), Some(resume_from)) => {
+                            Some(ProcessedBlock { hash, resume_from })
+                        }
+                        _ => None,
+                    };
+
+                    Ok(RecordedEvent {
+                        event: serde_json::from_str(&event)?,
+                        processed_block,
+                    })
+                },
+            )
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn loads_the_saved_events_of_a_ledger_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Sqlite::new_in_dir(dir.path()).unwrap();
+        let swap_id = SwapId::default();
+
+        let processed_block = ProcessedBlock {
+            hash: "0a".to_owned(),
+            resume_from: NaiveDateTime::from_timestamp(1_500_000_000, 0),
+        };
+        let recorded = |event: &str, processed_block: &Option<ProcessedBlock>| RecordedEvent {
+            event: event.to_owned(),
+            processed_block: processed_block.clone(),
+        };
+
+        for (side, event, processed_block) in &[
+            (LedgerSide::Alpha, "deployed", Some(processed_block.clone())),
+            (LedgerSide::Beta, "deployed", Some(processed_block.clone())),
+            (LedgerSide::Alpha, "retracted", None),
+        ] {
+            db.save(LedgerEvent {
+                swap_id,
+                side: *side,
+                recorded: recorded(event, processed_block),
+            })
+            .await
+            .unwrap();
+        }
+
+        let events: Vec<RecordedEvent<String>> = db
+            .load_ledger_events(&swap_id, LedgerSide::Alpha)
+            .await
+            .unwrap();
+
+        assert_eq!(events, vec![
+            recorded("deployed", &Some(processed_block)),
+            recorded("retracted", &None)
+        ]);
+    }
+}
@@ -6,22 +6,24 @@ use crate::{
             custom_sql_types::{Text, U32},
             BitcoinNetwork, Erc20Amount, Ether, EthereumAddress, Satoshis,
         },
-        Sqlite,
+        CreatedSwap, FinalizedSwapParams, Sqlite,
     },
     identity,
+    network::DialInformation,
     swap_protocols::{
         ledger::{bitcoin, Ethereum},
         rfc003::{
             messages::{Accept, Request},
             SecretHash, SwapId,
         },
-        HashFunction,
+        HanEtherereumHalightBitcoinCreateSwapParams, HashFunction, LocalSwapId, Role, SharedSwapId,
     },
 };
 use async_trait::async_trait;
 use chrono::NaiveDateTime;
 use diesel::{self, prelude::*, RunQueryDsl};
 use impl_template::impl_template;
+use libp2p::{Multiaddr, PeerId};
 use schema::{
     rfc003_bitcoin_ethereum_accept_messages,
     rfc003_bitcoin_ethereum_bitcoin_erc20_request_messages,
@@ -30,6 +32,7 @@ use schema::{
     rfc003_ethereum_bitcoin_erc20_bitcoin_request_messages,
     rfc003_ethereum_bitcoin_ether_bitcoin_request_messages,
 };
+use std::collections::HashMap;
 
 pub type AcceptedSwap<AL, BL, AA, BA, AI, BI> = (
     Request<AL, BL, AA, BA, AI, BI>,
@@ -565,3 +568,133 @@ impl
         Ok(record.into())
     }
 }
+
+/// A han-ether/halight swap together with the time it was created, the
+/// parameters we learned while finalizing it and the time at which it was
+/// finalized, if it got that far.
+pub type HanEtherereumHalightBitcoinSwap = (
+    CreatedSwap<HanEtherereumHalightBitcoinCreateSwapParams>,
+    NaiveDateTime,
+    Option<(FinalizedSwapParams, NaiveDateTime)>,
+);
+
+#[async_trait]
+pub trait LoadHanEtherereumHalightBitcoinSwaps {
+    async fn load_han_etherereum_halight_bitcoin_swaps(
+        &self,
+    ) -> anyhow::Result<Vec<HanEtherereumHalightBitcoinSwap>>;
+}
+
+#[derive(Queryable, Debug, Clone, PartialEq)]
+pub(super) struct QueryableHanEthereumHalightBitcoinSwap {
+    pub id: i32,
+    pub local_swap_id: Text<LocalSwapId>,
+    pub role: Text<Role>,
+    pub counterparty_peer_id: Text<PeerId>,
+    pub counterparty_address_hint: Option<Text<Multiaddr>>,
+    pub ethereum_identity: Text<EthereumAddress>,
+    pub ethereum_absolute_expiry: U32,
+    pub ethereum_amount: Text<Ether>,
+    pub lightning_identity: Text<identity::Lightning>,
+    pub lightning_cltv_expiry: U32,
+    pub lightning_amount: Text<Satoshis>,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<QueryableHanEthereumHalightBitcoinSwap>
+    for CreatedSwap<HanEtherereumHalightBitcoinCreateSwapParams>
+{
+    fn from(record: QueryableHanEthereumHalightBitcoinSwap) -> Self {
+        CreatedSwap {
+            swap_id: *record.local_swap_id,
+            params: HanEtherereumHalightBitcoinCreateSwapParams {
+                role: *record.role,
+                peer: DialInformation {
+                    peer_id: record.counterparty_peer_id.0,
+                    address_hint: record.counterparty_address_hint.map(|hint| hint.0),
+                },
+                ethereum_identity: identity::Ethereum::from(record.ethereum_identity.0).into(),
+                ethereum_absolute_expiry: record.ethereum_absolute_expiry.into(),
+                ethereum_amount: record.ethereum_amount.0.into(),
+                lightning_identity: *record.lightning_identity,
+                lightning_cltv_expiry: record.lightning_cltv_expiry.into(),
+                lightning_amount: record.lightning_amount.0.into(),
+            },
+        }
+    }
+}
+
+#[derive(Queryable, Debug, Clone, PartialEq)]
+pub(super) struct QueryableHanEthereumHalightBitcoinFinalizedSwap {
+    pub id: i32,
+    pub local_swap_id: Text<LocalSwapId>,
+    pub shared_swap_id: Text<SharedSwapId>,
+    pub counterparty_ethereum_identity: Text<EthereumAddress>,
+    pub counterparty_lightning_identity: Text<identity::Lightning>,
+    pub secret_hash: Text<SecretHash>,
+    pub at: NaiveDateTime,
+}
+
+impl From<QueryableHanEthereumHalightBitcoinFinalizedSwap>
+    for (FinalizedSwapParams, NaiveDateTime)
+{
+    fn from(record: QueryableHanEthereumHalightBitcoinFinalizedSwap) -> Self {
+        (
+            FinalizedSwapParams {
+                swap_id: *record.local_swap_id,
+                shared_swap_id: *record.shared_swap_id,
+                counterparty_ethereum_identity: record.counterparty_ethereum_identity.0.into(),
+                counterparty_lightning_identity: *record.counterparty_lightning_identity,
+                secret_hash: *record.secret_hash,
+            },
+            record.at,
+        )
+    }
+}
+
+#[async_trait]
+impl LoadHanEtherereumHalightBitcoinSwaps for Sqlite {
+    async fn load_han_etherereum_halight_bitcoin_swaps(
+        &self,
+    ) -> anyhow::Result<Vec<HanEtherereumHalightBitcoinSwap>> {
+        use schema::{
+            han_ethereum_halight_bitcoin_finalized_swaps as finalized_swaps,
+            han_ethereum_halight_bitcoin_swaps as swaps,
+        };
+
+        let (swap_records, finalized_records) = self
+            .do_in_transaction(|connection| {
+                let swap_records: Vec<QueryableHanEthereumHalightBitcoinSwap> =
+                    swaps::table.load(connection)?;
+                let finalized_records: Vec<QueryableHanEthereumHalightBitcoinFinalizedSwap> =
+                    finalized_swaps::table.load(connection)?;
+
+                Ok::<_, diesel::result::Error>((swap_records, finalized_records))
+            })
+            .await?;
+
+        Ok(with_finalized(swap_records, finalized_records))
+    }
+}
+
+/// Pairs each swap with its finalized parameters, if there are any among the
+/// given ones.
+pub(super) fn with_finalized(
+    swap_records: Vec<QueryableHanEthereumHalightBitcoinSwap>,
+    finalized_records: Vec<QueryableHanEthereumHalightBitcoinFinalizedSwap>,
+) -> Vec<HanEtherereumHalightBitcoinSwap> {
+    let mut finalized = finalized_records
+        .into_iter()
+        .map(|record| (*record.local_swap_id, record.into()))
+        .collect::<HashMap<LocalSwapId, (FinalizedSwapParams, NaiveDateTime)>>();
+
+    swap_records
+        .into_iter()
+        .map(|record| {
+            let finalized = finalized.remove(&*record.local_swap_id);
+            let created_at = record.created_at;
+
+            (record.into(), created_at, finalized)
+        })
+        .collect()
+}
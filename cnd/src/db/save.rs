@@ -6,19 +6,20 @@ use crate::{
             custom_sql_types::{Text, U32},
             BitcoinNetwork, Erc20Amount, Ether, EthereumAddress, Satoshis,
         },
-        Sqlite, Swap,
+        CreatedSwap, FinalizedSwapParams, Sqlite, Swap,
     },
     identity,
     swap_protocols::{
         ledger::{self, Ethereum},
         rfc003::{Accept, Decline, Request, SecretHash, SwapId},
-        HashFunction, Role,
+        HanEtherereumHalightBitcoinCreateSwapParams, HashFunction, LocalSwapId, Role, SharedSwapId,
     },
 };
 use async_trait::async_trait;
+use chrono::NaiveDateTime;
 use diesel::RunQueryDsl;
 use impl_template::impl_template;
-use libp2p::{self, PeerId};
+use libp2p::{self, Multiaddr, PeerId};
 
 /// Save swap to database.
 #[async_trait]
@@ -48,6 +49,7 @@ struct InsertableSwap {
     pub swap_id: Text<SwapId>,
     pub role: Text<Role>,
     pub counterparty: Text<PeerId>,
+    pub created_at: NaiveDateTime,
 }
 
 impl From<Swap> for InsertableSwap {
@@ -56,6 +58,7 @@ impl From<Swap> for InsertableSwap {
             swap_id: Text(swap.swap_id),
             role: Text(swap.role),
             counterparty: Text(swap.counterparty),
+            created_at: swap.created_at,
         }
     }
 }
@@ -505,3 +508,90 @@ impl Save<Decline> for Sqlite {
         Ok(())
     }
 }
+
+#[derive(Insertable, Debug, Clone)]
+#[table_name = "han_ethereum_halight_bitcoin_swaps"]
+struct InsertableHanEthereumHalightBitcoinSwap {
+    local_swap_id: Text<LocalSwapId>,
+    role: Text<Role>,
+    counterparty_peer_id: Text<PeerId>,
+    counterparty_address_hint: Option<Text<Multiaddr>>,
+    ethereum_identity: Text<EthereumAddress>,
+    ethereum_absolute_expiry: U32,
+    ethereum_amount: Text<Ether>,
+    lightning_identity: Text<identity::Lightning>,
+    lightning_cltv_expiry: U32,
+    lightning_amount: Text<Satoshis>,
+}
+
+#[async_trait]
+impl Save<CreatedSwap<HanEtherereumHalightBitcoinCreateSwapParams>> for Sqlite {
+    async fn save(
+        &self,
+        swap: CreatedSwap<HanEtherereumHalightBitcoinCreateSwapParams>,
+    ) -> anyhow::Result<()> {
+        let CreatedSwap { swap_id, params } = swap;
+
+        let insertable = InsertableHanEthereumHalightBitcoinSwap {
+            local_swap_id: Text(swap_id),
+            role: Text(params.role),
+            counterparty_peer_id: Text(params.peer.peer_id),
+            counterparty_address_hint: params.peer.address_hint.map(Text),
+            ethereum_identity: Text(identity::Ethereum::from(params.ethereum_identity).into()),
+            ethereum_absolute_expiry: U32(params.ethereum_absolute_expiry.into()),
+            ethereum_amount: Text(params.ethereum_amount.into()),
+            lightning_identity: Text(params.lightning_identity),
+            lightning_cltv_expiry: U32(params.lightning_cltv_expiry.into()),
+            lightning_amount: Text(params.lightning_amount.into()),
+        };
+
+        self.do_in_transaction(|connection| {
+            diesel::insert_into(han_ethereum_halight_bitcoin_swaps::table)
+                .values(&insertable)
+                .execute(&*connection)
+        })
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[table_name = "han_ethereum_halight_bitcoin_finalized_swaps"]
+struct InsertableHanEthereumHalightBitcoinFinalizedSwap {
+    local_swap_id: Text<LocalSwapId>,
+    shared_swap_id: Text<SharedSwapId>,
+    counterparty_ethereum_identity: Text<EthereumAddress>,
+    counterparty_lightning_identity: Text<identity::Lightning>,
+    secret_hash: Text<SecretHash>,
+}
+
+#[async_trait]
+impl Save<FinalizedSwapParams> for Sqlite {
+    async fn save(&self, finalized: FinalizedSwapParams) -> anyhow::Result<()> {
+        let FinalizedSwapParams {
+            swap_id,
+            shared_swap_id,
+            counterparty_ethereum_identity,
+            counterparty_lightning_identity,
+            secret_hash,
+        } = finalized;
+
+        let insertable = InsertableHanEthereumHalightBitcoinFinalizedSwap {
+            local_swap_id: Text(swap_id),
+            shared_swap_id: Text(shared_swap_id),
+            counterparty_ethereum_identity: Text(counterparty_ethereum_identity.into()),
+            counterparty_lightning_identity: Text(counterparty_lightning_identity),
+            secret_hash: Text(secret_hash),
+        };
+
+        self.do_in_transaction(|connection| {
+            diesel::insert_into(han_ethereum_halight_bitcoin_finalized_swaps::table)
+                .values(&insertable)
+                .execute(&*connection)
+        })
+        .await?;
+
+        Ok(())
+    }
+}
@@ -102,5 +102,57 @@ table! {
        swap_id -> Text,
        role -> Text,
        counterparty -> Text,
+       created_at -> Timestamp,
+   }
+}
+
+table! {
+   han_ethereum_halight_bitcoin_swaps {
+       id -> Integer,
+       local_swap_id -> Text,
+       role -> Text,
+       counterparty_peer_id -> Text,
+       counterparty_address_hint -> Nullable<Text>,
+       ethereum_identity -> Text,
+       ethereum_absolute_expiry -> BigInt,
+       ethereum_amount -> Text,
+       lightning_identity -> Text,
+       lightning_cltv_expiry -> BigInt,
+       lightning_amount -> Text,
+       created_at -> Timestamp,
+   }
+}
+
+table! {
+   han_ethereum_halight_bitcoin_finalized_swaps {
+       id -> Integer,
+       local_swap_id -> Text,
+       shared_swap_id -> Text,
+       counterparty_ethereum_identity -> Text,
+       counterparty_lightning_identity -> Text,
+       secret_hash -> Text,
+       at -> Timestamp,
+   }
+}
+
+table! {
+   cached_blocks {
+       id -> Integer,
+       item -> Text,
+       hash -> Text,
+       data -> Binary,
+       last_used -> BigInt,
+   }
+}
+
+table! {
+   ledger_events {
+       id -> Integer,
+       swap_id -> Text,
+       ledger -> Text,
+       event -> Text,
+       block_hash -> Nullable<Text>,
+       resume_from -> Nullable<Timestamp>,
+       seen_at -> Timestamp,
    }
 }
@@ -1,9 +1,14 @@
 use crate::{
     db::{schema, wrapper_types::custom_sql_types::Text, Error, Sqlite},
     diesel::{ExpressionMethods, OptionalExtension, QueryDsl},
-    swap_protocols::{rfc003::SwapId, Role},
+    identity,
+    swap_protocols::{
+        rfc003::{SecretHash, SwapId},
+        LocalSwapId, Role, SharedSwapId,
+    },
 };
 use async_trait::async_trait;
+use chrono::NaiveDateTime;
 use diesel::RunQueryDsl;
 use libp2p::{self, PeerId};
 
@@ -20,14 +25,17 @@ pub struct Swap {
     pub swap_id: SwapId,
     pub role: Role,
     pub counterparty: PeerId,
+    pub created_at: NaiveDateTime,
 }
 
 impl Swap {
+    /// A swap created just now.
     pub fn new(swap_id: SwapId, role: Role, counterparty: PeerId) -> Swap {
         Swap {
             swap_id,
             role,
             counterparty,
+            created_at: chrono::Utc::now().naive_utc(),
         }
     }
 }
@@ -64,11 +72,12 @@ impl Retrieve for Sqlite {
 }
 
 #[derive(Queryable, Debug, Clone, PartialEq)]
-struct QueryableSwap {
+pub(super) struct QueryableSwap {
     pub id: i32,
     pub swap_id: Text<SwapId>,
     pub role: Text<Role>,
     pub counterparty: Text<PeerId>,
+    pub created_at: NaiveDateTime,
 }
 
 impl From<QueryableSwap> for Swap {
@@ -77,6 +86,26 @@ impl From<QueryableSwap> for Swap {
             swap_id: *swap.swap_id,
             role: *swap.role,
             counterparty: (*swap.counterparty).clone(),
+            created_at: swap.created_at,
         }
     }
 }
+
+/// A swap that was created locally through the HTTP API, identified by the
+/// `LocalSwapId` we handed out for it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CreatedSwap<P> {
+    pub swap_id: LocalSwapId,
+    pub params: P,
+}
+
+/// The parameters we learn from the counterparty while finalizing a
+/// han-ether/halight swap.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FinalizedSwapParams {
+    pub swap_id: LocalSwapId,
+    pub shared_swap_id: SharedSwapId,
+    pub counterparty_ethereum_identity: identity::Ethereum,
+    pub counterparty_lightning_identity: identity::Lightning,
+    pub secret_hash: SecretHash,
+}
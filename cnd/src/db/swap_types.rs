@@ -5,10 +5,12 @@ use crate::{
         wrapper_types::{custom_sql_types::Text, BitcoinNetwork},
         Sqlite,
     },
-    diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl},
+    diesel::{ExpressionMethods, OptionalExtension, QueryDsl, QueryResult, RunQueryDsl},
     swap_protocols::{rfc003::SwapId, Role},
 };
 use async_trait::async_trait;
+use diesel::sqlite::SqliteConnection;
+use std::collections::HashMap;
 use strum_macros::{Display, EnumString};
 
 /// Determine swap types for swaps currently stored in the database.
@@ -117,6 +119,94 @@ impl Sqlite {
     impl_has_swap!(rfc003_ethereum_bitcoin_erc20_bitcoin_request_messages);
 }
 
+macro_rules! impl_bitcoin_networks {
+    ($table:ident) => {
+        paste::item! {
+            fn [<$table _bitcoin_networks>](connection: &SqliteConnection, keys: &[Text<SwapId>]) -> QueryResult<Vec<QueryableSwap>> {
+                use schema::$table as swaps;
+
+                swaps::table
+                    .filter(swaps::swap_id.eq_any(keys))
+                    .select((swaps::swap_id, swaps::bitcoin_network))
+                    .load(connection)
+            }
+        }
+    };
+}
+
+impl_bitcoin_networks!(rfc003_bitcoin_ethereum_bitcoin_ether_request_messages);
+impl_bitcoin_networks!(rfc003_ethereum_bitcoin_ether_bitcoin_request_messages);
+impl_bitcoin_networks!(rfc003_bitcoin_ethereum_bitcoin_erc20_request_messages);
+impl_bitcoin_networks!(rfc003_ethereum_bitcoin_erc20_bitcoin_request_messages);
+
+/// Like `determine_types` but for many swaps at once, with one query per
+/// request table instead of up to four per swap.
+pub(super) fn types_of(
+    connection: &SqliteConnection,
+    swaps: &[(SwapId, Role)],
+) -> QueryResult<HashMap<SwapId, SwapTypes>> {
+    let keys = swaps
+        .iter()
+        .map(|(swap_id, _)| Text(*swap_id))
+        .collect::<Vec<_>>();
+    let roles = swaps.iter().copied().collect::<HashMap<_, _>>();
+    let mut types = HashMap::with_capacity(swaps.len());
+
+    let mut insert = |records: Vec<QueryableSwap>,
+                      swap_types: fn(LedgerKind, Role) -> SwapTypes| {
+        for record in records {
+            let bitcoin_ledger = LedgerKind::from(*record.bitcoin_network);
+            // We only asked for the given swaps.
+            let role = roles[&*record.swap_id];
+
+            types.insert(*record.swap_id, swap_types(bitcoin_ledger, role));
+        }
+    };
+
+    insert(
+        rfc003_bitcoin_ethereum_bitcoin_ether_request_messages_bitcoin_networks(connection, &keys)?,
+        |bitcoin_ledger, role| SwapTypes {
+            alpha_ledger: bitcoin_ledger,
+            beta_ledger: LedgerKind::Ethereum,
+            alpha_asset: AssetKind::Bitcoin,
+            beta_asset: AssetKind::Ether,
+            role,
+        },
+    );
+    insert(
+        rfc003_ethereum_bitcoin_ether_bitcoin_request_messages_bitcoin_networks(connection, &keys)?,
+        |bitcoin_ledger, role| SwapTypes {
+            alpha_ledger: LedgerKind::Ethereum,
+            beta_ledger: bitcoin_ledger,
+            alpha_asset: AssetKind::Ether,
+            beta_asset: AssetKind::Bitcoin,
+            role,
+        },
+    );
+    insert(
+        rfc003_bitcoin_ethereum_bitcoin_erc20_request_messages_bitcoin_networks(connection, &keys)?,
+        |bitcoin_ledger, role| SwapTypes {
+            alpha_ledger: bitcoin_ledger,
+            beta_ledger: LedgerKind::Ethereum,
+            alpha_asset: AssetKind::Bitcoin,
+            beta_asset: AssetKind::Erc20,
+            role,
+        },
+    );
+    insert(
+        rfc003_ethereum_bitcoin_erc20_bitcoin_request_messages_bitcoin_networks(connection, &keys)?,
+        |bitcoin_ledger, role| SwapTypes {
+            alpha_ledger: LedgerKind::Ethereum,
+            beta_ledger: bitcoin_ledger,
+            alpha_asset: AssetKind::Erc20,
+            beta_asset: AssetKind::Bitcoin,
+            role,
+        },
+    );
+
+    Ok(types)
+}
+
 #[derive(Queryable, Debug, Clone, PartialEq)]
 struct QueryableSwap {
     swap_id: Text<SwapId>,
@@ -0,0 +1,278 @@
+//! Loading the swaps listed through the HTTP API a page at a time. The
+//! database does the ordering and the cheap filtering, so we never have to
+//! load all swaps.
+
+use crate::{
+    db::{
+        load_swaps::{
+            with_finalized, QueryableHanEthereumHalightBitcoinFinalizedSwap,
+            QueryableHanEthereumHalightBitcoinSwap,
+        },
+        schema,
+        swap::QueryableSwap,
+        swap_types::types_of,
+        wrapper_types::custom_sql_types::Text,
+        HanEtherereumHalightBitcoinSwap, Sqlite, Swap, SwapTypes,
+    },
+    swap_protocols::{rfc003::SwapId, LocalSwapId, Role},
+};
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use diesel::{self, prelude::*, RunQueryDsl};
+use libp2p::PeerId;
+use uuid::Uuid;
+
+/// Where a swap is in the list of all swaps. Swaps are ordered by the time
+/// they were created, their id breaks ties.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PagePosition {
+    pub created_at: NaiveDateTime,
+    pub id: Uuid,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PageOrder {
+    Asc,
+    Desc,
+}
+
+/// The filters the database applies to the swaps of every protocol.
+#[derive(Clone, Debug)]
+pub struct SwapsPageQuery {
+    pub role: Option<Role>,
+    pub counterparty: Option<PeerId>,
+    pub created_after: Option<NaiveDateTime>,
+    pub created_before: Option<NaiveDateTime>,
+    pub order: PageOrder,
+    /// Only swaps after this position in the given order are loaded.
+    pub after: Option<PagePosition>,
+    pub limit: i64,
+}
+
+#[async_trait]
+pub trait LoadSwapsPage {
+    /// `None` if there is no swap with the given id.
+    async fn page_position(&self, id: Uuid) -> anyhow::Result<Option<PagePosition>>;
+
+    async fn load_rfc003_swaps_page(
+        &self,
+        query: &SwapsPageQuery,
+    ) -> anyhow::Result<Vec<(Swap, SwapTypes)>>;
+
+    async fn load_han_etherereum_halight_bitcoin_swaps_page(
+        &self,
+        query: &SwapsPageQuery,
+    ) -> anyhow::Result<Vec<HanEtherereumHalightBitcoinSwap>>;
+}
+
+/// Builds the statement selecting a page of the given swaps table.
+macro_rules! page_statement {
+    ($swaps:ident, $id:ident, $counterparty:ident, $query:expr, $to_id:expr) => {{
+        let query: &SwapsPageQuery = $query;
+        let mut statement = $swaps::table.into_boxed();
+
+        if let Some(role) = query.role {
+            statement = statement.filter($swaps::role.eq(Text(role)));
+        }
+        if let Some(counterparty) = &query.counterparty {
+            statement = statement.filter($swaps::$counterparty.eq(Text(counterparty.clone())));
+        }
+        if let Some(after) = query.created_after {
+            statement = statement.filter($swaps::created_at.gt(after));
+        }
+        if let Some(before) = query.created_before {
+            statement = statement.filter($swaps::created_at.lt(before));
+        }
+
+        let statement = match (query.order, query.after) {
+            (PageOrder::Asc, Some(after)) => statement.filter(
+                $swaps::created_at
+                    .gt(after.created_at)
+                    .or($swaps::created_at
+                        .eq(after.created_at)
+                        .and($swaps::$id.gt(Text($to_id(after.id))))),
+            ),
+            (PageOrder::Desc, Some(after)) => statement.filter(
+                $swaps::created_at
+                    .lt(after.created_at)
+                    .or($swaps::created_at
+                        .eq(after.created_at)
+                        .and($swaps::$id.lt(Text($to_id(after.id))))),
+            ),
+            (_, None) => statement,
+        };
+
+        let statement = match query.order {
+            PageOrder::Asc => statement.order(($swaps::created_at.asc(), $swaps::$id.asc())),
+            PageOrder::Desc => statement.order(($swaps::created_at.desc(), $swaps::$id.desc())),
+        };
+
+        statement.limit(query.limit)
+    }};
+}
+
+#[async_trait]
+impl LoadSwapsPage for Sqlite {
+    async fn page_position(&self, id: Uuid) -> anyhow::Result<Option<PagePosition>> {
+        use schema::{han_ethereum_halight_bitcoin_swaps as han_swaps, rfc003_swaps};
+
+        let created_at = self
+            .do_in_transaction(|connection| {
+                let created_at = rfc003_swaps::table
+                    .filter(rfc003_swaps::swap_id.eq(Text(SwapId(id))))
+                    .select(rfc003_swaps::created_at)
+                    .first::<NaiveDateTime>(connection)
+                    .optional()?;
+
+                match created_at {
+                    Some(created_at) => Ok(Some(created_at)),
+                    None => han_swaps::table
+                        .filter(han_swaps::local_swap_id.eq(Text(LocalSwapId::from(id))))
+                        .select(han_swaps::created_at)
+                        .first::<NaiveDateTime>(connection)
+                        .optional(),
+                }
+            })
+            .await?;
+
+        Ok(created_at.map(|created_at| PagePosition { created_at, id }))
+    }
+
+    async fn load_rfc003_swaps_page(
+        &self,
+        query: &SwapsPageQuery,
+    ) -> anyhow::Result<Vec<(Swap, SwapTypes)>> {
+        use schema::rfc003_swaps as swaps;
+
+        let (records, mut types) = self
+            .do_in_transaction(|connection| {
+                let records: Vec<QueryableSwap> =
+                    page_statement!(swaps, swap_id, counterparty, query, SwapId)
+                        .load(connection)?;
+
+                let keys = records
+                    .iter()
+                    .map(|record| (*record.swap_id, *record.role))
+                    .collect::<Vec<_>>();
+                let types = types_of(connection, &keys)?;
+
+                Ok::<_, diesel::result::Error>((records, types))
+            })
+            .await?;
+
+        records
+            .into_iter()
+            .map(|record| {
+                let swap = Swap::from(record);
+                let types = types
+                    .remove(&swap.swap_id)
+                    .ok_or_else(|| anyhow::anyhow!("no request found for swap {}", swap.swap_id))?;
+
+                Ok((swap, types))
+            })
+            .collect()
+    }
+
+    async fn load_han_etherereum_halight_bitcoin_swaps_page(
+        &self,
+        query: &SwapsPageQuery,
+    ) -> anyhow::Result<Vec<HanEtherereumHalightBitcoinSwap>> {
+        use schema::{
+            han_ethereum_halight_bitcoin_finalized_swaps as finalized_swaps,
+            han_ethereum_halight_bitcoin_swaps as swaps,
+        };
+
+        let (swap_records, finalized_records) = self
+            .do_in_transaction(|connection| {
+                let swap_records: Vec<QueryableHanEthereumHalightBitcoinSwap> = page_statement!(
+                    swaps,
+                    local_swap_id,
+                    counterparty_peer_id,
+                    query,
+                    LocalSwapId::from
+                )
+                .load(connection)?;
+
+                let keys = swap_records
+                    .iter()
+                    .map(|record| record.local_swap_id)
+                    .collect::<Vec<_>>();
+                let finalized_records: Vec<QueryableHanEthereumHalightBitcoinFinalizedSwap> =
+                    finalized_swaps::table
+                        .filter(finalized_swaps::local_swap_id.eq_any(keys))
+                        .load(connection)?;
+
+                Ok::<_, diesel::result::Error>((swap_records, finalized_records))
+            })
+            .await?;
+
+        Ok(with_finalized(swap_records, finalized_records))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Save;
+    use chrono::Duration;
+
+    fn query(order: PageOrder, after: Option<PagePosition>, limit: i64) -> SwapsPageQuery {
+        SwapsPageQuery {
+            role: None,
+            counterparty: None,
+            created_after: None,
+            created_before: None,
+            order,
+            after,
+            limit,
+        }
+    }
+
+    #[tokio::test]
+    async fn pages_continue_after_the_given_position() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Sqlite::new_in_dir(dir.path()).unwrap();
+        let created_at = NaiveDateTime::from_timestamp(1_500_000_000, 0);
+
+        let mut ids = Vec::new();
+        for minutes in &[0, 0, 1] {
+            let swap = Swap {
+                created_at: created_at + Duration::minutes(*minutes),
+                ..Swap::new(SwapId::default(), Role::Alice, PeerId::random())
+            };
+            ids.push(swap.swap_id.0);
+            db.save(swap).await.unwrap();
+        }
+        // The two swaps created at the same time are ordered by their id.
+        if ids[0] > ids[1] {
+            ids.swap(0, 1);
+        }
+
+        let position = db.page_position(ids[0]).await.unwrap().unwrap();
+        let positions = |page: Vec<Swap>| {
+            page.into_iter()
+                .map(|swap| swap.swap_id.0)
+                .collect::<Vec<_>>()
+        };
+
+        let asc = load_page(&db, &query(PageOrder::Asc, Some(position), 10)).await;
+        let desc = load_page(&db, &query(PageOrder::Desc, None, 2)).await;
+
+        assert_eq!(positions(asc), vec![ids[1], ids[2]]);
+        assert_eq!(positions(desc), vec![ids[2], ids[1]]);
+        assert_eq!(db.page_position(Uuid::new_v4()).await.unwrap(), None);
+    }
+
+    async fn load_page(db: &Sqlite, query: &SwapsPageQuery) -> Vec<Swap> {
+        use schema::rfc003_swaps as swaps;
+
+        let records: Vec<QueryableSwap> = db
+            .do_in_transaction(|connection| {
+                page_statement!(swaps, swap_id, counterparty, query, SwapId).load(connection)
+            })
+            .await
+            .unwrap();
+
+        records.into_iter().map(Swap::from).collect()
+    }
+}
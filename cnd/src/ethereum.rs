@@ -146,7 +146,7 @@ impl Display for Hash {
 }
 
 /// "Receipt" of an executed transaction: details of its execution.
-#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct TransactionReceipt {
     /// Contract address created, or `None` if not a deployment.
     #[serde(rename = "contractAddress")]
@@ -165,7 +165,7 @@ impl TransactionReceipt {
 }
 
 /// Description of a Transaction, pending or in the chain.
-#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Transaction {
     /// Hash
     pub hash: Hash,
@@ -178,7 +178,7 @@ pub struct Transaction {
 }
 
 /// A log produced by a transaction.
-#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Log {
     /// H160
     pub address: Address,
@@ -186,18 +186,29 @@ pub struct Log {
     pub topics: Vec<Hash>,
     /// Data
     pub data: Bytes,
+    /// Hash of the transaction that produced this log, `None` if pending
+    #[serde(rename = "transactionHash")]
+    pub transaction_hash: Option<Hash>,
+    /// Hash of the block the log is in, `None` if pending
+    #[serde(rename = "blockHash")]
+    pub block_hash: Option<Hash>,
+    /// Number of the block the log is in, `None` if pending
+    #[serde(rename = "blockNumber")]
+    pub block_number: Option<U256>,
 }
 
 /// The block returned from RPC calls.
 ///
 /// This type contains only the fields we are actually using.
-#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Block {
     /// Hash of the block
     pub hash: Option<Hash>,
     /// Hash of the parent
     #[serde(rename = "parentHash")]
     pub parent_hash: Hash,
+    /// Block number, `None` if pending
+    pub number: Option<U256>,
     /// Logs bloom
     #[serde(rename = "logsBloom")]
     pub logs_bloom: H2048,
@@ -0,0 +1,233 @@
+//! Checks that the expiries of a swap leave both parties enough time to redeem
+//! or refund.
+//!
+//! An HTLC is only safe if a transaction sent right before its expiry still
+//! has time to confirm. We therefore require every expiry to be a configurable
+//! margin ahead of the latest block time of its ledger. Bob redeems the alpha
+//! HTLC only after Alice revealed the secret on the beta ledger, hence the
+//! alpha expiry also has to be the same margin after the beta expiry.
+
+use crate::{
+    btsieve::{
+        bitcoin::{self, Connector as BitcoinConnector},
+        ethereum::{self, Web3Connector},
+        LatestBlock,
+    },
+    comit_api::LedgerKind,
+    config::ExpiryMargins,
+    ethereum::U256,
+    timestamp::Timestamp,
+};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+pub enum UnsafeExpiry {
+    #[error("{ledger} expiry {expiry} is less than {margin} seconds after the latest block time {block_time}")]
+    TooSoon {
+        ledger: &'static str,
+        expiry: u32,
+        block_time: u32,
+        margin: u32,
+    },
+    #[error(
+        "alpha expiry {alpha_expiry} is less than {margin} seconds after beta expiry {beta_expiry}"
+    )]
+    GapTooSmall {
+        alpha_expiry: u32,
+        beta_expiry: u32,
+        margin: u32,
+    },
+}
+
+/// An expiry and the latest block time of the ledger it is enforced on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct LedgerExpiry {
+    ledger: &'static str,
+    expiry: Timestamp,
+    block_time: Timestamp,
+    margin: u32,
+}
+
+impl LedgerExpiry {
+    fn validate(self) -> Result<(), UnsafeExpiry> {
+        if self.expiry < self.block_time.plus(self.margin) {
+            return Err(UnsafeExpiry::TooSoon {
+                ledger: self.ledger,
+                expiry: self.expiry.into(),
+                block_time: self.block_time.into(),
+                margin: self.margin,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+fn validate(alpha: LedgerExpiry, beta: LedgerExpiry) -> Result<(), UnsafeExpiry> {
+    alpha.validate()?;
+    beta.validate()?;
+
+    if alpha.expiry < beta.expiry.plus(alpha.margin) {
+        return Err(UnsafeExpiry::GapTooSmall {
+            alpha_expiry: alpha.expiry.into(),
+            beta_expiry: beta.expiry.into(),
+            margin: alpha.margin,
+        });
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Debug)]
+pub struct ExpiryValidator {
+    margins: ExpiryMargins,
+    bitcoin_connector: Arc<bitcoin::Cache<BitcoinConnector>>,
+    ethereum_connector: Arc<ethereum::Cache<Web3Connector>>,
+}
+
+impl ExpiryValidator {
+    pub fn new(
+        margins: ExpiryMargins,
+        bitcoin_connector: Arc<bitcoin::Cache<BitcoinConnector>>,
+        ethereum_connector: Arc<ethereum::Cache<Web3Connector>>,
+    ) -> Self {
+        Self {
+            margins,
+            bitcoin_connector,
+            ethereum_connector,
+        }
+    }
+
+    /// Validates the expiries of an rfc003 swap.
+    pub async fn validate_rfc003(
+        &self,
+        alpha_ledger: LedgerKind,
+        alpha_expiry: Timestamp,
+        beta_ledger: LedgerKind,
+        beta_expiry: Timestamp,
+    ) -> Result<(), UnsafeExpiry> {
+        let alpha = self.ledger_expiry(alpha_ledger, alpha_expiry).await;
+        let beta = self.ledger_expiry(beta_ledger, beta_expiry).await;
+
+        validate(alpha, beta)
+    }
+
+    /// Validates the absolute expiry of an HTLC on Ethereum.
+    ///
+    /// The expiry of a halight swap counts from when the invoice is paid and
+    /// can therefore not be compared with it upfront.
+    pub async fn validate_ethereum(&self, expiry: Timestamp) -> Result<(), UnsafeExpiry> {
+        self.ethereum_expiry(expiry).await.validate()
+    }
+
+    async fn ledger_expiry(&self, ledger: LedgerKind, expiry: Timestamp) -> LedgerExpiry {
+        match ledger {
+            LedgerKind::BitcoinMainnet
+            | LedgerKind::BitcoinTestnet
+            | LedgerKind::BitcoinRegtest => self.bitcoin_expiry(expiry).await,
+            LedgerKind::Ethereum(_) => self.ethereum_expiry(expiry).await,
+        }
+    }
+
+    async fn bitcoin_expiry(&self, expiry: Timestamp) -> LedgerExpiry {
+        let block_time = match self.bitcoin_connector.latest_block().await {
+            Ok(block) => Timestamp::from(block.header.time),
+            Err(e) => local_time_instead("bitcoin", e),
+        };
+
+        LedgerExpiry {
+            ledger: "bitcoin",
+            expiry,
+            block_time,
+            margin: self.margins.bitcoin,
+        }
+    }
+
+    async fn ethereum_expiry(&self, expiry: Timestamp) -> LedgerExpiry {
+        let block_time = match self.ethereum_connector.latest_block().await {
+            Ok(block) if block.timestamp <= U256::from(std::u32::MAX) => {
+                Timestamp::from(block.timestamp.low_u32())
+            }
+            Ok(block) => local_time_instead(
+                "ethereum",
+                anyhow::anyhow!("block timestamp {} is out of range", block.timestamp),
+            ),
+            Err(e) => local_time_instead("ethereum", e),
+        };
+
+        LedgerExpiry {
+            ledger: "ethereum",
+            expiry,
+            block_time,
+            margin: self.margins.ethereum,
+        }
+    }
+}
+
+/// Without the latest block of a ledger, our own clock is the best estimate of
+/// its block time.
+fn local_time_instead(ledger: &str, e: anyhow::Error) -> Timestamp {
+    tracing::warn!(
+        "failed to get the latest {} block time, using the local time instead: {:#}",
+        ledger,
+        e
+    );
+
+    Timestamp::now()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spectral::prelude::*;
+
+    fn ledger_expiry(expiry: u32, block_time: u32, margin: u32) -> LedgerExpiry {
+        LedgerExpiry {
+            ledger: "bitcoin",
+            expiry: Timestamp::from(expiry),
+            block_time: Timestamp::from(block_time),
+            margin,
+        }
+    }
+
+    #[test]
+    fn accepts_expiries_that_respect_all_margins() {
+        let alpha = ledger_expiry(2_000, 500, 1_000);
+        let beta = ledger_expiry(1_000, 500, 500);
+
+        assert_that(&validate(alpha, beta)).is_ok();
+    }
+
+    #[test]
+    fn rejects_expiry_too_close_to_latest_block_time() {
+        let alpha = ledger_expiry(2_000, 500, 1_000);
+        let beta = ledger_expiry(999, 500, 500);
+
+        assert_that(&validate(alpha, beta)).is_err_containing(UnsafeExpiry::TooSoon {
+            ledger: "bitcoin",
+            expiry: 999,
+            block_time: 500,
+            margin: 500,
+        });
+    }
+
+    #[test]
+    fn rejects_expiry_in_the_past() {
+        let alpha = ledger_expiry(2_000, 2_001, 0);
+        let beta = ledger_expiry(1_000, 500, 0);
+
+        assert_that(&validate(alpha, beta)).is_err();
+    }
+
+    #[test]
+    fn rejects_alpha_expiry_too_close_to_beta_expiry() {
+        let alpha = ledger_expiry(1_999, 500, 1_000);
+        let beta = ledger_expiry(1_000, 500, 500);
+
+        assert_that(&validate(alpha, beta)).is_err_containing(UnsafeExpiry::GapTooSmall {
+            alpha_expiry: 1_999,
+            beta_expiry: 1_000,
+            margin: 1_000,
+        });
+    }
+}
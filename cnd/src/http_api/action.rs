@@ -1,5 +1,6 @@
 use crate::{
     asset,
+    btsieve::bitcoin::EstimateFeePerWu,
     http_api::{problem, Http, MissingQueryParameters, UnexpectedQueryParameters},
     identity,
     swap_protocols::{
@@ -19,7 +20,10 @@ use anyhow::Context;
 use blockchain_contracts::bitcoin::witness;
 use http_api_problem::HttpApiProblem;
 use serde::{Deserialize, Serialize};
-use std::convert::Infallible;
+use std::{
+    collections::HashMap,
+    convert::{Infallible, TryFrom},
+};
 use warp::http::StatusCode;
 
 pub mod rfc003 {
@@ -38,16 +42,123 @@ pub trait ListRequiredFields {
     fn list_required_fields() -> Vec<siren::Field>;
 }
 
+/// The time between two Bitcoin blocks on average.
+const SECONDS_PER_BLOCK: u32 = 600;
+
+/// We do not wait longer than a day for a transaction to be confirmed.
+const MAX_CONFIRMATION_TARGET: u16 = 144;
+
+/// Actions which pay a Bitcoin fee rate know how soon their transaction has to
+/// be confirmed, which is what cnd needs to suggest a fee rate.
+pub trait ConfirmationTarget {
+    /// The number of blocks the transaction should be confirmed within, `None`
+    /// if the action does not pay a fee rate.
+    fn confirmation_target(&self, now: Timestamp) -> Option<u16>;
+}
+
+/// Aims for half of the blocks left until the HTLC expires, leaving time to
+/// bump the fee. Once it expired, we want the next block.
+fn confirmation_target(htlc_expiry: Timestamp, now: Timestamp) -> u16 {
+    let seconds_left = u32::from(htlc_expiry).saturating_sub(u32::from(now));
+    let target = (seconds_left / SECONDS_PER_BLOCK / 2)
+        .max(1)
+        .min(u32::from(MAX_CONFIRMATION_TARGET));
+
+    u16::try_from(target).unwrap_or(MAX_CONFIRMATION_TARGET)
+}
+
+/// The fee rates estimated while answering one request, so we ask the
+/// Bitcoin node only once per confirmation target.
+pub struct FeeEstimates<'c, C> {
+    connector: &'c C,
+    by_confirmation_target: HashMap<u16, Option<usize>>,
+}
+
+impl<'c, C> FeeEstimates<'c, C>
+where
+    C: EstimateFeePerWu,
+{
+    pub fn new(connector: &'c C) -> Self {
+        Self {
+            connector,
+            by_confirmation_target: HashMap::new(),
+        }
+    }
+
+    /// The fee per weight unit we suggest for the action, `None` if it does
+    /// not pay a fee rate or the Bitcoin backend cannot estimate one.
+    pub async fn suggested_fee_per_wu<A>(&mut self, action: &A) -> Option<usize>
+    where
+        A: ConfirmationTarget,
+    {
+        if !self.connector.can_estimate_fee() {
+            return None;
+        }
+
+        let confirmation_target = action.confirmation_target(Timestamp::now())?;
+
+        if let Some(fee_per_wu) = self.by_confirmation_target.get(&confirmation_target) {
+            return *fee_per_wu;
+        }
+
+        let fee_per_wu = match self
+            .connector
+            .estimate_fee_per_wu(confirmation_target)
+            .await
+        {
+            Ok(fee_per_wu) => Some(fee_per_wu),
+            Err(e) => {
+                tracing::warn!(
+                    "failed to estimate fee for confirmation within {} blocks: {:#}",
+                    confirmation_target,
+                    e
+                );
+                None
+            }
+        };
+        self.by_confirmation_target
+            .insert(confirmation_target, fee_per_wu);
+
+        fee_per_wu
+    }
+}
+
 #[derive(Clone, Deserialize, Debug, PartialEq)]
 #[serde(untagged)]
 pub enum ActionExecutionParameters {
     BitcoinAddressAndFee {
         address: bitcoin::Address,
-        fee_per_wu: String,
+        /// Suggested by cnd if not given.
+        #[serde(default)]
+        fee_per_wu: Option<String>,
     },
     None {},
 }
 
+impl ActionExecutionParameters {
+    /// Fills in the fee rate we suggest for the action if the client only
+    /// gave the address.
+    pub async fn with_suggested_fee<A, C>(self, action: &A, connector: &C) -> Self
+    where
+        A: ConfirmationTarget,
+        C: EstimateFeePerWu,
+    {
+        match self {
+            ActionExecutionParameters::BitcoinAddressAndFee {
+                address,
+                fee_per_wu: None,
+            } => ActionExecutionParameters::BitcoinAddressAndFee {
+                address,
+                fee_per_wu: FeeEstimates::new(connector)
+                    .suggested_fee_per_wu(action)
+                    .await
+                    .map(|fee_per_wu| fee_per_wu.to_string()),
+            },
+            parameters => parameters,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(tag = "type", content = "payload")]
@@ -268,6 +379,12 @@ impl From<ethereum::CallContract> for ActionResponseBody {
     }
 }
 
+impl ConfirmationTarget for SendToAddress {
+    fn confirmation_target(&self, _: Timestamp) -> Option<u16> {
+        None
+    }
+}
+
 impl ListRequiredFields for SendToAddress {
     fn list_required_fields() -> Vec<siren::Field> {
         vec![]
@@ -282,7 +399,7 @@ impl IntoResponsePayload for SpendOutput {
         match query_params {
             ActionExecutionParameters::BitcoinAddressAndFee {
                 address,
-                fee_per_wu,
+                fee_per_wu: Some(fee_per_wu),
             } => {
                 let fee_per_wu = fee_per_wu.parse::<usize>().with_context(|| {
                     HttpApiProblem::new("Invalid query parameter.")
@@ -331,7 +448,7 @@ impl IntoResponsePayload for SpendOutput {
                         name: "fee_per_wu",
                         data_type: "uint",
                         description:
-                        "The fee per weight unit you want to pay for the transaction in satoshis.",
+                        "The fee per weight unit you want to pay for the transaction in satoshis, required if cnd cannot estimate it.",
                     },
                 ]
             }))
@@ -339,6 +456,12 @@ impl IntoResponsePayload for SpendOutput {
     }
 }
 
+impl ConfirmationTarget for SpendOutput {
+    fn confirmation_target(&self, now: Timestamp) -> Option<u16> {
+        Some(confirmation_target(self.htlc_expiry, now))
+    }
+}
+
 impl ListRequiredFields for SpendOutput {
     fn list_required_fields() -> Vec<siren::Field> {
         vec![
@@ -381,6 +504,12 @@ impl IntoResponsePayload for ethereum::DeployContract {
     }
 }
 
+impl ConfirmationTarget for ethereum::DeployContract {
+    fn confirmation_target(&self, _: Timestamp) -> Option<u16> {
+        None
+    }
+}
+
 impl ListRequiredFields for ethereum::DeployContract {
     fn list_required_fields() -> Vec<siren::Field> {
         vec![]
@@ -402,6 +531,12 @@ impl IntoResponsePayload for ethereum::CallContract {
     }
 }
 
+impl ConfirmationTarget for ethereum::CallContract {
+    fn confirmation_target(&self, _: Timestamp) -> Option<u16> {
+        None
+    }
+}
+
 impl ListRequiredFields for ethereum::CallContract {
     fn list_required_fields() -> Vec<siren::Field> {
         vec![]
@@ -414,6 +549,12 @@ impl ListRequiredFields for Infallible {
     }
 }
 
+impl ConfirmationTarget for Infallible {
+    fn confirmation_target(&self, _: Timestamp) -> Option<u16> {
+        unreachable!("how did you manage to construct Infallible?")
+    }
+}
+
 impl IntoResponsePayload for Infallible {
     fn into_response_payload(
         self,
@@ -447,11 +588,106 @@ mod test {
             res,
             Ok(ActionExecutionParameters::BitcoinAddressAndFee {
                 address: "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".parse().unwrap(),
-                fee_per_wu: "10.59".to_string(),
+                fee_per_wu: Some("10.59".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn given_bitcoin_identity_only_deserialize_without_fee() {
+        let s = "address=1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa";
+
+        let res = serde_urlencoded::from_str::<ActionExecutionParameters>(s);
+        assert_eq!(
+            res,
+            Ok(ActionExecutionParameters::BitcoinAddressAndFee {
+                address: "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".parse().unwrap(),
+                fee_per_wu: None,
             })
         );
     }
 
+    #[test]
+    fn confirmation_target_is_half_the_blocks_until_expiry() {
+        let now = Timestamp::from(1_000_000);
+
+        assert_eq!(confirmation_target(now.plus(12 * 600), now), 6);
+        assert_eq!(confirmation_target(now.plus(7 * 24 * 3600), now), 144);
+    }
+
+    #[test]
+    fn confirmation_target_is_next_block_once_expired() {
+        let now = Timestamp::from(1_000_000);
+
+        assert_eq!(confirmation_target(now.plus(600), now), 1);
+        assert_eq!(confirmation_target(now, now.plus(3600)), 1);
+    }
+
+    struct CountingConnector {
+        can_estimate_fee: bool,
+        estimates: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl EstimateFeePerWu for CountingConnector {
+        fn can_estimate_fee(&self) -> bool {
+            self.can_estimate_fee
+        }
+
+        async fn estimate_fee_per_wu(&self, confirmation_target: u16) -> anyhow::Result<usize> {
+            self.estimates
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+
+            Ok(usize::from(confirmation_target))
+        }
+    }
+
+    struct Target(u16);
+
+    impl ConfirmationTarget for Target {
+        fn confirmation_target(&self, _: Timestamp) -> Option<u16> {
+            Some(self.0)
+        }
+    }
+
+    #[tokio::test]
+    async fn fee_is_estimated_once_per_confirmation_target() {
+        let connector = CountingConnector {
+            can_estimate_fee: true,
+            estimates: Default::default(),
+        };
+        let mut fee_estimates = FeeEstimates::new(&connector);
+
+        assert_eq!(
+            fee_estimates.suggested_fee_per_wu(&Target(6)).await,
+            Some(6)
+        );
+        assert_eq!(
+            fee_estimates.suggested_fee_per_wu(&Target(6)).await,
+            Some(6)
+        );
+        assert_eq!(
+            fee_estimates.suggested_fee_per_wu(&Target(1)).await,
+            Some(1)
+        );
+        assert_eq!(connector.estimates.into_inner(), 2);
+    }
+
+    #[tokio::test]
+    async fn fee_is_not_estimated_if_the_backend_cannot() {
+        let connector = CountingConnector {
+            can_estimate_fee: false,
+            estimates: Default::default(),
+        };
+
+        let fee_per_wu = FeeEstimates::new(&connector)
+            .suggested_fee_per_wu(&Target(6))
+            .await;
+
+        assert_eq!(fee_per_wu, None);
+        assert_eq!(connector.estimates.into_inner(), 0);
+    }
+
     #[test]
     fn call_contract_serializes_correctly_to_json_with_none() {
         let addr =
@@ -0,0 +1,344 @@
+//! Bearer token authentication of the HTTP API.
+//!
+//! Similar to the macaroons of lnd, there is one token for each scope and
+//! tokens which are not configured are generated and kept in the data
+//! directory, for clients running on the same machine to pick them up.
+
+use crate::{config::Auth, http_api::generated_files};
+use http_api_problem::HttpApiProblem;
+use rand::RngCore;
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use warp::{filters::BoxedFilter, http::StatusCode, Filter};
+
+const TOKEN_LENGTH: usize = 32;
+
+/// What a token grants access to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scope {
+    /// Reading swaps, peers and offers.
+    ReadOnly,
+    /// Everything, including creating swaps and fetching their actions.
+    Admin,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tokens {
+    admin: String,
+    read_only: String,
+}
+
+impl Tokens {
+    /// Uses the configured tokens, the missing ones are read from the data
+    /// directory or generated and written there.
+    pub fn from_config_or_generate<R>(
+        auth: &Auth,
+        data_dir: &Path,
+        mut rand: R,
+    ) -> anyhow::Result<Self>
+    where
+        R: RngCore,
+    {
+        let admin = match &auth.admin_token {
+            Some(token) => configured(token, Scope::Admin)?,
+            None => read_or_generate(&token_path(data_dir, Scope::Admin), &mut rand)?,
+        };
+        let read_only = match &auth.read_only_token {
+            Some(token) => configured(token, Scope::ReadOnly)?,
+            None => read_or_generate(&token_path(data_dir, Scope::ReadOnly), &mut rand)?,
+        };
+
+        if admin == read_only {
+            anyhow::bail!("the admin and the read-only token of the HTTP API must differ")
+        }
+
+        Ok(Self { admin, read_only })
+    }
+
+    /// The admin token grants every scope.
+    fn grants(&self, token: &str, scope: Scope) -> bool {
+        match scope {
+            Scope::Admin => constant_time_eq(token, &self.admin),
+            Scope::ReadOnly => {
+                constant_time_eq(token, &self.admin) | constant_time_eq(token, &self.read_only)
+            }
+        }
+    }
+}
+
+/// Rejects requests which do not carry a token granting the scope in their
+/// `Authorization` header.
+///
+/// Every request passes if no tokens are given, i.e. authentication is
+/// disabled.
+pub fn scope(tokens: Option<Tokens>, scope: Scope) -> BoxedFilter<()> {
+    warp::header::optional::<String>("authorization")
+        .and_then(move |header: Option<String>| {
+            let result = match &tokens {
+                None => Ok(()),
+                Some(tokens) => authorize(
+                    tokens,
+                    header.as_deref().and_then(bearer_token),
+                    scope,
+                    "Provide a token as bearer token in the Authorization header.",
+                ),
+            };
+
+            async move { result.map_err(warp::reject::custom) }
+        })
+        .untuple_one()
+        .boxed()
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+/// Like `scope` but also accepts the token in the `token` query parameter.
+///
+/// Meant for the event stream, browsers cannot set headers on an
+/// `EventSource`.
+pub fn scope_or_query_token(tokens: Option<Tokens>, scope: Scope) -> BoxedFilter<()> {
+    let query = warp::query::<TokenQuery>()
+        .or(warp::any().map(TokenQuery::default))
+        .unify();
+
+    warp::header::optional::<String>("authorization")
+        .and(query)
+        .and_then(move |header: Option<String>, query: TokenQuery| {
+            let result = match &tokens {
+                None => Ok(()),
+                Some(tokens) => authorize(
+                    tokens,
+                    header
+                        .as_deref()
+                        .and_then(bearer_token)
+                        .or_else(|| query.token.as_deref()),
+                    scope,
+                    "Provide a token as bearer token in the Authorization header or in the \
+                     token query parameter.",
+                ),
+            };
+
+            async move { result.map_err(warp::reject::custom) }
+        })
+        .untuple_one()
+        .boxed()
+}
+
+fn authorize(
+    tokens: &Tokens,
+    token: Option<&str>,
+    scope: Scope,
+    missing_token_detail: &str,
+) -> Result<(), HttpApiProblem> {
+    let token = match token {
+        Some(token) => token,
+        None => return Err(unauthorized(missing_token_detail)),
+    };
+
+    if !tokens.grants(token, scope) {
+        return Err(unauthorized(match scope {
+            Scope::Admin => "This route requires the admin token.",
+            Scope::ReadOnly => "The token is neither the admin nor the read-only token.",
+        }));
+    }
+
+    Ok(())
+}
+
+fn unauthorized(detail: &str) -> HttpApiProblem {
+    tracing::warn!("rejecting unauthorized request to the HTTP API");
+
+    HttpApiProblem::new("Unauthorized.")
+        .set_status(StatusCode::UNAUTHORIZED)
+        .set_detail(detail)
+}
+
+fn bearer_token(header: &str) -> Option<&str> {
+    let mut parts = header.splitn(2, ' ');
+    let scheme = parts.next()?;
+    let token = parts.next()?.trim();
+
+    if scheme.eq_ignore_ascii_case("bearer") && !token.is_empty() {
+        Some(token)
+    } else {
+        None
+    }
+}
+
+/// Does not return early on the first differing byte, so the time it takes
+/// does not tell how much of a guessed token is correct.
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |difference, (x, y)| difference | (x ^ y))
+            == 0
+}
+
+fn token_path(data_dir: &Path, scope: Scope) -> PathBuf {
+    let file = match scope {
+        Scope::Admin => "admin.token",
+        Scope::ReadOnly => "read_only.token",
+    };
+
+    generated_files::dir(data_dir).join(file)
+}
+
+/// An empty token would be granted to anyone sending an empty `token` query
+/// parameter.
+fn configured(token: &str, scope: Scope) -> anyhow::Result<String> {
+    if token.trim().is_empty() {
+        anyhow::bail!(
+            "the {} token of the HTTP API must not be empty",
+            match scope {
+                Scope::Admin => "admin",
+                Scope::ReadOnly => "read-only",
+            }
+        )
+    }
+
+    Ok(token.to_owned())
+}
+
+/// An empty token file, e.g. truncated by hand, is replaced by a newly
+/// generated token.
+fn read_or_generate<R>(path: &Path, rand: &mut R) -> anyhow::Result<String>
+where
+    R: RngCore,
+{
+    if path.exists() {
+        let token = fs::read_to_string(path)?.trim().to_owned();
+
+        if !token.is_empty() {
+            tracing::info!("Read in HTTP API token from file: {}", path.display());
+
+            return Ok(token);
+        }
+
+        tracing::warn!("HTTP API token file {} is empty", path.display());
+    }
+
+    let mut bytes = [0u8; TOKEN_LENGTH];
+    rand.try_fill_bytes(&mut bytes)?;
+    let token = hex::encode(bytes);
+
+    generated_files::write_private(path, token.as_bytes())?;
+
+    tracing::info!("No HTTP API token found, creating at: {}", path.display());
+
+    Ok(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    fn tokens() -> Tokens {
+        Tokens {
+            admin: "admin".to_owned(),
+            read_only: "read".to_owned(),
+        }
+    }
+
+    async fn request(scope: Scope, authorization: Option<&str>) -> bool {
+        let filter = super::scope(Some(tokens()), scope);
+        let request = warp::test::request();
+        let request = match authorization {
+            Some(authorization) => request.header("authorization", authorization),
+            None => request,
+        };
+
+        request.filter(&filter).await.is_ok()
+    }
+
+    #[tokio::test]
+    async fn admin_token_grants_every_scope() {
+        assert!(request(Scope::Admin, Some("Bearer admin")).await);
+        assert!(request(Scope::ReadOnly, Some("Bearer admin")).await);
+    }
+
+    #[tokio::test]
+    async fn read_only_token_does_not_grant_admin_scope() {
+        assert!(request(Scope::ReadOnly, Some("Bearer read")).await);
+        assert!(!request(Scope::Admin, Some("Bearer read")).await);
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_and_unknown_tokens() {
+        assert!(!request(Scope::ReadOnly, None).await);
+        assert!(!request(Scope::ReadOnly, Some("Bearer adm")).await);
+        assert!(!request(Scope::ReadOnly, Some("Basic admin")).await);
+    }
+
+    #[tokio::test]
+    async fn event_stream_accepts_the_token_as_query_parameter() {
+        let filter = scope_or_query_token(Some(tokens()), Scope::ReadOnly);
+        let request = |path: &str| warp::test::request().path(path);
+
+        assert!(request("/?token=read").filter(&filter).await.is_ok());
+        assert!(request("/?token=adm").filter(&filter).await.is_err());
+        assert!(request("/").filter(&filter).await.is_err());
+        assert!(request("/")
+            .header("authorization", "Bearer read")
+            .filter(&filter)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn passes_every_request_without_tokens() {
+        let filter = scope(None, Scope::Admin);
+
+        assert!(warp::test::request().filter(&filter).await.is_ok());
+    }
+
+    #[test]
+    fn generated_tokens_are_read_back_from_the_data_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let auth = Auth {
+            admin_token: None,
+            read_only_token: Some("read".to_owned()),
+        };
+
+        let generated = Tokens::from_config_or_generate(&auth, dir.path(), OsRng).unwrap();
+        let read_back = Tokens::from_config_or_generate(&auth, dir.path(), OsRng).unwrap();
+
+        assert_eq!(generated, read_back);
+        assert_eq!(generated.admin.len(), TOKEN_LENGTH * 2);
+        assert!(!token_path(dir.path(), Scope::ReadOnly).exists());
+    }
+
+    #[test]
+    fn refuses_empty_configured_tokens() {
+        let dir = tempfile::tempdir().unwrap();
+        let auth = Auth {
+            admin_token: Some("admin".to_owned()),
+            read_only_token: Some(" ".to_owned()),
+        };
+
+        assert!(Tokens::from_config_or_generate(&auth, dir.path(), OsRng).is_err());
+    }
+
+    #[test]
+    fn replaces_an_empty_token_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = token_path(dir.path(), Scope::Admin);
+        generated_files::write_private(&path, b"\n").unwrap();
+        let auth = Auth {
+            admin_token: None,
+            read_only_token: Some("read".to_owned()),
+        };
+
+        let tokens = Tokens::from_config_or_generate(&auth, dir.path(), OsRng).unwrap();
+
+        assert_eq!(tokens.admin.len(), TOKEN_LENGTH * 2);
+        assert_eq!(fs::read_to_string(&path).unwrap(), tokens.admin);
+    }
+}
@@ -0,0 +1,49 @@
+//! The tokens and the certificate we generate into the data directory.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// The directory inside the data directory the generated tokens and
+/// certificate are kept in.
+pub fn dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("http_api")
+}
+
+/// Writes a file which, like the seed, must only be readable by us.
+pub fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    write_atomically(path, contents, 0o600)
+}
+
+/// Writes a file which other users may read, e.g. to hand it to their
+/// clients.
+pub fn write_public(path: &Path, contents: &[u8]) -> io::Result<()> {
+    write_atomically(path, contents, 0o644)
+}
+
+/// Writes to a temporary file first and renames it into place, so a crash
+/// never leaves a truncated file behind.
+#[cfg_attr(not(unix), allow(unused_variables))]
+fn write_atomically(path: &Path, contents: &[u8], mode: u32) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let temp_path = path.with_extension("tmp");
+
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(mode);
+    }
+
+    let mut file = options.open(&temp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+
+    fs::rename(&temp_path, path)
+}
@@ -1,10 +1,17 @@
 use crate::{
+    btsieve::bitcoin::TransactionRejected,
     db,
+    expiries::UnsafeExpiry,
     http_api::routes::{
-        rfc003::handlers::{post_swap::UnsupportedSwap, InvalidAction, InvalidActionInvocation},
+        rfc003::handlers::{
+            post_swap::UnsupportedSwap, ActionNotExecutable, InvalidAction, InvalidActionInvocation,
+        },
         LndActionError,
     },
-    network::comit_ln::SwapExists,
+    network::{
+        comit_ln::SwapExists,
+        orderbook::{AmountNotWithinLimits, OfferNotFound, TakeRejected},
+    },
 };
 use http_api_problem::HttpApiProblem;
 use warp::{
@@ -57,6 +64,27 @@ pub fn from_anyhow(e: anyhow::Error) -> HttpApiProblem {
         return HttpApiProblem::new("Swap already exists.").set_status(StatusCode::BAD_REQUEST);
     }
 
+    if let Some(err) = e.downcast_ref::<OfferNotFound>() {
+        tracing::warn!("{}", err);
+        return HttpApiProblem::new("Offer not found.")
+            .set_status(StatusCode::NOT_FOUND)
+            .set_detail("Fetch the offers of the maker before taking one of them.");
+    }
+
+    if let Some(err) = e.downcast_ref::<TakeRejected>() {
+        tracing::warn!("{}", err);
+        return HttpApiProblem::new("Take rejected.")
+            .set_status(StatusCode::CONFLICT)
+            .set_detail("The maker did not accept the take, no swap was created.");
+    }
+
+    if let Some(err) = e.downcast_ref::<AmountNotWithinLimits>() {
+        tracing::warn!("{}", err);
+        return HttpApiProblem::new("Amount not within limits.")
+            .set_status(StatusCode::BAD_REQUEST)
+            .set_detail(format!("{}", err));
+    }
+
     if let Some(e) = e.downcast_ref::<UnexpectedQueryParameters>() {
         tracing::error!("{}", e);
 
@@ -108,6 +136,24 @@ pub fn from_anyhow(e: anyhow::Error) -> HttpApiProblem {
             .set_detail("Cannot perform requested action for this swap.");
     }
 
+    if e.is::<ActionNotExecutable>() {
+        tracing::warn!("{}", e);
+
+        return HttpApiProblem::new("Action cannot be executed.")
+            .set_status(StatusCode::BAD_REQUEST)
+            .set_detail(
+                "Only actions resulting in a signed Bitcoin transaction can be executed by cnd.",
+            );
+    }
+
+    if let Some(err) = e.downcast_ref::<TransactionRejected>() {
+        tracing::warn!("{}", e);
+
+        return HttpApiProblem::new("Transaction was rejected.")
+            .set_status(StatusCode::BAD_REQUEST)
+            .set_detail(err.message.clone());
+    }
+
     if e.is::<UnsupportedSwap>() {
         tracing::warn!("{}", e);
 
@@ -116,6 +162,14 @@ pub fn from_anyhow(e: anyhow::Error) -> HttpApiProblem {
             .set_detail("The requested combination of ledgers and assets is not supported.");
     }
 
+    if let Some(err) = e.downcast_ref::<UnsafeExpiry>() {
+        tracing::warn!("{}", err);
+
+        return HttpApiProblem::new("Unsafe expiries.")
+            .set_status(StatusCode::BAD_REQUEST)
+            .set_detail(format!("{}.", err));
+    }
+
     if e.is::<LndActionError>() {
         return HttpApiProblem::new("Action not found.").set_status(StatusCode::NOT_FOUND);
     }
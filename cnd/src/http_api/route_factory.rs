@@ -1,8 +1,14 @@
 use crate::{
     config::settings::AllowedOrigins,
-    http_api,
-    network::LocalPeerId,
-    swap_protocols::{self, rfc003::SwapId, Facade, LocalSwapId, Rfc003Facade},
+    http_api::{
+        self,
+        auth::{self, Scope, Tokens},
+        routes::rfc003::handlers::GetSwapsQuery,
+    },
+    network::{protocols::orderbook::OfferId, LocalPeerId},
+    swap_protocols::{
+        self, rfc003::SwapId, state_changes::StateChanges, Facade, LocalSwapId, Rfc003Facade,
+    },
 };
 use warp::{self, filters::BoxedFilter, Filter, Reply};
 
@@ -19,19 +25,26 @@ pub fn new_action_link(id: &SwapId, action: &str) -> String {
 pub fn create(
     rfc003_facade: Rfc003Facade,
     facade: Facade,
+    state_changes: StateChanges,
     allowed_origins: &AllowedOrigins,
+    tokens: Option<Tokens>,
 ) -> BoxedFilter<(impl Reply,)> {
+    let read_only = auth::scope(tokens.clone(), Scope::ReadOnly);
+    let read_only_event_stream = auth::scope_or_query_token(tokens.clone(), Scope::ReadOnly);
+    let admin = auth::scope(tokens, Scope::Admin);
     let peer_id = rfc003_facade.local_peer_id();
     let swaps = warp::path(http_api::PATH);
     let rfc003 = swaps.and(warp::path(RFC003));
     let peer_id = warp::any().map(move || peer_id.clone());
     let empty_json_body = warp::any().map(|| serde_json::json!({}));
+    let raw_query = warp::query::raw().or(warp::any().map(String::new)).unify();
     let rfc003_facade = warp::any().map(move || rfc003_facade.clone());
     let facade = warp::any().map(move || facade.clone());
+    let state_changes = warp::any().map(move || state_changes.clone());
 
     let cors = warp::cors()
         .allow_methods(vec!["GET", "POST"])
-        .allow_header("content-type");
+        .allow_headers(vec!["content-type", "authorization"]);
     let cors = match allowed_origins {
         AllowedOrigins::None => cors.allow_origins(Vec::<&str>::new()),
         AllowedOrigins::All => cors.allow_any_origin(),
@@ -45,6 +58,7 @@ pub fn create(
     let rfc003_post_swap = rfc003
         .and(warp::path::end())
         .and(warp::post())
+        .and(admin.clone())
         .and(rfc003_facade.clone())
         .and(warp::body::json())
         .and_then(http_api::routes::rfc003::post_swap);
@@ -54,12 +68,17 @@ pub fn create(
         .and(rfc003_facade.clone())
         .and(warp::path::param())
         .and(warp::path::end())
+        .and(read_only.clone())
         .and_then(http_api::routes::rfc003::get_swap);
 
     let get_swaps = swaps
         .and(warp::get())
         .and(warp::path::end())
+        .and(read_only.clone())
         .and(rfc003_facade.clone())
+        .and(facade.clone())
+        .and(warp::query::<GetSwapsQuery>())
+        .and(raw_query)
         .and_then(http_api::routes::rfc003::get_swaps);
 
     let rfc003_action = warp::method()
@@ -69,26 +88,51 @@ pub fn create(
             swap_protocols::rfc003::actions::ActionKind,
         >())
         .and(warp::path::end())
+        .and(admin.clone())
         .and(warp::query::<http_api::action::ActionExecutionParameters>())
         .and(rfc003_facade.clone())
         .and(warp::body::json().or(empty_json_body).unify())
         .and_then(http_api::routes::rfc003::action);
 
+    let rfc003_execute_action = warp::post()
+        .and(rfc003)
+        .and(warp::path::param::<SwapId>())
+        .and(warp::path::param::<
+            swap_protocols::rfc003::actions::ActionKind,
+        >())
+        .and(warp::path("execute"))
+        .and(warp::path::end())
+        .and(admin.clone())
+        .and(warp::query::<http_api::action::ActionExecutionParameters>())
+        .and(rfc003_facade.clone())
+        .and_then(http_api::routes::rfc003::execute_action);
+
+    let get_events = swaps
+        .and(warp::get())
+        .and(warp::path("events"))
+        .and(warp::path::end())
+        .and(read_only_event_stream)
+        .and(state_changes)
+        .and_then(http_api::routes::events::get_events);
+
     let get_peers = warp::get()
         .and(warp::path("peers"))
         .and(warp::path::end())
+        .and(read_only.clone())
         .and(rfc003_facade.clone())
         .and_then(http_api::routes::peers::get_peers);
 
     let get_info_siren = warp::get()
         .and(warp::path::end())
         .and(warp::header::exact("accept", "application/vnd.siren+json"))
+        .and(read_only.clone())
         .and(peer_id.clone())
         .and(rfc003_facade.clone())
         .and_then(http_api::routes::index::get_info_siren);
 
     let get_info = warp::get()
         .and(warp::path::end())
+        .and(read_only.clone())
         .and(peer_id)
         .and(rfc003_facade)
         .and_then(http_api::routes::index::get_info);
@@ -98,6 +142,7 @@ pub fn create(
             "swaps" / "han" / "ethereum" / "ether" / "halight" / "lightning" / "bitcoin"
         ))
         .and(warp::path::end())
+        .and(admin.clone())
         .and(warp::body::json())
         .and(facade.clone())
         .and_then(http_api::routes::index::post_han_ethereum_halight_bitcoin);
@@ -107,6 +152,7 @@ pub fn create(
             "swaps" / "herc20" / "ethereum" / "erc20" / "halight" / "lightning" / "bitcoin"
         ))
         .and(warp::path::end())
+        .and(admin.clone())
         .and(warp::body::json())
         .and(facade.clone())
         .and_then(http_api::routes::index::post_herc20_halight_bitcoin);
@@ -116,6 +162,7 @@ pub fn create(
             "swaps" / "halight" / "lightning" / "bitcoin" / "han" / "ethereum" / "ether"
         ))
         .and(warp::path::end())
+        .and(admin.clone())
         .and(warp::body::json())
         .and(facade.clone())
         .and_then(http_api::routes::index::post_halight_bitcoin_han_ether);
@@ -125,14 +172,52 @@ pub fn create(
             "swaps" / "halight" / "lightning" / "bitcoin" / "herc20" / "ethereum" / "erc20"
         ))
         .and(warp::path::end())
+        .and(admin.clone())
         .and(warp::body::json())
         .and(facade.clone())
         .and_then(http_api::routes::index::post_halight_bitcoin_herc20);
 
+    let offers = warp::path("offers");
+
+    let post_offer = offers
+        .and(warp::post())
+        .and(warp::path::end())
+        .and(admin.clone())
+        .and(warp::body::json())
+        .and(facade.clone())
+        .and_then(http_api::routes::offers::post_offer);
+
+    let get_offers = offers
+        .and(warp::get())
+        .and(warp::path::end())
+        .and(read_only.clone())
+        .and(facade.clone())
+        .and_then(http_api::routes::offers::get_offers);
+
+    let fetch_offers = offers
+        .and(warp::post())
+        .and(warp::path("fetch"))
+        .and(warp::path::end())
+        .and(admin.clone())
+        .and(warp::body::json())
+        .and(facade.clone())
+        .and_then(http_api::routes::offers::post_fetch_offers);
+
+    let take_offer = offers
+        .and(warp::post())
+        .and(warp::path::param::<OfferId>())
+        .and(warp::path("take"))
+        .and(warp::path::end())
+        .and(admin.clone())
+        .and(warp::body::json())
+        .and(facade.clone())
+        .and_then(http_api::routes::offers::post_take_offer);
+
     let get_halight_swap = swaps
         .and(warp::get())
         .and(warp::path::param())
         .and(warp::path::end())
+        .and(read_only)
         .and(facade.clone())
         .and_then(http_api::routes::get_halight_swap);
 
@@ -141,6 +226,7 @@ pub fn create(
         .and(warp::path::param::<LocalSwapId>())
         .and(warp::path("init"))
         .and(warp::path::end())
+        .and(admin.clone())
         .and(facade.clone())
         .and_then(http_api::routes::action_init);
 
@@ -149,6 +235,7 @@ pub fn create(
         .and(warp::path::param::<LocalSwapId>())
         .and(warp::path("fund"))
         .and(warp::path::end())
+        .and(admin.clone())
         .and(facade.clone())
         .and_then(http_api::routes::action_fund);
 
@@ -157,6 +244,7 @@ pub fn create(
         .and(warp::path::param::<LocalSwapId>())
         .and(warp::path("redeem"))
         .and(warp::path::end())
+        .and(admin.clone())
         .and(facade.clone())
         .and_then(http_api::routes::action_redeem);
 
@@ -165,6 +253,7 @@ pub fn create(
         .and(warp::path::param::<LocalSwapId>())
         .and(warp::path("refund"))
         .and(warp::path::end())
+        .and(admin)
         .and(facade)
         .and_then(http_api::routes::action_refund);
 
@@ -172,7 +261,9 @@ pub fn create(
         .or(rfc003_get_swap)
         .or(rfc003_post_swap)
         .or(rfc003_action)
+        .or(rfc003_execute_action)
         .or(get_swaps)
+        .or(get_events)
         .or(get_peers)
         .or(get_info_siren)
         .or(get_info)
@@ -180,6 +271,10 @@ pub fn create(
         .or(herc20_halight_bitcoin)
         .or(halight_bitcoin_han_ether)
         .or(halight_bitcoin_herc20)
+        .or(post_offer)
+        .or(get_offers)
+        .or(fetch_offers)
+        .or(take_offer)
         .or(get_halight_swap)
         .or(lightning_action_init)
         .or(lightning_action_fund)
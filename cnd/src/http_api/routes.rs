@@ -1,12 +1,15 @@
+pub mod events;
 pub mod index;
+pub mod offers;
 pub mod peers;
 pub mod rfc003;
 
 use crate::{
     asset,
+    db::{CreatedSwap, HanEtherereumHalightBitcoinSwap},
     ethereum::Bytes,
     htlc_location,
-    http_api::{action::ActionResponseBody, problem},
+    http_api::{action::ActionResponseBody, problem, Http, SwapStatus, PATH},
     network::comit_ln,
     swap_protocols::{
         actions::{
@@ -15,7 +18,7 @@ use crate::{
         },
         halight::{self, Settled},
         ledger::ethereum::ChainId,
-        rfc003::LedgerState,
+        rfc003::{HtlcState, LedgerState},
         state::Get,
         Facade, FundAction, InitAction, LocalSwapId, RedeemAction, RefundAction, Role,
     },
@@ -23,7 +26,14 @@ use crate::{
 };
 use blockchain_contracts::ethereum::rfc003::ether_htlc::EtherHtlc;
 use http_api_problem::HttpApiProblem;
-use warp::{http, Rejection, Reply};
+use libp2p::PeerId;
+use serde::Serialize;
+use warp::{http, http::StatusCode, Rejection, Reply};
+
+/// How han-ether/halight swaps are named in the `protocol` of their resource.
+pub const HAN_ETHEREUM_HALIGHT_BITCOIN: &str = "han-halight";
+
+type EtherLedgerState = LedgerState<asset::Ether, htlc_location::Ethereum, transaction::Ethereum>;
 
 pub fn into_rejection(problem: HttpApiProblem) -> Rejection {
     warp::reject::custom(problem)
@@ -46,11 +56,7 @@ pub async fn handle_get_halight_swap(
     swap_id: LocalSwapId,
 ) -> anyhow::Result<siren::Entity> {
     // This is ok, we use a new create_watcher in han.rs and call it with local id.
-    let alpha_ledger_state: Option<
-        LedgerState<asset::Ether, htlc_location::Ethereum, transaction::Ethereum>,
-    > = facade.alpha_ledger_states.get(&swap_id).await?;
-
-    let beta_ledger_state = facade.beta_ledger_states.get(&swap_id).await?;
+    let (alpha_ledger_state, beta_ledger_state) = ledger_states(&facade, swap_id).await?;
 
     let finalized_swap = facade.get_finalized_swap(swap_id).await;
 
@@ -70,7 +76,129 @@ pub async fn handle_get_halight_swap(
             }
         };
 
-    let entity = match finalized_swap.role {
+    let maybe_action_names = action_names(alpha_ledger_state, beta_ledger_state, finalized_swap);
+
+    Ok(make_swap_entity(swap_id, maybe_action_names))
+}
+
+#[derive(Debug, Serialize)]
+pub struct HanEthereumHalightBitcoinSwapResource {
+    pub id: LocalSwapId,
+    pub role: String,
+    pub counterparty: Http<PeerId>,
+    pub protocol: &'static str,
+    pub status: SwapStatus,
+    pub state: HanEthereumHalightBitcoinSwapState,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HanEthereumHalightBitcoinSwapState {
+    pub alpha_ledger: rfc003::LedgerState<htlc_location::Ethereum, transaction::Ethereum>,
+    pub beta_ledger: HalightLedgerState,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HalightLedgerState {
+    pub status: halight::Status,
+}
+
+/// Builds the entity of a han-ether/halight swap as listed by `GET /swaps`.
+///
+/// Ledgers we do not watch yet, e.g. because the swap is not finalized, are
+/// shown in their initial state.
+pub async fn build_han_ethereum_halight_bitcoin_siren_entity(
+    facade: &Facade,
+    swap: HanEtherereumHalightBitcoinSwap,
+) -> anyhow::Result<siren::Entity> {
+    let (CreatedSwap { swap_id, params }, ..) = swap;
+
+    let (alpha_ledger_state, beta_ledger_state) = ledger_states(facade, swap_id).await?;
+    let alpha = alpha_ledger_state
+        .clone()
+        .unwrap_or(LedgerState::NotDeployed);
+    let beta = beta_ledger_state.unwrap_or(halight::State::None);
+
+    let resource = HanEthereumHalightBitcoinSwapResource {
+        id: swap_id,
+        role: params.role.to_string(),
+        counterparty: Http(params.peer.peer_id),
+        protocol: HAN_ETHEREUM_HALIGHT_BITCOIN,
+        status: status(HtlcState::from(&alpha), halight::Status::from(&beta)),
+        state: HanEthereumHalightBitcoinSwapState {
+            alpha_ledger: rfc003::LedgerState::from(alpha),
+            beta_ledger: HalightLedgerState {
+                status: halight::Status::from(&beta),
+            },
+        },
+    };
+
+    let finalized_swap = facade.get_finalized_swap(swap_id).await;
+    let maybe_action_names = match (alpha_ledger_state, beta_ledger_state, finalized_swap) {
+        (Some(alpha_ledger_state), Some(beta_ledger_state), Some(finalized_swap)) => {
+            action_names(alpha_ledger_state, beta_ledger_state, finalized_swap)
+        }
+        _ => vec![],
+    };
+
+    let entity = make_swap_entity(swap_id, maybe_action_names)
+        .with_properties(resource)
+        .map_err(|e| {
+            tracing::error!("failed to set properties of entity: {:?}", e);
+            HttpApiProblem::with_title_and_type_from_status(StatusCode::INTERNAL_SERVER_ERROR)
+        })?
+        .with_link(siren::NavigationalLink::new(
+            &["self"],
+            format!("/{}/{}", PATH, swap_id),
+        ));
+
+    Ok(entity)
+}
+
+/// The status of a han-ether/halight swap without building its resource.
+pub async fn han_ethereum_halight_bitcoin_swap_status(
+    facade: &Facade,
+    swap_id: LocalSwapId,
+) -> anyhow::Result<SwapStatus> {
+    let (alpha_ledger_state, beta_ledger_state) = ledger_states(facade, swap_id).await?;
+
+    Ok(status(
+        alpha_ledger_state
+            .as_ref()
+            .map_or(HtlcState::NotDeployed, HtlcState::from),
+        beta_ledger_state
+            .as_ref()
+            .map_or(halight::Status::None, halight::Status::from),
+    ))
+}
+
+async fn ledger_states(
+    facade: &Facade,
+    swap_id: LocalSwapId,
+) -> anyhow::Result<(Option<EtherLedgerState>, Option<halight::State>)> {
+    let alpha_ledger_state: Option<EtherLedgerState> =
+        facade.alpha_ledger_states.get(&swap_id).await?;
+    let beta_ledger_state = facade.beta_ledger_states.get(&swap_id).await?;
+
+    Ok((alpha_ledger_state, beta_ledger_state))
+}
+
+/// Same as for rfc003 swaps, a swap is only swapped once both parties
+/// redeemed.
+fn status(alpha_ledger: HtlcState, beta_ledger: halight::Status) -> SwapStatus {
+    match (alpha_ledger, beta_ledger) {
+        (HtlcState::Redeemed, halight::Status::Settled) => SwapStatus::Swapped,
+        (HtlcState::IncorrectlyFunded, _) | (HtlcState::Refunded, _) => SwapStatus::NotSwapped,
+        (_, halight::Status::Cancelled) => SwapStatus::NotSwapped,
+        _ => SwapStatus::InProgress,
+    }
+}
+
+fn action_names(
+    alpha_ledger_state: EtherLedgerState,
+    beta_ledger_state: halight::State,
+    finalized_swap: comit_ln::FinalizedSwap,
+) -> Vec<Option<&'static str>> {
+    match finalized_swap.role {
         Role::Alice => {
             let state = AliceHanEthereumHalightBitcoinState {
                 alpha_ledger_state,
@@ -78,14 +206,12 @@ pub async fn handle_get_halight_swap(
                 finalized_swap,
             };
 
-            let maybe_action_names = vec![
+            vec![
                 state.init_action().map(|_| "init"),
                 state.fund_action().map(|_| "fund"),
                 state.redeem_action().map(|_| "redeem"),
                 state.refund_action().map(|_| "refund"),
-            ];
-
-            make_swap_entity(swap_id, maybe_action_names)
+            ]
         }
         Role::Bob => {
             let state = BobHanEthereumHalightBitcoinState {
@@ -95,16 +221,12 @@ pub async fn handle_get_halight_swap(
             };
 
             // Bob cannot init and refund in this swap combination
-            let maybe_action_names = vec![
+            vec![
                 state.fund_action().map(|_| "fund"),
                 state.redeem_action().map(|_| "redeem"),
-            ];
-
-            make_swap_entity(swap_id, maybe_action_names)
+            ]
         }
-    };
-
-    Ok(entity)
+    }
 }
 
 fn make_swap_entity(swap_id: LocalSwapId, maybe_action_names: Vec<Option<&str>>) -> siren::Entity {
@@ -134,16 +256,14 @@ fn make_siren_action(swap_id: LocalSwapId, action_name: &str) -> siren::Action {
 
 #[derive(Debug)]
 pub struct AliceHanEthereumHalightBitcoinState {
-    pub alpha_ledger_state:
-        LedgerState<asset::Ether, htlc_location::Ethereum, transaction::Ethereum>,
+    pub alpha_ledger_state: EtherLedgerState,
     pub beta_ledger_state: halight::State,
     pub finalized_swap: comit_ln::FinalizedSwap,
 }
 
 #[derive(Debug)]
 pub struct BobHanEthereumHalightBitcoinState {
-    pub alpha_ledger_state:
-        LedgerState<asset::Ether, htlc_location::Ethereum, transaction::Ethereum>,
+    pub alpha_ledger_state: EtherLedgerState,
     pub beta_ledger_state: halight::State,
     pub finalized_swap: comit_ln::FinalizedSwap,
 }
@@ -539,3 +659,32 @@ pub enum LndActionError {
     #[error("action not found")]
     NotFound,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_ether_redeemed_and_invoice_settled_should_be_swapped() {
+        assert_eq!(
+            status(HtlcState::Redeemed, halight::Status::Settled),
+            SwapStatus::Swapped
+        );
+    }
+
+    #[test]
+    fn given_invoice_cancelled_should_be_not_swapped() {
+        assert_eq!(
+            status(HtlcState::Funded, halight::Status::Cancelled),
+            SwapStatus::NotSwapped
+        );
+    }
+
+    #[test]
+    fn given_invoice_opened_and_ether_not_deployed_should_be_in_progress() {
+        assert_eq!(
+            status(HtlcState::NotDeployed, halight::Status::Opened),
+            SwapStatus::InProgress
+        );
+    }
+}
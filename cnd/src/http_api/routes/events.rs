@@ -0,0 +1,33 @@
+use crate::swap_protocols::state_changes::{StateChange, StateChanges};
+use std::convert::Infallible;
+use tokio::sync::broadcast::{self, RecvError};
+use warp::{sse::ServerSentEvent, Rejection, Reply};
+
+/// Streams every change of a swap state as a server-sent event.
+#[allow(clippy::needless_pass_by_value)]
+pub async fn get_events(state_changes: StateChanges) -> Result<impl Reply, Rejection> {
+    let events = futures::stream::unfold(state_changes.subscribe(), next_event);
+
+    Ok(warp::sse::reply(warp::sse::keep_alive().stream(events)))
+}
+
+async fn next_event(
+    mut receiver: broadcast::Receiver<StateChange>,
+) -> Option<(
+    Result<impl ServerSentEvent, Infallible>,
+    broadcast::Receiver<StateChange>,
+)> {
+    loop {
+        match receiver.recv().await {
+            Ok(change) => {
+                let event = (warp::sse::event("state_change"), warp::sse::json(change));
+
+                return Some((Ok(event), receiver));
+            }
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!("event stream is lagging behind, skipped {} events", skipped);
+            }
+            Err(RecvError::Closed) => return None,
+        }
+    }
+}
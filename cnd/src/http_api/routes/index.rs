@@ -68,11 +68,24 @@ pub async fn post_han_ethereum_halight_bitcoin(
     let reply = warp::reply::reply();
 
     let id = LocalSwapId::default();
+    let swap_params = HanEtherereumHalightBitcoinCreateSwapParams::from(body);
 
-    facade.save(id, ()).await;
+    facade
+        .expiry_validator
+        .validate_ethereum(swap_params.ethereum_absolute_expiry)
+        .await
+        .map_err(anyhow::Error::new)
+        .map_err(problem::from_anyhow)
+        .map_err(into_rejection)?;
+
+    facade
+        .save(id, swap_params.clone())
+        .await
+        .map_err(problem::from_anyhow)
+        .map_err(into_rejection)?;
 
     facade
-        .initiate_communication(id, body.into())
+        .initiate_communication(id, swap_params)
         .await
         .map(|_| {
             warp::reply::with_status(
@@ -0,0 +1,173 @@
+use crate::{
+    asset,
+    http_api::{problem, routes::into_rejection, Http},
+    identity,
+    network::{
+        orderbook::OwnOffer,
+        protocols::orderbook::{Offer, OfferId},
+        DialInformation,
+    },
+    swap_protocols::{Facade, LocalSwapId},
+};
+use libp2p::PeerId;
+use serde::{Deserialize, Serialize};
+use warp::{http::StatusCode, Rejection, Reply};
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct PublishOfferBody {
+    pub ether_per_bitcoin: asset::Ether,
+    pub min_amount: Http<asset::Bitcoin>,
+    pub max_amount: Http<asset::Bitcoin>,
+    pub ethereum_expiry_offset: u32,
+    pub lightning_cltv_expiry: u32,
+    pub ethereum_identity: identity::Ethereum,
+    pub lightning_identity: identity::Lightning,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct FetchOffersBody {
+    pub peer: DialInformation,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct TakeOfferBody {
+    pub amount: Http<asset::Bitcoin>,
+    pub ethereum_identity: identity::Ethereum,
+    pub lightning_identity: identity::Lightning,
+}
+
+#[derive(Serialize, Debug)]
+pub struct OffersResource {
+    offers: Vec<OfferResource>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct OfferResource {
+    id: OfferId,
+    maker: Http<PeerId>,
+    ether_per_bitcoin: asset::Ether,
+    min_amount: Http<asset::Bitcoin>,
+    max_amount: Http<asset::Bitcoin>,
+    ethereum_expiry_offset: u32,
+    lightning_cltv_expiry: u32,
+}
+
+impl From<(PeerId, Offer)> for OfferResource {
+    fn from((maker, offer): (PeerId, Offer)) -> Self {
+        Self {
+            id: offer.id,
+            maker: Http(maker),
+            ether_per_bitcoin: offer.ether_per_bitcoin,
+            min_amount: Http(asset::Bitcoin::from_sat(offer.min_lightning_amount)),
+            max_amount: Http(asset::Bitcoin::from_sat(offer.max_lightning_amount)),
+            ethereum_expiry_offset: offer.ethereum_expiry_offset,
+            lightning_cltv_expiry: offer.lightning_cltv_expiry,
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub async fn post_offer(body: serde_json::Value, facade: Facade) -> Result<impl Reply, Rejection> {
+    let body = PublishOfferBody::deserialize(&body)
+        .map_err(anyhow::Error::new)
+        .map_err(problem::from_anyhow)
+        .map_err(warp::reject::custom)?;
+
+    let id = OfferId::default();
+    facade
+        .publish_offer(OwnOffer {
+            offer: Offer {
+                id,
+                ether_per_bitcoin: body.ether_per_bitcoin,
+                min_lightning_amount: body.min_amount.as_sat(),
+                max_lightning_amount: body.max_amount.as_sat(),
+                ethereum_expiry_offset: body.ethereum_expiry_offset,
+                lightning_cltv_expiry: body.lightning_cltv_expiry,
+            },
+            ethereum_identity: body.ethereum_identity,
+            lightning_identity: body.lightning_identity,
+        })
+        .await;
+
+    Ok(warp::reply::with_status(
+        warp::reply::with_header(warp::reply::reply(), "Location", format!("/offers/{}", id)),
+        StatusCode::CREATED,
+    ))
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub async fn get_offers(facade: Facade) -> Result<impl Reply, Rejection> {
+    let offers = facade
+        .offers()
+        .await
+        .into_iter()
+        .map(OfferResource::from)
+        .collect();
+
+    Ok(warp::reply::json(&OffersResource { offers }))
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub async fn post_fetch_offers(
+    body: serde_json::Value,
+    facade: Facade,
+) -> Result<impl Reply, Rejection> {
+    let body = FetchOffersBody::deserialize(&body)
+        .map_err(anyhow::Error::new)
+        .map_err(problem::from_anyhow)
+        .map_err(warp::reject::custom)?;
+
+    facade.fetch_offers(body.peer).await;
+
+    Ok(warp::reply::with_status(
+        warp::reply::reply(),
+        StatusCode::ACCEPTED,
+    ))
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub async fn post_take_offer(
+    offer_id: OfferId,
+    body: serde_json::Value,
+    facade: Facade,
+) -> Result<impl Reply, Rejection> {
+    let body = TakeOfferBody::deserialize(&body)
+        .map_err(anyhow::Error::new)
+        .map_err(problem::from_anyhow)
+        .map_err(warp::reject::custom)?;
+
+    let id = LocalSwapId::default();
+    let swap_params = facade
+        .take_offer(
+            id,
+            offer_id,
+            body.amount.0,
+            body.ethereum_identity,
+            body.lightning_identity,
+        )
+        .await
+        .map_err(problem::from_anyhow)
+        .map_err(into_rejection)?;
+
+    facade
+        .save(id, swap_params.clone())
+        .await
+        .map_err(problem::from_anyhow)
+        .map_err(into_rejection)?;
+
+    facade
+        .initiate_communication(id, swap_params)
+        .await
+        .map(|_| {
+            warp::reply::with_status(
+                warp::reply::with_header(
+                    warp::reply::reply(),
+                    "Location",
+                    format!("/swaps/{}", id),
+                ),
+                StatusCode::CREATED,
+            )
+        })
+        .map_err(problem::from_anyhow)
+        .map_err(into_rejection)
+}
@@ -9,12 +9,15 @@ use crate::{
         route_factory::swap_path,
         routes::{
             into_rejection,
-            rfc003::handlers::{handle_action, handle_get_swap, handle_post_swap},
+            rfc003::handlers::{
+                handle_action, handle_execute_action, handle_get_swap, handle_post_swap,
+                GetSwapsQuery,
+            },
         },
     },
     swap_protocols::{
         rfc003::{actions::ActionKind, SwapId},
-        Rfc003Facade,
+        Facade, Rfc003Facade,
     },
 };
 use warp::{
@@ -52,8 +55,13 @@ pub async fn get_swap(dependencies: Rfc003Facade, id: SwapId) -> Result<impl Rep
 }
 
 #[allow(clippy::needless_pass_by_value)]
-pub async fn get_swaps(dependencies: Rfc003Facade) -> Result<impl Reply, Rejection> {
-    handlers::handle_get_swaps(dependencies)
+pub async fn get_swaps(
+    dependencies: Rfc003Facade,
+    facade: Facade,
+    query: GetSwapsQuery,
+    raw_query: String,
+) -> Result<impl Reply, Rejection> {
+    handlers::handle_get_swaps(dependencies, facade, query, raw_query)
         .await
         .map(|swaps| {
             Ok(warp::reply::with_header(
@@ -81,3 +89,17 @@ pub async fn action(
         .map_err(problem::from_anyhow)
         .map_err(into_rejection)
 }
+
+#[allow(clippy::needless_pass_by_value)]
+pub async fn execute_action(
+    id: SwapId,
+    action_kind: ActionKind,
+    query_params: ActionExecutionParameters,
+    dependencies: Rfc003Facade,
+) -> Result<impl Reply, Rejection> {
+    handle_execute_action(id, action_kind, query_params, dependencies)
+        .await
+        .map(|executed| warp::reply::json(&executed))
+        .map_err(problem::from_anyhow)
+        .map_err(into_rejection)
+}
@@ -4,8 +4,11 @@ mod get_swaps;
 pub mod post_swap;
 
 pub use self::{
-    action::{handle_action, InvalidAction, InvalidActionInvocation},
+    action::{
+        handle_action, handle_execute_action, ActionNotExecutable, ExecutedAction, InvalidAction,
+        InvalidActionInvocation,
+    },
     get_swap::handle_get_swap,
-    get_swaps::handle_get_swaps,
+    get_swaps::{handle_get_swaps, GetSwapsQuery},
     post_swap::handle_post_swap,
 };
@@ -1,14 +1,16 @@
 use crate::{
+    btsieve::bitcoin::{BroadcastSignedTransaction, TransactionRejected},
     db::{DetermineTypes, LoadAcceptedSwap, Save},
     http_api::{
         action::{
             rfc003::ToSirenAction, ActionExecutionParameters, ActionResponseBody,
-            IntoResponsePayload, ListRequiredFields,
+            ConfirmationTarget, IntoResponsePayload, ListRequiredFields,
         },
         route_factory::new_action_link,
         routes::rfc003::decline::{to_swap_decline_reason, DeclineBody},
     },
     init_swap::init_accepted_swap,
+    jsonrpc,
     libp2p_comit_ext::ToHeader,
     network::PendingRequestFor,
     seed::Rfc003DeriveSwapSeed,
@@ -23,6 +25,7 @@ use crate::{
         },
         Rfc003Facade,
     },
+    timestamp::Timestamp,
 };
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
@@ -157,16 +160,79 @@ pub async fn handle_action(
             }
             Action::Redeem(action) => {
                 tracing::trace!("received redeem action");
+                let query_params = query_params
+                    .with_suggested_fee(&action, dependencies.bitcoin_connector.as_ref())
+                    .await;
                 action.into_response_payload(query_params)
             }
             Action::Refund(action) => {
                 tracing::trace!("received refund action");
+                let query_params = query_params
+                    .with_suggested_fee(&action, dependencies.bitcoin_connector.as_ref())
+                    .await;
                 action.into_response_payload(query_params)
             }
         }
     })
 }
 
+/// Executes the given action by broadcasting the signed transaction it
+/// produces instead of handing it to the client.
+pub async fn handle_execute_action(
+    swap_id: SwapId,
+    action_kind: ActionKind,
+    query_params: ActionExecutionParameters,
+    dependencies: Rfc003Facade,
+) -> anyhow::Result<ExecutedAction> {
+    match action_kind {
+        ActionKind::Redeem | ActionKind::Refund => {}
+        _ => return Err(anyhow::Error::from(ActionNotExecutable { action_kind })),
+    }
+
+    let body = handle_action(
+        http::Method::from(action_kind),
+        swap_id,
+        action_kind,
+        serde_json::Value::Null,
+        query_params,
+        dependencies.clone(),
+    )
+    .await?;
+
+    let hex = match body {
+        ActionResponseBody::BitcoinBroadcastSignedTransaction { hex, .. } => hex,
+        _ => return Err(anyhow::Error::from(ActionNotExecutable { action_kind })),
+    };
+    let transaction = bitcoin::consensus::encode::deserialize(&hex::decode(hex)?)?;
+
+    let txid = dependencies
+        .bitcoin_connector
+        .broadcast_signed_transaction(transaction)
+        .await
+        .map_err(|e| match e.downcast::<jsonrpc::Error>() {
+            Ok(jsonrpc::Error::JsonRpc { message, .. }) => {
+                anyhow::Error::from(TransactionRejected { message })
+            }
+            Ok(e) => anyhow::Error::from(e),
+            Err(e) => e,
+        })?;
+
+    tracing::info!("executed {} action of swap {}", action_kind, swap_id);
+
+    Ok(ExecutedAction { txid })
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct ExecutedAction {
+    txid: bitcoin::Txid,
+}
+
+#[derive(Debug, Clone, Copy, thiserror::Error, PartialEq)]
+#[error("action {action_kind} cannot be executed by cnd")]
+pub struct ActionNotExecutable {
+    action_kind: ActionKind,
+}
+
 #[derive(Debug, thiserror::Error, PartialEq)]
 #[error("attempt to invoke {action_kind} action with http method {method}, which is an invalid combination")]
 pub struct InvalidActionInvocation {
@@ -420,6 +486,25 @@ where
     }
 }
 
+impl<Accept, Decline, Deploy, Fund, Redeem, Refund> ConfirmationTarget
+    for Action<Accept, Decline, Deploy, Fund, Redeem, Refund>
+where
+    Deploy: ConfirmationTarget,
+    Fund: ConfirmationTarget,
+    Redeem: ConfirmationTarget,
+    Refund: ConfirmationTarget,
+{
+    fn confirmation_target(&self, now: Timestamp) -> Option<u16> {
+        match self {
+            Action::Deploy(payload) => payload.confirmation_target(now),
+            Action::Fund(payload) => payload.confirmation_target(now),
+            Action::Redeem(payload) => payload.confirmation_target(now),
+            Action::Refund(payload) => payload.confirmation_target(now),
+            Action::Accept(_) | Action::Decline(_) => None,
+        }
+    }
+}
+
 impl<Accept, Decline, Deploy, Fund, Redeem, Refund> ToSirenAction
     for Action<Accept, Decline, Deploy, Fund, Redeem, Refund>
 where
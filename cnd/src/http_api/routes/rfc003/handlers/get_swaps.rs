@@ -1,25 +1,378 @@
 use crate::{
-    db::{DetermineTypes, Retrieve},
-    http_api::swap_resource::{build_rfc003_siren_entity, IncludeState, OnFail},
-    swap_protocols::Rfc003Facade,
+    db::{
+        AssetKind, HanEtherereumHalightBitcoinSwap, LedgerKind, LoadSwapsPage, PageOrder,
+        PagePosition, Swap, SwapTypes, SwapsPageQuery,
+    },
+    http_api::{
+        routes::{
+            build_han_ethereum_halight_bitcoin_siren_entity,
+            han_ethereum_halight_bitcoin_swap_status,
+        },
+        swap_resource::{
+            build_rfc003_siren_entity, rfc003_swap_status, IncludeState, OnFail, SuggestFees,
+        },
+        Http, SwapStatus, PATH,
+    },
+    swap_protocols::{Facade, Rfc003Facade, Role},
 };
+use chrono::{DateTime, NaiveDateTime, Utc};
+use http_api_problem::HttpApiProblem;
+use libp2p::PeerId;
+use serde::Deserialize;
+use std::{cmp::Ordering, convert::TryFrom};
+use uuid::Uuid;
+use warp::http::StatusCode;
+
+/// The number of swaps returned if the client does not ask for a page size.
+const DEFAULT_LIMIT: usize = 100;
+
+/// The most swaps returned on a page, whatever the client asks for. This also
+/// keeps the swaps we look up at once below the number of parameters SQLite
+/// allows in a query.
+const MAX_LIMIT: usize = 500;
+
+/// The query parameters of `GET /swaps`, all of them are optional.
+///
+/// A swap is returned if it matches all of the given filters. `ledger` and
+/// `asset` match either side of a swap.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct GetSwapsQuery {
+    pub role: Option<Http<Role>>,
+    pub protocol: Option<Protocol>,
+    pub ledger: Option<Ledger>,
+    pub asset: Option<Asset>,
+    pub status: Option<SwapStatus>,
+    pub counterparty: Option<Http<PeerId>>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub order: Order,
+    pub limit: Option<usize>,
+    /// The id of the last swap of the previous page.
+    pub cursor: Option<Uuid>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Protocol {
+    Rfc003,
+    HanHalight,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Ledger {
+    Bitcoin,
+    Ethereum,
+    Lightning,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Asset {
+    Bitcoin,
+    Ether,
+    Erc20,
+}
+
+/// The order of the swaps by the time they were created.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+impl Default for Order {
+    fn default() -> Self {
+        Order::Asc
+    }
+}
+
+/// A swap of any of the protocols we support, as loaded from the database.
+enum ListedSwap {
+    Rfc003(Swap, SwapTypes),
+    HanEthereumHalightBitcoin(Box<HanEtherereumHalightBitcoinSwap>),
+}
+
+impl ListedSwap {
+    fn id(&self) -> Uuid {
+        match self {
+            ListedSwap::Rfc003(swap, _) => swap.swap_id.0,
+            ListedSwap::HanEthereumHalightBitcoin(swap) => swap.0.swap_id.into(),
+        }
+    }
+
+    fn created_at(&self) -> NaiveDateTime {
+        match self {
+            ListedSwap::Rfc003(swap, _) => swap.created_at,
+            ListedSwap::HanEthereumHalightBitcoin(swap) => swap.1,
+        }
+    }
+
+    fn position(&self) -> PagePosition {
+        PagePosition {
+            created_at: self.created_at(),
+            id: self.id(),
+        }
+    }
+}
+
+impl GetSwapsQuery {
+    /// The filters the database takes care of.
+    fn page_query(&self, after: Option<PagePosition>, limit: usize) -> SwapsPageQuery {
+        SwapsPageQuery {
+            role: self.role.as_ref().map(|role| role.0),
+            counterparty: self
+                .counterparty
+                .as_ref()
+                .map(|counterparty| counterparty.0.clone()),
+            created_after: self.created_after.map(|after| after.naive_utc()),
+            created_before: self.created_before.map(|before| before.naive_utc()),
+            order: match self.order {
+                Order::Asc => PageOrder::Asc,
+                Order::Desc => PageOrder::Desc,
+            },
+            after,
+            limit: i64::try_from(limit).expect("the limit is clamped"),
+        }
+    }
+
+    fn lists_rfc003(&self) -> bool {
+        self.protocol
+            .map_or(true, |protocol| protocol == Protocol::Rfc003)
+    }
+
+    fn lists_han_ethereum_halight_bitcoin(&self) -> bool {
+        self.protocol
+            .map_or(true, |protocol| protocol == Protocol::HanHalight)
+            && self.matches_ledgers_and_assets([Ledger::Ethereum, Ledger::Lightning], [
+                Asset::Ether,
+                Asset::Bitcoin,
+            ])
+    }
+
+    fn matches_types(&self, types: &SwapTypes) -> bool {
+        let ledger = |kind: LedgerKind| match kind {
+            LedgerKind::Bitcoin(_) => Ledger::Bitcoin,
+            LedgerKind::Ethereum => Ledger::Ethereum,
+        };
+        let asset = |kind: AssetKind| match kind {
+            AssetKind::Bitcoin => Asset::Bitcoin,
+            AssetKind::Ether => Asset::Ether,
+            AssetKind::Erc20 => Asset::Erc20,
+        };
+
+        self.matches_ledgers_and_assets([ledger(types.alpha_ledger), ledger(types.beta_ledger)], [
+            asset(types.alpha_asset),
+            asset(types.beta_asset),
+        ])
+    }
+
+    fn matches_ledgers_and_assets(&self, ledgers: [Ledger; 2], assets: [Asset; 2]) -> bool {
+        self.ledger.map_or(true, |wanted| ledgers.contains(&wanted))
+            && self.asset.map_or(true, |wanted| assets.contains(&wanted))
+    }
+
+    fn compare(&self, left: &ListedSwap, right: &ListedSwap) -> Ordering {
+        // The id breaks ties, so the order is the same for every page.
+        let ordering = left
+            .created_at()
+            .cmp(&right.created_at())
+            .then_with(|| left.id().cmp(&right.id()));
+
+        match self.order {
+            Order::Asc => ordering,
+            Order::Desc => ordering.reverse(),
+        }
+    }
+}
+
+/// Lists the swaps of all protocols matching the query, a page at a time.
+///
+/// The database orders the swaps and applies the filters it can, we load
+/// them in batches of a page until the remaining filters let a page worth of
+/// them through. Only the swaps on the requested page are built into
+/// entities.
+pub async fn handle_get_swaps(
+    rfc003_facade: Rfc003Facade,
+    facade: Facade,
+    query: GetSwapsQuery,
+    raw_query: String,
+) -> anyhow::Result<siren::Entity> {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    // One more swap than fits on the page tells us whether there is a next one.
+    let batch_size = limit + 1;
+
+    // The cursor may not match the filters, hence we look for it among all
+    // swaps.
+    let mut after =
+        match query.cursor {
+            Some(cursor) => Some(rfc003_facade.db.page_position(cursor).await?.ok_or_else(
+                || {
+                    anyhow::anyhow!(HttpApiProblem::new("Invalid cursor.")
+                        .set_status(StatusCode::BAD_REQUEST)
+                        .set_detail(format!("{} is not a known swap.", cursor)))
+                },
+            )?),
+            None => None,
+        };
+
+    let mut page = Vec::new();
+    let mut has_next_page = false;
+
+    'batches: loop {
+        let page_query = query.page_query(after, batch_size);
+
+        let rfc003_swaps = if query.lists_rfc003() {
+            rfc003_facade.db.load_rfc003_swaps_page(&page_query).await?
+        } else {
+            Vec::new()
+        };
+        let han_swaps = if query.lists_han_ethereum_halight_bitcoin() {
+            facade
+                .db
+                .load_han_etherereum_halight_bitcoin_swaps_page(&page_query)
+                .await?
+        } else {
+            Vec::new()
+        };
+        let exhausted = rfc003_swaps.len() < batch_size && han_swaps.len() < batch_size;
+
+        let mut batch = rfc003_swaps
+            .into_iter()
+            .map(|(swap, types)| ListedSwap::Rfc003(swap, types))
+            .chain(
+                han_swaps
+                    .into_iter()
+                    .map(|swap| ListedSwap::HanEthereumHalightBitcoin(Box::new(swap))),
+            )
+            .collect::<Vec<_>>();
+        batch.sort_by(|left, right| query.compare(left, right));
+        // Of a protocol with more swaps left, we only got a batch worth. The
+        // other protocol's swaps after that come in the next batch.
+        if !exhausted {
+            batch.truncate(batch_size);
+        }
+        after = batch.last().map(ListedSwap::position).or(after);
+
+        for swap in batch {
+            if let ListedSwap::Rfc003(_, types) = &swap {
+                if !query.matches_types(types) {
+                    continue;
+                }
+            }
+
+            if let Some(status) = query.status {
+                let actual = match &swap {
+                    ListedSwap::Rfc003(swap, types) => {
+                        rfc003_swap_status(&rfc003_facade, swap.swap_id, *types).await?
+                    }
+                    ListedSwap::HanEthereumHalightBitcoin(swap) => {
+                        han_ethereum_halight_bitcoin_swap_status(&facade, swap.0.swap_id).await?
+                    }
+                };
+                if actual != status {
+                    continue;
+                }
+            }
+
+            if page.len() == limit {
+                has_next_page = true;
+                break 'batches;
+            }
+            page.push(swap);
+        }
+
+        if exhausted {
+            break;
+        }
+    }
 
-pub async fn handle_get_swaps(dependencies: Rfc003Facade) -> anyhow::Result<siren::Entity> {
     let mut entity = siren::Entity::default().with_class_member("swaps");
 
-    for swap in Retrieve::all(&dependencies).await?.into_iter() {
-        let types = dependencies.determine_types(&swap.swap_id).await?;
+    if has_next_page {
+        if let Some(last) = page.last() {
+            entity = entity.with_link(siren::NavigationalLink::new(
+                &["next"],
+                next_page_link(&raw_query, last.id()),
+            ));
+        }
+    }
 
-        let sub_entity = build_rfc003_siren_entity(
-            &dependencies,
-            swap,
-            types,
-            IncludeState::No,
-            OnFail::NoAction,
-        )
-        .await?;
+    for swap in page {
+        let sub_entity = match swap {
+            ListedSwap::Rfc003(swap, types) => {
+                build_rfc003_siren_entity(
+                    &rfc003_facade,
+                    swap,
+                    types,
+                    IncludeState::Yes,
+                    SuggestFees::No,
+                    OnFail::NoAction,
+                )
+                .await?
+            }
+            ListedSwap::HanEthereumHalightBitcoin(swap) => {
+                build_han_ethereum_halight_bitcoin_siren_entity(&facade, *swap).await?
+            }
+        };
         entity.push_sub_entity(siren::SubEntity::from_entity(sub_entity, &["item"]));
     }
 
     Ok(entity)
 }
+
+/// The link to the page after the one ending with the given swap.
+fn next_page_link(raw_query: &str, last: Uuid) -> String {
+    let mut query = url::form_urlencoded::Serializer::new(String::new());
+    for (key, value) in url::form_urlencoded::parse(raw_query.as_bytes()) {
+        if key != "cursor" {
+            query.append_pair(&key, &value);
+        }
+    }
+    query.append_pair("cursor", &last.to_string());
+
+    format!("/{}?{}", PATH, query.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_page_link_replaces_the_cursor() {
+        let last = Uuid::new_v4();
+
+        let link = next_page_link(
+            "role=Alice&cursor=ad2652ca-ecf2-4cc6-b35c-b4351ac28a34",
+            last,
+        );
+
+        assert_eq!(link, format!("/swaps?role=Alice&cursor={}", last));
+    }
+
+    #[test]
+    fn query_deserializes_from_url_parameters() {
+        let query = serde_urlencoded::from_str::<GetSwapsQuery>(
+            "status=IN_PROGRESS&ledger=bitcoin&order=desc&limit=10&created_after=2020-05-01T00:00:00Z",
+        )
+        .unwrap();
+
+        assert_eq!(query.status, Some(SwapStatus::InProgress));
+        assert_eq!(query.ledger, Some(Ledger::Bitcoin));
+        assert_eq!(query.order, Order::Desc);
+        assert_eq!(query.limit, Some(10));
+        assert!(query.created_after.is_some());
+    }
+
+    #[test]
+    fn protocol_deserializes_from_its_name() {
+        let query =
+            serde_urlencoded::from_str::<GetSwapsQuery>("protocol=han-halight&ledger=lightning")
+                .unwrap();
+
+        assert_eq!(query.protocol, Some(Protocol::HanHalight));
+        assert_eq!(query.ledger, Some(Ledger::Lightning));
+    }
+}
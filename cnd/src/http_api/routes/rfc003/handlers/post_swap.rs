@@ -1,4 +1,5 @@
 use crate::{
+    comit_api::LedgerKind,
     db::{LoadAcceptedSwap, Save, Sqlite, Swap},
     htlc_location,
     http_api::{HttpAsset, HttpLedger},
@@ -9,7 +10,10 @@ use crate::{
     swap_protocols::{
         rfc003::{
             self,
-            events::{HtlcDeployed, HtlcFunded, HtlcRedeemed, HtlcRefunded},
+            events::{
+                HtlcDeployed, HtlcFunded, HtlcRedeemed, HtlcRefunded, TransactionProcessed,
+                TransactionRetracted,
+            },
             state::Insert,
             Accept, Decline, DeriveIdentities, DeriveSecret, LedgerState, Request, SecretHash,
             SwapCommunication, SwapId,
@@ -56,10 +60,25 @@ where
         + HtlcRedeemed<AL, AA, AH, AI, AT>
         + HtlcRedeemed<BL, BA, BH, BI, BT>
         + HtlcRefunded<AL, AA, AH, AI, AT>
-        + HtlcRefunded<BL, BA, BH, BI, BT>,
+        + HtlcRefunded<BL, BA, BH, BI, BT>
+        + TransactionRetracted<AT>
+        + TransactionRetracted<BT>
+        + TransactionProcessed<AT>
+        + TransactionProcessed<BT>,
+    LedgerKind: From<AL> + From<BL>,
 {
     tracing::trace!("initiating new request: {}", swap_request.swap_id);
 
+    dependencies
+        .expiry_validator
+        .validate_rfc003(
+            swap_request.alpha_ledger.clone().into(),
+            swap_request.alpha_expiry,
+            swap_request.beta_ledger.clone().into(),
+            swap_request.beta_expiry,
+        )
+        .await?;
+
     let counterparty = peer.peer_id.clone();
 
     Save::save(&dependencies, Swap::new(id, Role::Alice, counterparty)).await?;
@@ -136,7 +136,14 @@ where
                 redeem_tx: None,
                 refund_tx: None,
             },
-            Redeemed {
+            RedeemPending {
+                htlc_location,
+                deploy_transaction,
+                fund_transaction,
+                redeem_transaction,
+                ..
+            }
+            | Redeemed {
                 htlc_location,
                 deploy_transaction,
                 fund_transaction,
@@ -3,7 +3,7 @@
 use crate::{
     db::{Swap, SwapTypes},
     http_api::{
-        action::rfc003::ToSirenAction,
+        action::{rfc003::ToSirenAction, FeeEstimates},
         route_factory::swap_path,
         routes::rfc003::{LedgerState, SwapCommunication, SwapState},
         Http, HttpAsset, HttpLedger,
@@ -18,7 +18,7 @@ use crate::{
 use anyhow::anyhow;
 use http_api_problem::HttpApiProblem;
 use libp2p::PeerId;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use warp::http::StatusCode;
 
 #[derive(Debug, Serialize)]
@@ -41,7 +41,7 @@ pub struct SwapParameters {
     beta_asset: HttpAsset,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum SwapStatus {
     InProgress,
@@ -72,6 +72,13 @@ pub enum IncludeState {
     No,
 }
 
+/// Whether to fill in the fee rates we suggest for the actions. Estimating
+/// them is too expensive to do for every swap of a list.
+pub enum SuggestFees {
+    Yes,
+    No,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum OnFail {
     Error,
@@ -86,6 +93,7 @@ pub async fn build_rfc003_siren_entity(
     swap: Swap,
     types: SwapTypes,
     include_state: IncludeState,
+    suggest_fees: SuggestFees,
     on_fail: OnFail,
 ) -> anyhow::Result<siren::Entity> {
     let id = swap.swap_id;
@@ -175,11 +183,68 @@ pub async fn build_rfc003_siren_entity(
             return Ok(entity);
         }
 
-        let entity = actions.into_iter().fold(entity, |acc, action| {
-            let action = action.to_siren_action(&id);
-            acc.with_action(action)
-        });
+        let mut fee_estimates = match suggest_fees {
+            SuggestFees::Yes => Some(FeeEstimates::new(dependencies.bitcoin_connector.as_ref())),
+            SuggestFees::No => None,
+        };
+
+        let mut entity = entity;
+        for action in actions {
+            let mut siren_action = action.to_siren_action(&id);
+
+            let fee_per_wu = match fee_estimates.as_mut() {
+                Some(fee_estimates) => fee_estimates.suggested_fee_per_wu(&action).await,
+                None => None,
+            };
+            if let Some(fee_per_wu) = fee_per_wu {
+                for field in siren_action
+                    .fields
+                    .iter_mut()
+                    .filter(|field| field.name == "fee_per_wu")
+                {
+                    field.value = Some(fee_per_wu.into());
+                }
+            }
+
+            entity = entity.with_action(siren_action);
+        }
 
         Ok(entity)
     })
 }
+
+/// The status of a swap without building its resource, `InternalFailure` if
+/// watching the swap failed.
+#[allow(clippy::cognitive_complexity)]
+pub async fn rfc003_swap_status(
+    dependencies: &Rfc003Facade,
+    id: SwapId,
+    types: SwapTypes,
+) -> anyhow::Result<SwapStatus> {
+    if dependencies.swap_error_states.has_failed(&id).await {
+        return Ok(SwapStatus::InternalFailure);
+    }
+
+    with_swap_types!(types, {
+        let swap_communication: rfc003::SwapCommunication<AL, BL, AA, BA, AI, BI> = dependencies
+            .get(&id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("swap communication state not found for {}", id))?;
+        let alpha_ledger_state: rfc003::LedgerState<AA, AH, AT> = dependencies
+            .alpha_ledger_states
+            .get(&id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("alpha ledger state not found for {}", id))?;
+        let beta_ledger_state: rfc003::LedgerState<BA, BH, BT> = dependencies
+            .beta_ledger_states
+            .get(&id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("beta ledger state not found for {}", id))?;
+
+        Ok(SwapStatus::new(
+            SwapCommunication::from(swap_communication).status,
+            LedgerState::from(alpha_ledger_state).status,
+            LedgerState::from(beta_ledger_state).status,
+        ))
+    })
+}
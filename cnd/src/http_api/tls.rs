@@ -0,0 +1,209 @@
+//! Serving the HTTP API over TLS, so clients on other machines can talk to
+//! cnd without a reverse proxy in front of it.
+//!
+//! Like lnd, we generate a self-signed certificate into the data directory
+//! if none is configured.
+
+use crate::{config::Tls, http_api::generated_files};
+use anyhow::Context;
+use futures::{stream, Stream};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::{mpsc, Semaphore},
+};
+pub use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::{
+    rustls::{internal::pemfile, Certificate, NoClientAuth, PrivateKey},
+    server::TlsStream,
+    TlsAcceptor,
+};
+
+/// How long we wait before accepting connections again after it failed, e.g.
+/// because we ran out of file descriptors.
+const ACCEPT_ERROR_DELAY: Duration = Duration::from_secs(1);
+
+/// How long a client has to complete the TLS handshake before we drop the
+/// connection.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How many handshakes we run at once. Further connections wait in the
+/// listen backlog until one of them completes.
+const MAX_CONCURRENT_HANDSHAKES: usize = 64;
+
+pub fn server_config(tls: &Tls, data_dir: &Path) -> anyhow::Result<ServerConfig> {
+    let (cert, key) = match (&tls.cert, &tls.key) {
+        (Some(cert), Some(key)) => (cert.clone(), key.clone()),
+        (None, None) => generated_certificate(data_dir, &tls.extra_domains)?,
+        _ => anyhow::bail!("either both or none of the TLS certificate and key must be configured"),
+    };
+
+    let certs = read_certs(&cert)?;
+    let key = read_key(&key)?;
+
+    let mut config = ServerConfig::new(NoClientAuth::new());
+    config
+        .set_single_cert(certs, key)
+        .with_context(|| format!("invalid TLS certificate {}", cert.display()))?;
+
+    Ok(config)
+}
+
+/// Accepts connections on the listener and yields them once the TLS
+/// handshake completed.
+///
+/// Handshakes run in the background, so a slow client does not hold up the
+/// others. They are limited in number and time, so clients that never
+/// complete them cannot exhaust our resources. Failed ones are only logged
+/// because an error would stop the server.
+pub fn incoming(
+    mut listener: TcpListener,
+    config: ServerConfig,
+) -> impl Stream<Item = Result<TlsStream<TcpStream>, io::Error>> {
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+    let (sender, receiver) = mpsc::unbounded_channel::<Result<_, io::Error>>();
+
+    let handshakes = Arc::new(Semaphore::new(MAX_CONCURRENT_HANDSHAKES));
+
+    tokio::spawn(async move {
+        loop {
+            // The permit borrows the semaphore, hence we hand it back
+            // ourselves once the handshake is done.
+            handshakes.acquire().await.forget();
+
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    handshakes.add_permits(1);
+                    tracing::warn!("failed to accept connection: {}", e);
+                    tokio::time::delay_for(ACCEPT_ERROR_DELAY).await;
+                    continue;
+                }
+            };
+
+            let acceptor = acceptor.clone();
+            let sender = sender.clone();
+            let handshakes = handshakes.clone();
+            tokio::spawn(async move {
+                match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                    Ok(Ok(stream)) => {
+                        let _ = sender.send(Ok(stream));
+                    }
+                    Ok(Err(e)) => tracing::debug!("TLS handshake with {} failed: {}", peer, e),
+                    Err(_) => tracing::debug!("TLS handshake with {} timed out", peer),
+                }
+                handshakes.add_permits(1);
+            });
+        }
+    });
+
+    stream::unfold(receiver, |mut receiver| async move {
+        let stream = receiver.recv().await?;
+
+        Some((stream, receiver))
+    })
+}
+
+/// Generates the certificate on first use, later uses return the existing
+/// one. Delete it to have it generated again, e.g. for other domains.
+///
+/// The key is written before the certificate, hence a key without a
+/// certificate is left over from an interrupted generation and replaced.
+fn generated_certificate(
+    data_dir: &Path,
+    extra_domains: &[String],
+) -> anyhow::Result<(PathBuf, PathBuf)> {
+    let dir = generated_files::dir(data_dir);
+    let cert = dir.join("tls.cert");
+    let key = dir.join("tls.key");
+
+    if cert.exists() && key.exists() {
+        return Ok((cert, key));
+    }
+
+    let mut domains = vec!["localhost".to_owned()];
+    domains.extend_from_slice(extra_domains);
+
+    let certificate = rcgen::generate_simple_self_signed(domains)?;
+    generated_files::write_private(&key, certificate.serialize_private_key_pem().as_bytes())?;
+    generated_files::write_public(&cert, certificate.serialize_pem()?.as_bytes())?;
+
+    tracing::info!(
+        "No TLS certificate found, creating self-signed one at: {}",
+        cert.display()
+    );
+
+    Ok((cert, key))
+}
+
+fn read_certs(path: &Path) -> anyhow::Result<Vec<Certificate>> {
+    let contents = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let certs = pemfile::certs(&mut contents.as_slice())
+        .map_err(|()| anyhow::anyhow!("{} is not a PEM encoded certificate", path.display()))?;
+
+    if certs.is_empty() {
+        anyhow::bail!("{} does not contain a certificate", path.display())
+    }
+
+    Ok(certs)
+}
+
+fn read_key(path: &Path) -> anyhow::Result<PrivateKey> {
+    let contents = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let invalid = |()| anyhow::anyhow!("{} is not a PEM encoded private key", path.display());
+
+    let pkcs8 = pemfile::pkcs8_private_keys(&mut contents.as_slice()).map_err(invalid)?;
+    let rsa = pemfile::rsa_private_keys(&mut contents.as_slice()).map_err(invalid)?;
+
+    pkcs8
+        .into_iter()
+        .chain(rsa)
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("{} does not contain a private key", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_a_certificate_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let tls = Tls::default();
+
+        server_config(&tls, dir.path()).unwrap();
+        let generated = fs::read(dir.path().join("http_api").join("tls.cert")).unwrap();
+        server_config(&tls, dir.path()).unwrap();
+        let reused = fs::read(dir.path().join("http_api").join("tls.cert")).unwrap();
+
+        assert_eq!(generated, reused);
+    }
+
+    #[test]
+    fn replaces_a_key_without_a_certificate() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = dir.path().join("http_api").join("tls.key");
+        generated_files::write_private(&key, b"left over").unwrap();
+
+        server_config(&Tls::default(), dir.path()).unwrap();
+
+        assert!(dir.path().join("http_api").join("tls.cert").exists());
+        assert_ne!(fs::read(&key).unwrap(), b"left over");
+    }
+
+    #[test]
+    fn refuses_a_certificate_without_a_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let tls = Tls {
+            cert: Some(dir.path().join("tls.cert")),
+            ..Tls::default()
+        };
+
+        assert!(server_config(&tls, dir.path()).is_err());
+    }
+}
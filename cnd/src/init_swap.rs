@@ -1,11 +1,14 @@
 use crate::{
-    db::AcceptedSwap,
+    db::{AcceptedSwap, LedgerSide, LoadLedgerEvents, RecordedEvent},
     swap_protocols::{
         rfc003::{
-            create_swap::{create_watcher, OngoingSwap},
-            events::{HtlcDeployed, HtlcFunded, HtlcRedeemed, HtlcRefunded},
-            state::Insert,
-            Accept, Request, SwapCommunication,
+            create_swap::{create_watcher, OngoingSwap, SwapEvent},
+            events::{
+                HtlcDeployed, HtlcFunded, HtlcRedeemed, HtlcRefunded, TransactionProcessed,
+                TransactionRetracted,
+            },
+            state::{Insert, Update},
+            Accept, LedgerStates, Request, SwapCommunication,
         },
         Rfc003Facade,
     },
@@ -25,7 +28,15 @@ where
         + HtlcRedeemed<AL, AA, AH, AI, AT>
         + HtlcRedeemed<BL, BA, BH, BI, BT>
         + HtlcRefunded<AL, AA, AH, AI, AT>
-        + HtlcRefunded<BL, BA, BH, BI, BT>,
+        + HtlcRefunded<BL, BA, BH, BI, BT>
+        + TransactionRetracted<AT>
+        + TransactionRetracted<BT>
+        + TransactionProcessed<AT>
+        + TransactionProcessed<BT>
+        + LoadLedgerEvents<SwapEvent<AA, AH, AT>>
+        + LoadLedgerEvents<SwapEvent<BA, BH, BT>>,
+    LedgerStates:
+        Update<RecordedEvent<SwapEvent<AA, AH, AT>>> + Update<RecordedEvent<SwapEvent<BA, BH, BT>>>,
     AL: Clone + Send + Sync + 'static,
     BL: Clone + Send + Sync + 'static,
     AA: Ord + Clone + Send + Sync + 'static,
@@ -51,6 +62,19 @@ where
 
     let swap = OngoingSwap::new(request, accept);
 
+    let alpha_events = LoadLedgerEvents::<SwapEvent<AA, AH, AT>>::load_ledger_events(
+        dependencies,
+        &id,
+        LedgerSide::Alpha,
+    )
+    .await?;
+    let beta_events = LoadLedgerEvents::<SwapEvent<BA, BH, BT>>::load_ledger_events(
+        dependencies,
+        &id,
+        LedgerSide::Beta,
+    )
+    .await?;
+
     tracing::trace!("initialising accepted swap: {}", id);
 
     tokio::task::spawn(
@@ -60,6 +84,7 @@ where
             id,
             swap.alpha_htlc_params(),
             accepted_at,
+            alpha_events,
         )
         .instrument(tracing::info_span!("alpha")),
     );
@@ -71,6 +96,7 @@ where
             id,
             swap.beta_htlc_params(),
             accepted_at,
+            beta_events,
         )
         .instrument(tracing::info_span!("beta")),
     );
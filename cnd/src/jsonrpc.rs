@@ -1,3 +1,5 @@
+pub mod ws;
+
 use anyhow::Context;
 use serde::{de::DeserializeOwned, Serialize};
 
@@ -13,6 +15,12 @@ pub enum Error {
     JsonRpc { code: i64, message: String },
     #[error("connection error: {0}")]
     Connection(#[from] reqwest::Error),
+    #[error("websocket error: {0}")]
+    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+    #[error("connection closed before the response arrived")]
+    ConnectionClosed,
+    #[error("invalid json: {0}")]
+    Json(#[from] serde_json::Error),
 }
 
 impl Client {
@@ -28,18 +36,21 @@ impl Client {
         Req: Serialize,
         Res: DeserializeOwned,
     {
-        let response = self
-            .inner
-            .post(self.url.clone())
-            .json(&request)
-            .send()
-            .await?
-            .json::<Response<Res>>()
-            .await?;
+        let mut http_request = self.inner.post(self.url.clone()).json(&request);
+
+        // bitcoind's JSON-RPC interface requires authentication, allow the
+        // credentials to be passed as part of the node url.
+        if !self.url.username().is_empty() {
+            http_request = http_request.basic_auth(self.url.username(), self.url.password());
+        }
+
+        let response = http_request.send().await?.json::<Response<Res>>().await?;
 
         match response {
             Response::Success { result } => Ok(result),
-            Response::Error { code, message } => Err(Error::JsonRpc { code, message }),
+            Response::Error {
+                error: ErrorObject { code, message },
+            } => Err(Error::JsonRpc { code, message }),
         }
     }
 }
@@ -66,8 +77,16 @@ impl<T> Request<T> {
 #[derive(serde::Deserialize, Debug)]
 #[serde(untagged)]
 pub enum Response<T> {
+    // Needs to come first, otherwise an error response would be deserialized
+    // as success if `T` is an `Option`.
+    Error { error: ErrorObject },
     Success { result: T },
-    Error { code: i64, message: String },
+}
+
+#[derive(serde::Deserialize, Debug, PartialEq)]
+pub struct ErrorObject {
+    code: i64,
+    message: String,
 }
 
 pub fn serialize<T>(t: T) -> anyhow::Result<serde_json::Value>
@@ -78,3 +97,48 @@ where
 
     Ok(value)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_success_response() {
+        let response = r#"{"result":"foo","error":null,"id":"1"}"#;
+
+        let response = serde_json::from_str::<Response<String>>(response).unwrap();
+
+        match response {
+            Response::Success { result } => assert_eq!(result, "foo"),
+            Response::Error { .. } => panic!("expected success response"),
+        }
+    }
+
+    #[test]
+    fn deserialize_error_response_for_optional_result() {
+        let response =
+            r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params"},"id":"1"}"#;
+
+        let response = serde_json::from_str::<Response<Option<String>>>(response).unwrap();
+
+        match response {
+            Response::Error { error } => assert_eq!(error.code, -32602),
+            Response::Success { .. } => panic!("expected error response"),
+        }
+    }
+
+    #[test]
+    fn deserialize_error_response() {
+        let response = r#"{"result":null,"error":{"code":-26,"message":"non-final"},"id":"1"}"#;
+
+        let response = serde_json::from_str::<Response<String>>(response).unwrap();
+
+        match response {
+            Response::Error { error } => assert_eq!(error, ErrorObject {
+                code: -26,
+                message: "non-final".to_owned()
+            }),
+            Response::Success { .. } => panic!("expected error response"),
+        }
+    }
+}
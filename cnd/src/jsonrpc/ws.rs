@@ -0,0 +1,286 @@
+//! JSON-RPC over a websocket which, unlike HTTP, lets the node push
+//! notifications to us, e.g. the ones of `eth_subscribe`.
+
+use crate::jsonrpc::{Error, ErrorObject, Request, Response};
+use futures::{
+    stream::{self, BoxStream},
+    Sink, SinkExt, Stream, StreamExt,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio_tungstenite::tungstenite::{self, Message};
+
+/// Multiplexes requests and subscriptions over a single websocket.
+///
+/// The connection is established on the first request and re-established on
+/// the first request after it was lost.
+#[derive(Debug)]
+pub struct Client {
+    url: reqwest::Url,
+    connection: Mutex<Option<mpsc::UnboundedSender<Command>>>,
+}
+
+#[derive(Debug)]
+enum Command {
+    Call {
+        request: Value,
+        response: oneshot::Sender<Value>,
+    },
+    Subscribe {
+        request: Value,
+        notifications: mpsc::UnboundedSender<Value>,
+    },
+}
+
+#[derive(Debug)]
+enum Pending {
+    Call(oneshot::Sender<Value>),
+    Subscribe(mpsc::UnboundedSender<Value>),
+}
+
+impl Client {
+    pub fn new(url: reqwest::Url) -> Self {
+        Self {
+            url,
+            connection: Mutex::new(None),
+        }
+    }
+
+    pub async fn send<Req, Res>(&self, request: Request<Req>) -> Result<Res, Error>
+    where
+        Req: Serialize,
+        Res: DeserializeOwned,
+    {
+        let (sender, receiver) = oneshot::channel();
+        self.command(Command::Call {
+            request: serde_json::to_value(&request)?,
+            response: sender,
+        })
+        .await?;
+
+        let response = receiver.await.map_err(|_| Error::ConnectionClosed)?;
+
+        match serde_json::from_value::<Response<Res>>(response)? {
+            Response::Success { result } => Ok(result),
+            Response::Error {
+                error: ErrorObject { code, message },
+            } => Err(Error::JsonRpc { code, message }),
+        }
+    }
+
+    /// Yields the notifications of a subscription, e.g. `eth_subscribe` for
+    /// `newHeads`.
+    ///
+    /// The stream ends if the node rejects the subscription or the connection
+    /// is lost.
+    pub async fn subscribe<Req>(
+        &self,
+        request: Request<Req>,
+    ) -> Result<BoxStream<'static, Value>, Error>
+    where
+        Req: Serialize,
+    {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.command(Command::Subscribe {
+            request: serde_json::to_value(&request)?,
+            notifications: sender,
+        })
+        .await?;
+
+        let notifications = stream::unfold(receiver, |mut receiver| async move {
+            let notification = receiver.recv().await?;
+
+            Some((notification, receiver))
+        });
+
+        Ok(notifications.boxed())
+    }
+
+    async fn command(&self, mut command: Command) -> Result<(), Error> {
+        let mut connection = self.connection.lock().await;
+
+        if let Some(commands) = connection.as_ref() {
+            match commands.send(command) {
+                Ok(()) => return Ok(()),
+                // The connection was lost, hence we reconnect below.
+                Err(mpsc::error::SendError(returned)) => command = returned,
+            }
+        }
+
+        let (websocket, _) = tokio_tungstenite::connect_async(self.url.as_str()).await?;
+        tracing::debug!("connected to {}", self.url);
+
+        let (commands, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(run(websocket, receiver));
+
+        commands
+            .send(command)
+            .map_err(|_| Error::ConnectionClosed)?;
+        *connection = Some(commands);
+
+        Ok(())
+    }
+}
+
+/// Sends the requests of the client and routes the responses and
+/// notifications back, until either the client or the connection is gone.
+async fn run<S>(mut websocket: S, mut commands: mpsc::UnboundedReceiver<Command>)
+where
+    S: Stream<Item = Result<Message, tungstenite::Error>>
+        + Sink<Message, Error = tungstenite::Error>
+        + Unpin,
+{
+    let mut next_id = 0u64;
+    let mut pending = HashMap::new();
+    let mut subscriptions = HashMap::new();
+
+    loop {
+        tokio::select! {
+            command = commands.recv() => {
+                let (mut request, waiting) = match command {
+                    Some(Command::Call { request, response }) => (request, Pending::Call(response)),
+                    Some(Command::Subscribe { request, notifications }) => {
+                        (request, Pending::Subscribe(notifications))
+                    }
+                    None => return,
+                };
+
+                // Requests are built with a constant id, which is not unique
+                // once they share a connection.
+                next_id += 1;
+                request["id"] = Value::from(next_id);
+                pending.insert(next_id, waiting);
+
+                if let Err(e) = websocket.send(Message::Text(request.to_string())).await {
+                    tracing::warn!("failed to send request over websocket: {}", e);
+                    return;
+                }
+            }
+            message = websocket.next() => {
+                match message {
+                    Some(Ok(Message::Text(text))) => {
+                        dispatch(&text, &mut pending, &mut subscriptions)
+                    }
+                    Some(Ok(Message::Close(_))) | None => {
+                        tracing::debug!("websocket was closed by the node");
+                        return;
+                    }
+                    // Pings are answered by tungstenite.
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => {
+                        tracing::warn!("websocket failed: {}", e);
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn dispatch(
+    text: &str,
+    pending: &mut HashMap<u64, Pending>,
+    subscriptions: &mut HashMap<String, mpsc::UnboundedSender<Value>>,
+) {
+    let message = match serde_json::from_str::<Value>(text) {
+        Ok(message) => message,
+        Err(e) => {
+            tracing::warn!("received invalid JSON over websocket: {}", e);
+            return;
+        }
+    };
+
+    if let Some(id) = message["id"].as_u64() {
+        match pending.remove(&id) {
+            Some(Pending::Call(response)) => {
+                let _ = response.send(message);
+            }
+            Some(Pending::Subscribe(notifications)) => match message["result"].as_str() {
+                Some(subscription) => {
+                    subscriptions.insert(subscription.to_owned(), notifications);
+                }
+                None => tracing::warn!("subscription was rejected: {}", message["error"]),
+            },
+            None => tracing::debug!("received response to unknown request {}", id),
+        }
+
+        return;
+    }
+
+    let params = &message["params"];
+    if let Some(subscription) = params["subscription"].as_str() {
+        let unsubscribed = match subscriptions.get(subscription) {
+            Some(notifications) => notifications.send(params["result"].clone()).is_err(),
+            None => false,
+        };
+
+        if unsubscribed {
+            subscriptions.remove(subscription);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    /// Answers every request with its method and confirms subscriptions with
+    /// a single notification, standing in for parity.
+    async fn stub_node() -> reqwest::Url {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut websocket = tokio_tungstenite::accept_async(stream).await.unwrap();
+
+            while let Some(Ok(Message::Text(text))) = websocket.next().await {
+                let request = serde_json::from_str::<Value>(&text).unwrap();
+                let id = request["id"].clone();
+
+                let replies = if request["method"] == "eth_subscribe" {
+                    vec![
+                        json!({ "jsonrpc": "2.0", "id": id, "result": "0xcafe" }),
+                        json!({
+                            "jsonrpc": "2.0",
+                            "method": "eth_subscription",
+                            "params": { "subscription": "0xcafe", "result": { "number": "0x1" } }
+                        }),
+                    ]
+                } else {
+                    vec![json!({ "jsonrpc": "2.0", "id": id, "result": request["method"] })]
+                };
+
+                for reply in replies {
+                    websocket
+                        .send(Message::Text(reply.to_string()))
+                        .await
+                        .unwrap();
+                }
+            }
+        });
+
+        format!("ws://{}", addr).parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn routes_responses_and_notifications() {
+        let client = Client::new(stub_node().await);
+
+        let method: String = client
+            .send(Request::new("net_version", Vec::<()>::new()))
+            .await
+            .unwrap();
+        let mut heads = client
+            .subscribe(Request::new("eth_subscribe", vec!["newHeads"]))
+            .await
+            .unwrap();
+
+        assert_eq!(method, "net_version");
+        assert_eq!(heads.next().await.unwrap()["number"], "0x1");
+    }
+}
@@ -30,17 +30,20 @@ pub mod libp2p_comit_ext;
 pub mod db;
 
 pub mod asset;
+pub mod autopilot;
 pub mod bitcoin;
 pub mod btsieve;
 pub mod comit_api;
 pub mod config;
 pub mod ethereum;
+pub mod expiries;
 pub mod http_api;
 pub mod init_swap;
 pub mod lightning;
 pub mod load_swaps;
 #[macro_use]
 pub mod network;
+pub mod policy;
 #[cfg(test)]
 pub mod quickcheck;
 #[macro_use]
@@ -1,33 +1,73 @@
 #![allow(clippy::type_repetition_in_bounds)]
 use crate::{
-    db::{DetermineTypes, LoadAcceptedSwap, Retrieve},
+    db::{
+        CreatedSwap, DetermineTypes, LoadAcceptedSwap, LoadHanEtherereumHalightBitcoinSwaps,
+        Retrieve,
+    },
     init_swap::init_accepted_swap,
-    swap_protocols::Rfc003Facade,
+    swap_protocols::{Facade, Rfc003Facade},
 };
 
 #[allow(clippy::cognitive_complexity)]
-pub async fn load_swaps_from_database(facade: Rfc003Facade) -> anyhow::Result<()> {
+pub async fn load_swaps_from_database(
+    rfc003_facade: Rfc003Facade,
+    facade: Facade,
+) -> anyhow::Result<()> {
     tracing::debug!("loading swaps from database ...");
 
-    for swap in Retrieve::all(&facade).await?.iter() {
+    for swap in Retrieve::all(&rfc003_facade).await?.iter() {
         let swap_id = swap.swap_id;
         tracing::debug!("got swap from database: {}", swap_id);
 
-        let types = DetermineTypes::determine_types(&facade, &swap_id).await?;
+        let types = DetermineTypes::determine_types(&rfc003_facade, &swap_id).await?;
 
         with_swap_types!(types, {
-            let accepted =
-                LoadAcceptedSwap::<AL, BL, AA, BA, AI, BI>::load_accepted_swap(&facade, &swap_id)
-                    .await;
+            let accepted = LoadAcceptedSwap::<AL, BL, AA, BA, AI, BI>::load_accepted_swap(
+                &rfc003_facade,
+                &swap_id,
+            )
+            .await;
 
             match accepted {
                 Ok(accepted) => {
-                    init_accepted_swap::<_, _, _, _, AH, BH, _, _, AT, BT>(&facade, accepted)
-                        .await?;
+                    init_accepted_swap::<_, _, _, _, AH, BH, _, _, AT, BT>(
+                        &rfc003_facade,
+                        accepted,
+                    )
+                    .await?;
                 }
                 Err(e) => tracing::error!("failed to load swap: {}, continuing ...", e),
             };
         });
     }
+
+    for (CreatedSwap { swap_id, params }, _, finalized) in facade
+        .db
+        .load_han_etherereum_halight_bitcoin_swaps()
+        .await?
+    {
+        tracing::debug!("got han-ether/halight swap from database: {}", swap_id);
+
+        match finalized {
+            Some((finalized, finalized_at)) => {
+                facade
+                    .swarm
+                    .restore_finalized_swap(params, finalized, finalized_at)
+                    .await;
+            }
+            None => {
+                // The communication with the counterparty did not finish before we were
+                // restarted, start it over again.
+                if let Err(e) = facade.initiate_communication(swap_id, params).await {
+                    tracing::error!(
+                        "failed to resume communication for swap {}: {:?}, continuing ...",
+                        swap_id,
+                        e
+                    );
+                }
+            }
+        }
+    }
+
     Ok(())
 }
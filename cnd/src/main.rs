@@ -15,32 +15,41 @@
 use crate::cli::Options;
 use anyhow::Context;
 use cnd::{
+    autopilot::Autopilot,
     btsieve::{
-        bitcoin::{self, BitcoindConnector},
+        bitcoin::{self, BitcoindConnector, EsploraConnector},
         ethereum::{self, Web3Connector},
     },
     config::{self, validation::validate_blockchain_config, Settings},
-    db::Sqlite,
+    db::{BlockStore, LedgerSide, Sqlite},
+    expiries::ExpiryValidator,
     file_lock::TryLockExclusive,
-    http_api::route_factory,
+    http_api::{auth::Tokens, route_factory, tls},
     jsonrpc, load_swaps,
     network::{Swarm, SwarmWorker},
-    seed::RootSeed,
+    policy::Policy,
+    seed::{self, RootSeed},
     swap_protocols::{
-        halight::States, rfc003, rfc003::SwapCommunicationStates, Facade, LedgerStates,
-        Rfc003Facade, SwapErrorStates,
+        halight::States,
+        rfc003,
+        rfc003::SwapCommunicationStates,
+        state_changes::{StateChanges, Subject},
+        Facade, LedgerStates, Rfc003Facade, SwapErrorStates,
     },
 };
 
 use cnd::swap_protocols::halight::LndConnectorParams;
 use rand::rngs::OsRng;
-use std::{process, sync::Arc};
+use std::{env, fs, io, process, sync::Arc};
 use structopt::StructOpt;
 use tokio::{net::TcpListener, runtime};
 
 mod cli;
 mod trace;
 
+/// The environment variable the passphrase of the seed file is read from.
+const SEED_PASSPHRASE_ENV: &str = "CND_SEED_PASSPHRASE";
+
 fn main() -> anyhow::Result<()> {
     let options = cli::Options::from_args();
 
@@ -58,11 +67,66 @@ fn main() -> anyhow::Result<()> {
 
     crate::trace::init_tracing(settings.logging.level)?;
 
-    let database = Sqlite::new_in_dir(&settings.data.dir)?;
+    // Another cnd using the same data directory would mess with the seed and
+    // the database.
+    fs::create_dir_all(&settings.data.dir)?;
+    let _locked_datadir = &settings.data.dir.try_lock_exclusive()?;
 
-    let seed = RootSeed::from_dir_or_generate(&settings.data.dir, OsRng)?;
+    let seed_passphrase = seed_passphrase(&options)?;
 
-    let _locked_datadir = &settings.data.dir.try_lock_exclusive()?;
+    if options.restore_from_mnemonic {
+        restore_from_mnemonic(&settings, seed_passphrase.as_deref())?;
+        process::exit(0);
+    }
+
+    if options.encrypt_seed {
+        encrypt_seed(&settings, seed_passphrase)?;
+        process::exit(0);
+    }
+
+    // Only asked for if the seed file is encrypted.
+    let passphrase = || -> Result<String, seed::Error> {
+        match seed_passphrase.clone() {
+            Some(passphrase) => Ok(passphrase),
+            None => Ok(rpassword::prompt_password_stderr(
+                "Enter the passphrase of the seed: ",
+            )?),
+        }
+    };
+
+    let (seed, generated) = match RootSeed::from_dir(&settings.data.dir, passphrase)? {
+        Some(seed) => (seed, false),
+        None => {
+            let seed =
+                RootSeed::generate_in_dir(&settings.data.dir, seed_passphrase.as_deref(), OsRng)?;
+            tracing::info!("Generated a new seed in {}", settings.data.dir.display());
+            (seed, true)
+        }
+    };
+
+    // A new seed is backed up right away, later on only if asked for.
+    if generated || options.show_mnemonic {
+        show_mnemonic(&seed);
+    }
+
+    if options.show_mnemonic {
+        process::exit(0);
+    }
+
+    let database = Sqlite::new_in_dir(&settings.data.dir)?;
+
+    let tokens = settings
+        .http_api
+        .auth
+        .as_ref()
+        .map(|auth| Tokens::from_config_or_generate(auth, &settings.data.dir, OsRng))
+        .transpose()?;
+    let tls_config = settings
+        .http_api
+        .tls
+        .as_ref()
+        .map(|tls| tls::server_config(tls, &settings.data.dir))
+        .transpose()?;
 
     let mut runtime = runtime::Builder::new()
         .enable_all()
@@ -70,9 +134,31 @@ fn main() -> anyhow::Result<()> {
         .thread_stack_size(1024 * 1024 * 8) // the default is 2MB but that causes a segfault for some reason
         .build()?;
 
+    let block_store = if settings.block_cache.persistent {
+        Some(BlockStore::new(
+            database.clone(),
+            settings.block_cache.capacity,
+            settings.block_cache.eviction,
+        ))
+    } else {
+        None
+    };
+
     let bitcoin_connector = {
-        let config::Bitcoin { bitcoind, network } = &settings.bitcoin;
-        let connector = BitcoindConnector::new(bitcoind.node_url.clone(), *network)?;
+        let config::Bitcoin { backend, network } = &settings.bitcoin;
+        let connector = match backend {
+            config::BitcoinBackend::Bitcoind(bitcoind) => {
+                let connector = BitcoindConnector::new(bitcoind.node_url.clone(), *network)?;
+
+                bitcoin::Connector::from(match &bitcoind.zmqpubhashblock {
+                    Some(endpoint) => connector.with_block_notifications(endpoint.clone()),
+                    None => connector,
+                })
+            }
+            config::BitcoinBackend::Esplora(esplora) => {
+                bitcoin::Connector::from(EsploraConnector::new(esplora.url.clone()))
+            }
+        };
 
         runtime.block_on(async {
             validate_blockchain_config(&connector, *network)
@@ -87,12 +173,24 @@ fn main() -> anyhow::Result<()> {
 
         const BITCOIN_BLOCK_CACHE_CAPACITY: usize = 144;
 
-        Arc::new(bitcoin::Cache::new(connector, BITCOIN_BLOCK_CACHE_CAPACITY))
+        let cache = Arc::new(
+            bitcoin::Cache::new(connector, BITCOIN_BLOCK_CACHE_CAPACITY)
+                .with_confirmations(settings.confirmations.bitcoin)
+                .with_mempool_watching(settings.mempool.bitcoin)
+                .with_block_store(block_store.clone()),
+        );
+        runtime.spawn(Arc::clone(&cache).poll_latest_blocks(bitcoin::poll_interval(*network)));
+        if settings.mempool.bitcoin {
+            runtime.spawn(Arc::clone(&cache).poll_mempool());
+        }
+
+        cache
     };
 
     let ethereum_connector = {
         let config::Ethereum { parity, chain_id } = &settings.ethereum;
-        let connector = Web3Connector::new(parity.node_url.clone());
+        let connector =
+            Web3Connector::new(parity.node_url.clone()).with_event_matching(parity.event_matching);
 
         runtime.block_on(async {
             validate_blockchain_config(&connector, *chain_id)
@@ -108,11 +206,22 @@ fn main() -> anyhow::Result<()> {
         const ETHEREUM_BLOCK_CACHE_CAPACITY: usize = 720;
         const ETHEREUM_RECEIPT_CACHE_CAPACITY: usize = 720;
 
-        Arc::new(ethereum::Cache::new(
-            connector,
-            ETHEREUM_BLOCK_CACHE_CAPACITY,
-            ETHEREUM_RECEIPT_CACHE_CAPACITY,
-        ))
+        let cache = Arc::new(
+            ethereum::Cache::new(
+                connector,
+                ETHEREUM_BLOCK_CACHE_CAPACITY,
+                ETHEREUM_RECEIPT_CACHE_CAPACITY,
+            )
+            .with_confirmations(settings.confirmations.ethereum)
+            .with_mempool_watching(settings.mempool.ethereum)
+            .with_block_store(block_store),
+        );
+        runtime.spawn(Arc::clone(&cache).poll_latest_blocks(ethereum::poll_interval(*chain_id)));
+        if settings.mempool.ethereum {
+            runtime.spawn(Arc::clone(&cache).poll_mempool());
+        }
+
+        cache
     };
 
     let lnd_connector_params = LndConnectorParams::new(
@@ -129,20 +238,47 @@ fn main() -> anyhow::Result<()> {
     })
     .ok();
 
+    let state_changes = StateChanges::default();
+
     // RCF003 protocol
-    let rfc003_alpha_ledger_states = Arc::new(rfc003::LedgerStates::default());
-    let rfc003_beta_ledger_states = Arc::new(rfc003::LedgerStates::default());
-    let swap_communication_states = Arc::new(SwapCommunicationStates::default());
+    let rfc003_alpha_ledger_states = Arc::new(
+        rfc003::LedgerStates::new(state_changes.publisher(Subject::AlphaLedger))
+            .with_ledger_events(database.clone(), LedgerSide::Alpha),
+    );
+    let rfc003_beta_ledger_states = Arc::new(
+        rfc003::LedgerStates::new(state_changes.publisher(Subject::BetaLedger))
+            .with_ledger_events(database.clone(), LedgerSide::Beta),
+    );
+    let swap_communication_states = Arc::new(SwapCommunicationStates::new(
+        state_changes.publisher(Subject::Communication),
+    ));
 
     // Han/HErc20 protocols (A.K.A split protocols)
-    let alpha_ledger_states = Arc::new(LedgerStates::default());
-    let beta_ledger_states = Arc::new(LedgerStates::default());
+    let alpha_ledger_states = Arc::new(LedgerStates::new(
+        state_changes.publisher(Subject::AlphaLedger),
+    ));
+    let beta_ledger_states = Arc::new(LedgerStates::new(
+        state_changes.publisher(Subject::BetaLedger),
+    ));
 
     // HALight
-    let halight_states = Arc::new(States::default());
+    let halight_states = Arc::new(States::new(state_changes.publisher(Subject::BetaLedger)));
 
     let swap_error_states = Arc::new(SwapErrorStates::default());
 
+    let (policy, accepted_swaps) = match settings.policy.clone().map(Policy::new) {
+        Some((policy, accepted_swaps)) => (Some(policy), Some(accepted_swaps)),
+        None => (None, None),
+    };
+
+    // Shared by the network and the HTTP API, so both check the expiries of
+    // swaps against the same margins.
+    let expiry_validator = ExpiryValidator::new(
+        settings.expiry_margins,
+        Arc::clone(&bitcoin_connector),
+        Arc::clone(&ethereum_connector),
+    );
+
     let swarm = Swarm::new(
         &settings,
         seed,
@@ -155,6 +291,8 @@ fn main() -> anyhow::Result<()> {
         Arc::clone(&alpha_ledger_states),
         Arc::clone(&beta_ledger_states),
         Arc::clone(&halight_states),
+        expiry_validator.clone(),
+        policy,
         &database,
         runtime.handle().clone(),
     )?;
@@ -168,8 +306,9 @@ fn main() -> anyhow::Result<()> {
         swap_communication_states,
         swap_error_states,
         seed,
-        db: database,
+        db: database.clone(),
         swarm: swarm.clone(),
+        expiry_validator: expiry_validator.clone(),
     };
 
     // split protocols
@@ -177,15 +316,31 @@ fn main() -> anyhow::Result<()> {
         swarm: swarm.clone(),
         alpha_ledger_states: Arc::clone(&alpha_ledger_states),
         beta_ledger_states: Arc::clone(&halight_states),
+        db: database,
+        expiry_validator,
     };
 
     let http_api_listener = runtime.block_on(bind_http_api_socket(&settings))?;
-    runtime.block_on(load_swaps::load_swaps_from_database(rfc003_facade.clone()))?;
+    runtime.block_on(load_swaps::load_swaps_from_database(
+        rfc003_facade.clone(),
+        facade.clone(),
+    ))?;
+
+    if let Some(autopilot) = settings.autopilot.clone() {
+        runtime.spawn(Autopilot::new(rfc003_facade.clone(), autopilot).run());
+    }
+
+    if let Some(accepted_swaps) = accepted_swaps {
+        runtime.spawn(accepted_swaps.run(rfc003_facade.clone()));
+    }
 
     runtime.spawn(make_http_api_worker(
         settings,
         rfc003_facade,
         facade,
+        state_changes,
+        tokens,
+        tls_config,
         http_api_listener,
     ));
     runtime.spawn(make_network_api_worker(swarm));
@@ -223,19 +378,32 @@ async fn make_http_api_worker(
     settings: Settings,
     rfc003_facade: Rfc003Facade,
     facade: Facade,
+    state_changes: StateChanges,
+    tokens: Option<Tokens>,
+    tls_config: Option<tls::ServerConfig>,
     incoming_requests: tokio::net::TcpListener,
 ) {
     let routes = route_factory::create(
         rfc003_facade,
         facade,
+        state_changes,
         &settings.http_api.cors.allowed_origins,
+        tokens,
     );
 
     match incoming_requests.local_addr() {
-        Ok(socket) => {
-            tracing::info!("Starting HTTP server on {} ...", socket);
-            warp::serve(routes).serve_incoming(incoming_requests).await;
-        }
+        Ok(socket) => match tls_config {
+            Some(tls_config) => {
+                tracing::info!("Starting HTTPS server on {} ...", socket);
+                warp::serve(routes)
+                    .serve_incoming(tls::incoming(incoming_requests, tls_config))
+                    .await;
+            }
+            None => {
+                tracing::info!("Starting HTTP server on {} ...", socket);
+                warp::serve(routes).serve_incoming(incoming_requests).await;
+            }
+        },
         Err(e) => {
             tracing::error!("Cannot start HTTP server because {:?}", e);
         }
@@ -283,3 +451,77 @@ fn dump_config(settings: Settings) -> anyhow::Result<()> {
     println!("{}", serialized);
     Ok(())
 }
+
+// The mnemonic is not supposed to end up in the logs.
+fn show_mnemonic(seed: &RootSeed) {
+    eprintln!(
+        "Write down the mnemonic of the seed to be able to restore it with --restore-from-mnemonic:\n\n{}\n",
+        seed.to_mnemonic()
+    );
+}
+
+// Don't use the logger so the prompt is not mixed with log lines.
+fn restore_from_mnemonic(settings: &Settings, passphrase: Option<&str>) -> anyhow::Result<()> {
+    eprintln!("Enter the mnemonic of the seed to restore:");
+
+    let mut mnemonic = String::new();
+    io::stdin().read_line(&mut mnemonic)?;
+
+    RootSeed::restore_in_dir(&settings.data.dir, mnemonic.trim(), passphrase, OsRng)
+        .context("failed to restore seed from mnemonic")?;
+
+    eprintln!("Restored seed into {}", settings.data.dir.display());
+
+    Ok(())
+}
+
+/// The passphrase of the seed file from `--seed-passphrase-file` or the
+/// environment, if any. Otherwise we ask for it on stdin once we need it.
+fn seed_passphrase(options: &Options) -> anyhow::Result<Option<String>> {
+    if let Some(path) = &options.seed_passphrase_file {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read seed passphrase file {}", path.display()))?;
+
+        return Ok(Some(
+            contents
+                .trim_end_matches(|c| c == '\n' || c == '\r')
+                .to_owned(),
+        ));
+    }
+
+    match env::var(SEED_PASSPHRASE_ENV) {
+        Ok(passphrase) => Ok(Some(passphrase)),
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("failed to read {}", SEED_PASSPHRASE_ENV)),
+    }
+}
+
+// Don't use the logger so the prompt is not mixed with log lines.
+fn encrypt_seed(settings: &Settings, passphrase: Option<String>) -> anyhow::Result<()> {
+    let passphrase = match passphrase {
+        Some(passphrase) => passphrase,
+        None => {
+            let passphrase = rpassword::prompt_password_stderr(
+                "Enter the passphrase to encrypt the seed with: ",
+            )?;
+            let repeated = rpassword::prompt_password_stderr("Repeat the passphrase: ")?;
+
+            if passphrase != repeated {
+                anyhow::bail!("the passphrases do not match")
+            }
+
+            passphrase
+        }
+    };
+
+    if passphrase.is_empty() {
+        anyhow::bail!("the seed passphrase must not be empty")
+    }
+
+    RootSeed::encrypt_in_dir(&settings.data.dir, &passphrase, OsRng)
+        .context("failed to encrypt seed file")?;
+
+    eprintln!("Encrypted seed file in {}", settings.data.dir.display());
+
+    Ok(())
+}
@@ -1,6 +1,7 @@
 pub mod comit_ln;
 pub mod oneshot_behaviour;
 pub mod oneshot_protocol;
+pub mod orderbook;
 pub mod protocols;
 #[cfg(test)]
 pub mod test_swarm;
@@ -9,18 +10,25 @@ pub mod transport;
 pub use transport::ComitTransport;
 
 use crate::{
-    asset::AssetKind,
+    asset::{self, AssetKind},
     btsieve::{
-        bitcoin::{self, BitcoindConnector},
+        bitcoin::{self, Connector as BitcoinConnector},
         ethereum::{self, Web3Connector},
     },
     comit_api::LedgerKind,
     config::Settings,
-    db::{Save, Sqlite, Swap},
+    db::{CreatedSwap, FinalizedSwapParams, Save, Sqlite, Swap},
+    expiries::ExpiryValidator,
     htlc_location,
     http_api::LedgerNotConfigured,
+    identity,
     libp2p_comit_ext::{FromHeader, ToHeader},
-    network::comit_ln::ComitLN,
+    network::{
+        comit_ln::ComitLN,
+        orderbook::{Orderbook, OwnOffer, TakeRejected},
+        protocols::orderbook::{Offer, OfferId, TakeDecision, TakeOffer},
+    },
+    policy::{Policy, SwapTerms, Verdict},
     seed::RootSeed,
     swap_protocols::{
         halight,
@@ -40,6 +48,7 @@ use crate::{
 };
 use anyhow::Context;
 use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
 use futures::{
     channel::oneshot::{self, Sender},
     stream::StreamExt,
@@ -64,8 +73,12 @@ use std::{
     pin::Pin,
     sync::Arc,
     task::{self, Poll},
+    time::Duration,
+};
+use tokio::{
+    runtime::Handle,
+    sync::{mpsc, Mutex},
 };
-use tokio::{runtime::Handle, sync::Mutex};
 use tracing_futures::Instrument;
 
 #[derive(Clone, derivative::Derivative)]
@@ -77,12 +90,15 @@ pub struct Swarm {
     local_peer_id: PeerId,
 }
 
+/// How long a taker waits for the maker to accept or reject a take.
+const TAKE_OFFER_TIMEOUT: Duration = Duration::from_secs(30);
+
 impl Swarm {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         settings: &Settings,
         seed: RootSeed,
-        bitcoin_connector: Arc<bitcoin::Cache<BitcoindConnector>>,
+        bitcoin_connector: Arc<bitcoin::Cache<BitcoinConnector>>,
         ethereum_connector: Arc<ethereum::Cache<Web3Connector>>,
         lnd_connector_params: Option<LndConnectorParams>,
         swap_communication_states: Arc<SwapCommunicationStates>,
@@ -91,6 +107,8 @@ impl Swarm {
         alpha_ledger_states: Arc<LedgerStates>,
         beta_ledger_states: Arc<LedgerStates>,
         halight_states: Arc<States>,
+        expiry_validator: ExpiryValidator,
+        policy: Option<Policy>,
         database: &Sqlite,
         task_executor: tokio::runtime::Handle,
     ) -> anyhow::Result<Self> {
@@ -99,6 +117,7 @@ impl Swarm {
         tracing::info!("Starting with peer_id: {}", local_peer_id);
 
         let transport = transport::build_comit_transport(local_key_pair)?;
+        let (taken_offers, taken_offers_receiver) = mpsc::unbounded_channel();
         let behaviour = ComitNode::new(
             bitcoin_connector,
             ethereum_connector,
@@ -109,9 +128,12 @@ impl Swarm {
             alpha_ledger_states,
             beta_ledger_states,
             halight_states,
+            expiry_validator,
+            policy,
             seed,
             database.clone(),
             task_executor.clone(),
+            taken_offers,
         )?;
 
         let mut swarm = SwarmBuilder::new(transport, behaviour, local_peer_id.clone())
@@ -127,6 +149,12 @@ impl Swarm {
 
         let swarm = Arc::new(Mutex::new(swarm));
 
+        task_executor.spawn(accept_takes(
+            swarm.clone(),
+            database.clone(),
+            taken_offers_receiver,
+        ));
+
         Ok(Self {
             inner: swarm,
             local_peer_id,
@@ -149,11 +177,129 @@ impl Swarm {
         guard.get_finalized_swap(id)
     }
 
+    pub async fn restore_finalized_swap(
+        &self,
+        swap_params: HanEtherereumHalightBitcoinCreateSwapParams,
+        finalized: FinalizedSwapParams,
+        finalized_at: NaiveDateTime,
+    ) {
+        let mut guard = self.inner.lock().await;
+
+        guard.restore_finalized_swap(swap_params, finalized, finalized_at)
+    }
+
+    pub async fn publish_offer(&self, offer: OwnOffer) {
+        let mut guard = self.inner.lock().await;
+
+        guard.orderbook.publish_offer(offer)
+    }
+
+    /// Returns our own offers followed by the ones we discovered, each with
+    /// the peer id of its maker.
+    pub async fn offers(&self) -> Vec<(PeerId, Offer)> {
+        let guard = self.inner.lock().await;
+
+        guard
+            .orderbook
+            .own_offers()
+            .into_iter()
+            .map(|offer| (self.local_peer_id.clone(), offer))
+            .chain(guard.orderbook.discovered_offers())
+            .collect()
+    }
+
+    pub async fn fetch_offers(&self, maker: DialInformation) {
+        let mut guard = self.inner.lock().await;
+
+        guard.orderbook.fetch_offers(maker)
+    }
+
+    pub async fn take_offer(
+        &self,
+        id: LocalSwapId,
+        offer_id: OfferId,
+        lightning_amount: asset::Bitcoin,
+        ethereum_identity: identity::Ethereum,
+        lightning_identity: identity::Lightning,
+    ) -> anyhow::Result<HanEtherereumHalightBitcoinCreateSwapParams> {
+        let (swap_params, decision) = {
+            let mut guard = self.inner.lock().await;
+
+            guard.supports_halight()?;
+            guard.orderbook.take_offer(
+                id,
+                offer_id,
+                lightning_amount,
+                ethereum_identity,
+                lightning_identity,
+            )?
+        };
+
+        match tokio::time::timeout(TAKE_OFFER_TIMEOUT, decision).await {
+            Ok(Ok(TakeDecision::Accepted)) => Ok(swap_params),
+            Ok(Ok(TakeDecision::Rejected)) => Err(anyhow::Error::from(TakeRejected(offer_id))),
+            Ok(Err(_)) | Err(_) => anyhow::bail!(
+                "maker did not respond to the take of offer {} in time",
+                offer_id
+            ),
+        }
+    }
+
     // On Bob's side, when an announce message is received execute the required
     // communication protocols and write the finalized swap to the database.  Then
     // spawn the same as is done for Alice.
 }
 
+/// Saves the swaps resulting from takes of our offers and only then accepts
+/// the takes, the takers announce the swaps once they are accepted.
+async fn accept_takes(
+    swarm: Arc<Mutex<libp2p::Swarm<ComitNode>>>,
+    db: Sqlite,
+    mut taken_offers: mpsc::UnboundedReceiver<TakenOffer>,
+) {
+    while let Some(TakenOffer {
+        local_swap_id,
+        swap_params,
+        take,
+    }) = taken_offers.recv().await
+    {
+        let taker = swap_params.peer.peer_id.clone();
+
+        let saved = db
+            .save(CreatedSwap {
+                swap_id: local_swap_id,
+                params: swap_params.clone(),
+            })
+            .await;
+
+        let mut guard = swarm.lock().await;
+
+        let decision =
+            match saved.and_then(|_| guard.initiate_communication(local_swap_id, swap_params)) {
+                Ok(()) => TakeDecision::Accepted,
+                Err(e) => {
+                    tracing::error!(
+                        "failed to accept take of offer {} for swap {}: {:?}",
+                        take.offer_id,
+                        local_swap_id,
+                        e
+                    );
+                    TakeDecision::Rejected
+                }
+            };
+
+        guard.orderbook.respond_to_take(taker, take, decision);
+    }
+}
+
+/// A take of one of our offers that is accepted once its swap is saved.
+#[derive(Debug)]
+pub struct TakenOffer {
+    local_swap_id: LocalSwapId,
+    swap_params: HanEtherereumHalightBitcoinCreateSwapParams,
+    take: TakeOffer,
+}
+
 struct TokioExecutor {
     handle: tokio::runtime::Handle,
 }
@@ -202,12 +348,13 @@ fn derive_key_pair(seed: &RootSeed) -> Keypair {
 pub struct ComitNode {
     rfc003_comit: Rfc003Comit,
     comit_ln: ComitLN,
+    orderbook: Orderbook,
     /// Multicast DNS discovery network behaviour.
     mdns: Mdns,
 
     // blockchain connectors
     #[behaviour(ignore)]
-    pub bitcoin_connector: Arc<bitcoin::Cache<BitcoindConnector>>,
+    pub bitcoin_connector: Arc<bitcoin::Cache<BitcoinConnector>>,
     #[behaviour(ignore)]
     pub ethereum_connector: Arc<ethereum::Cache<Web3Connector>>,
     #[behaviour(ignore)]
@@ -238,6 +385,13 @@ pub struct ComitNode {
 
     #[behaviour(ignore)]
     halight_states: Arc<States>,
+
+    #[behaviour(ignore)]
+    expiry_validator: ExpiryValidator,
+    #[behaviour(ignore)]
+    policy: Option<Policy>,
+    #[behaviour(ignore)]
+    taken_offers: mpsc::UnboundedSender<TakenOffer>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -275,7 +429,7 @@ pub struct Reason {
 impl ComitNode {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
-        bitcoin_connector: Arc<bitcoin::Cache<BitcoindConnector>>,
+        bitcoin_connector: Arc<bitcoin::Cache<BitcoinConnector>>,
         ethereum_connector: Arc<ethereum::Cache<Web3Connector>>,
         lnd_connector_params: Option<LndConnectorParams>,
         swap_communication_states: Arc<SwapCommunicationStates>,
@@ -284,9 +438,12 @@ impl ComitNode {
         alpha_ledger_states: Arc<LedgerStates>,
         beta_ledger_states: Arc<LedgerStates>,
         halight_states: Arc<States>,
+        expiry_validator: ExpiryValidator,
+        policy: Option<Policy>,
         seed: RootSeed,
         db: Sqlite,
         task_executor: Handle,
+        taken_offers: mpsc::UnboundedSender<TakenOffer>,
     ) -> Result<Self, io::Error> {
         let mut swap_headers = HashSet::new();
         swap_headers.insert("id".into());
@@ -303,6 +460,7 @@ impl ComitNode {
             rfc003_comit: Rfc003Comit::new(known_headers),
             mdns: Mdns::new()?,
             comit_ln: ComitLN::new(seed),
+            orderbook: Orderbook::default(),
             bitcoin_connector,
             ethereum_connector,
             rfc003_alpha_ledger_states,
@@ -316,6 +474,9 @@ impl ComitNode {
             task_executor,
             lnd_connector_params: lnd_connector_params.map(Arc::new),
             halight_states,
+            expiry_validator,
+            policy,
+            taken_offers,
         })
     }
 
@@ -342,6 +503,114 @@ impl ComitNode {
         self.comit_ln.get_finalized_swap(id)
     }
 
+    /// Restores a han-ether/halight swap that was finalized before cnd was
+    /// restarted and resumes watching both ledgers from `finalized_at`.
+    pub fn restore_finalized_swap(
+        &mut self,
+        create_swap_params: HanEtherereumHalightBitcoinCreateSwapParams,
+        finalized: FinalizedSwapParams,
+        finalized_at: NaiveDateTime,
+    ) {
+        self.comit_ln
+            .restore_finalized_swap(create_swap_params.clone(), finalized);
+
+        match self.han_halight_watchers(create_swap_params, finalized, finalized_at) {
+            Ok(watchers) => {
+                tokio::task::spawn(watchers);
+            }
+            Err(e) => tracing::error!(
+                "Internal Failure: {:?}, swap {} is not watched. This should be unreachable.",
+                e,
+                finalized.swap_id
+            ),
+        }
+    }
+
+    /// Returns the watchers of a han-ether/halight swap as one future. They
+    /// only start watching once the future is spawned.
+    fn han_halight_watchers(
+        &self,
+        create_swap_params: HanEtherereumHalightBitcoinCreateSwapParams,
+        finalized: FinalizedSwapParams,
+        start_of_swap: NaiveDateTime,
+    ) -> anyhow::Result<impl Future<Output = ()> + Send + 'static> {
+        let local_swap_id = finalized.swap_id;
+        let secret_hash = finalized.secret_hash;
+        let role = create_swap_params.role;
+
+        let lnd_connector_params = self
+            .lnd_connector_params
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("lnd connectors are not initialised"))?;
+        let halight_states = self.halight_states.clone();
+
+        let (redeem_identity, refund_identity) = match role {
+            Role::Alice => (
+                finalized.counterparty_ethereum_identity,
+                create_swap_params.ethereum_identity.into(),
+            ),
+            Role::Bob => (
+                create_swap_params.ethereum_identity.into(),
+                finalized.counterparty_ethereum_identity,
+            ),
+        };
+
+        let halight = async move {
+            match role {
+                Role::Alice => {
+                    let lnd_connector: LndConnectorAsReceiver =
+                        (*lnd_connector_params).clone().into();
+
+                    halight::new_halight_swap(
+                        local_swap_id,
+                        secret_hash,
+                        halight_states,
+                        lnd_connector,
+                    )
+                    .instrument(
+                        tracing::error_span!("beta_ledger", swap_id = %local_swap_id, role = %role),
+                    )
+                    .await
+                }
+                Role::Bob => {
+                    let lnd_connector: LndConnectorAsSender =
+                        (*lnd_connector_params).clone().into();
+
+                    halight::new_halight_swap(
+                        local_swap_id,
+                        secret_hash,
+                        halight_states,
+                        lnd_connector,
+                    )
+                    .instrument(
+                        tracing::error_span!("beta_ledger", swap_id = %local_swap_id, role = %role),
+                    )
+                    .await
+                }
+            }
+        };
+
+        let han = han::new_han_ethereum_ether_swap(
+            local_swap_id,
+            self.ethereum_connector.clone(),
+            self.alpha_ledger_states.clone(),
+            HtlcParams {
+                asset: create_swap_params.ethereum_amount,
+                ledger: ledger::Ethereum::default(),
+                redeem_identity,
+                refund_identity,
+                expiry: create_swap_params.ethereum_absolute_expiry,
+                secret_hash,
+            },
+            role,
+            start_of_swap,
+        );
+
+        Ok(async move {
+            futures::join!(halight, han);
+        })
+    }
+
     fn supports_halight(&self) -> anyhow::Result<()> {
         match self.lnd_connector_params {
             Some(_) => Ok(()),
@@ -360,9 +629,11 @@ async fn handle_request(
     swap_communication_states: Arc<SwapCommunicationStates>,
     alpha_ledger_states: Arc<rfc003::LedgerStates>,
     beta_ledger_states: Arc<rfc003::LedgerStates>,
+    expiry_validator: ExpiryValidator,
+    policy: Option<Policy>,
     counterparty: PeerId,
     mut request: ValidatedInboundRequest,
-) -> Result<SwapId, libp2p_comit::frame::Response> {
+) -> Result<(SwapId, bool), libp2p_comit::frame::Response> {
     match request.request_type() {
         "SWAP" => {
             let protocol: SwapProtocol = header!(request
@@ -400,6 +671,13 @@ async fn handle_request(
                                 hash_function,
                                 body!(request.take_body_as()),
                             );
+                            let verdict = check_request(
+                                policy.as_ref(),
+                                &expiry_validator,
+                                &counterparty,
+                                SwapTerms::from(&request),
+                            )
+                            .await;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -419,10 +697,11 @@ async fn handle_request(
                                 beta_ledger_states.clone(),
                                 counterparty,
                                 request,
+                                verdict,
                             )
                             .await
-                            .expect("Could not save state to db");
-                            Ok(swap_id)
+                            .expect("Could not save state to db")
+                            .map(|accepted| (swap_id, accepted))
                         }
                         (
                             LedgerKind::BitcoinTestnet,
@@ -439,6 +718,13 @@ async fn handle_request(
                                 hash_function,
                                 body!(request.take_body_as()),
                             );
+                            let verdict = check_request(
+                                policy.as_ref(),
+                                &expiry_validator,
+                                &counterparty,
+                                SwapTerms::from(&request),
+                            )
+                            .await;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -458,10 +744,11 @@ async fn handle_request(
                                 beta_ledger_states.clone(),
                                 counterparty,
                                 request,
+                                verdict,
                             )
                             .await
-                            .expect("Could not save state to db");
-                            Ok(swap_id)
+                            .expect("Could not save state to db")
+                            .map(|accepted| (swap_id, accepted))
                         }
                         (
                             LedgerKind::BitcoinMainnet,
@@ -478,6 +765,13 @@ async fn handle_request(
                                 hash_function,
                                 body!(request.take_body_as()),
                             );
+                            let verdict = check_request(
+                                policy.as_ref(),
+                                &expiry_validator,
+                                &counterparty,
+                                SwapTerms::from(&request),
+                            )
+                            .await;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -497,10 +791,11 @@ async fn handle_request(
                                 beta_ledger_states.clone(),
                                 counterparty,
                                 request,
+                                verdict,
                             )
                             .await
-                            .expect("Could not save state to db");
-                            Ok(swap_id)
+                            .expect("Could not save state to db")
+                            .map(|accepted| (swap_id, accepted))
                         }
                         (
                             LedgerKind::Ethereum(alpha_ledger),
@@ -517,6 +812,13 @@ async fn handle_request(
                                 hash_function,
                                 body!(request.take_body_as()),
                             );
+                            let verdict = check_request(
+                                policy.as_ref(),
+                                &expiry_validator,
+                                &counterparty,
+                                SwapTerms::from(&request),
+                            )
+                            .await;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -536,10 +838,11 @@ async fn handle_request(
                                 beta_ledger_states.clone(),
                                 counterparty,
                                 request,
+                                verdict,
                             )
                             .await
-                            .expect("Could not save state to db");
-                            Ok(swap_id)
+                            .expect("Could not save state to db")
+                            .map(|accepted| (swap_id, accepted))
                         }
                         (
                             LedgerKind::Ethereum(alpha_ledger),
@@ -556,6 +859,13 @@ async fn handle_request(
                                 hash_function,
                                 body!(request.take_body_as()),
                             );
+                            let verdict = check_request(
+                                policy.as_ref(),
+                                &expiry_validator,
+                                &counterparty,
+                                SwapTerms::from(&request),
+                            )
+                            .await;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -575,10 +885,11 @@ async fn handle_request(
                                 beta_ledger_states.clone(),
                                 counterparty,
                                 request,
+                                verdict,
                             )
                             .await
-                            .expect("Could not save state to db");
-                            Ok(swap_id)
+                            .expect("Could not save state to db")
+                            .map(|accepted| (swap_id, accepted))
                         }
                         (
                             LedgerKind::Ethereum(alpha_ledger),
@@ -595,6 +906,13 @@ async fn handle_request(
                                 hash_function,
                                 body!(request.take_body_as()),
                             );
+                            let verdict = check_request(
+                                policy.as_ref(),
+                                &expiry_validator,
+                                &counterparty,
+                                SwapTerms::from(&request),
+                            )
+                            .await;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -614,10 +932,11 @@ async fn handle_request(
                                 beta_ledger_states.clone(),
                                 counterparty,
                                 request,
+                                verdict,
                             )
                             .await
-                            .expect("Could not save state to db");
-                            Ok(swap_id)
+                            .expect("Could not save state to db")
+                            .map(|accepted| (swap_id, accepted))
                         }
                         (
                             LedgerKind::BitcoinRegtest,
@@ -634,6 +953,13 @@ async fn handle_request(
                                 hash_function,
                                 body!(request.take_body_as()),
                             );
+                            let verdict = check_request(
+                                policy.as_ref(),
+                                &expiry_validator,
+                                &counterparty,
+                                SwapTerms::from(&request),
+                            )
+                            .await;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -653,11 +979,11 @@ async fn handle_request(
                                 beta_ledger_states.clone(),
                                 counterparty,
                                 request,
+                                verdict,
                             )
                             .await
-                            .expect("Could not save state to db");
-
-                            Ok(swap_id)
+                            .expect("Could not save state to db")
+                            .map(|accepted| (swap_id, accepted))
                         }
                         (
                             LedgerKind::BitcoinTestnet,
@@ -674,6 +1000,13 @@ async fn handle_request(
                                 hash_function,
                                 body!(request.take_body_as()),
                             );
+                            let verdict = check_request(
+                                policy.as_ref(),
+                                &expiry_validator,
+                                &counterparty,
+                                SwapTerms::from(&request),
+                            )
+                            .await;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -693,11 +1026,11 @@ async fn handle_request(
                                 beta_ledger_states.clone(),
                                 counterparty,
                                 request,
+                                verdict,
                             )
                             .await
-                            .expect("Could not save state to db");
-
-                            Ok(swap_id)
+                            .expect("Could not save state to db")
+                            .map(|accepted| (swap_id, accepted))
                         }
                         (
                             LedgerKind::BitcoinMainnet,
@@ -714,6 +1047,13 @@ async fn handle_request(
                                 hash_function,
                                 body!(request.take_body_as()),
                             );
+                            let verdict = check_request(
+                                policy.as_ref(),
+                                &expiry_validator,
+                                &counterparty,
+                                SwapTerms::from(&request),
+                            )
+                            .await;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -733,11 +1073,11 @@ async fn handle_request(
                                 beta_ledger_states.clone(),
                                 counterparty,
                                 request,
+                                verdict,
                             )
                             .await
-                            .expect("Could not save state to db");
-
-                            Ok(swap_id)
+                            .expect("Could not save state to db")
+                            .map(|accepted| (swap_id, accepted))
                         }
                         (
                             LedgerKind::Ethereum(alpha_ledger),
@@ -754,6 +1094,13 @@ async fn handle_request(
                                 hash_function,
                                 body!(request.take_body_as()),
                             );
+                            let verdict = check_request(
+                                policy.as_ref(),
+                                &expiry_validator,
+                                &counterparty,
+                                SwapTerms::from(&request),
+                            )
+                            .await;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -773,10 +1120,11 @@ async fn handle_request(
                                 beta_ledger_states.clone(),
                                 counterparty,
                                 request,
+                                verdict,
                             )
                             .await
-                            .expect("Could not save state to db");
-                            Ok(swap_id)
+                            .expect("Could not save state to db")
+                            .map(|accepted| (swap_id, accepted))
                         }
                         (
                             LedgerKind::Ethereum(alpha_ledger),
@@ -793,6 +1141,13 @@ async fn handle_request(
                                 hash_function,
                                 body!(request.take_body_as()),
                             );
+                            let verdict = check_request(
+                                policy.as_ref(),
+                                &expiry_validator,
+                                &counterparty,
+                                SwapTerms::from(&request),
+                            )
+                            .await;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -812,10 +1167,11 @@ async fn handle_request(
                                 beta_ledger_states.clone(),
                                 counterparty,
                                 request,
+                                verdict,
                             )
                             .await
-                            .expect("Could not save state to db");
-                            Ok(swap_id)
+                            .expect("Could not save state to db")
+                            .map(|accepted| (swap_id, accepted))
                         }
                         (
                             LedgerKind::Ethereum(alpha_ledger),
@@ -832,6 +1188,13 @@ async fn handle_request(
                                 hash_function,
                                 body!(request.take_body_as()),
                             );
+                            let verdict = check_request(
+                                policy.as_ref(),
+                                &expiry_validator,
+                                &counterparty,
+                                SwapTerms::from(&request),
+                            )
+                            .await;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -851,30 +1214,18 @@ async fn handle_request(
                                 beta_ledger_states.clone(),
                                 counterparty,
                                 request,
+                                verdict,
                             )
                             .await
-                            .expect("Could not save state to db");
-                            Ok(swap_id)
+                            .expect("Could not save state to db")
+                            .map(|accepted| (swap_id, accepted))
                         }
                         (alpha_ledger, beta_ledger, alpha_asset, beta_asset) => {
                             tracing::warn!(
                                     "swapping {:?} to {:?} from {:?} to {:?} is currently not supported", alpha_asset, beta_asset, alpha_ledger, beta_ledger
                                 );
 
-                            let decline_body = DeclineResponseBody {
-                                reason: Some(SwapDeclineReason::UnsupportedSwap),
-                            };
-
-                            Err(libp2p_comit::frame::Response::empty()
-                                .with_header(
-                                    "decision",
-                                    Decision::Declined
-                                        .to_header()
-                                        .expect("Decision should not fail to serialize"),
-                                )
-                                .with_body(serde_json::to_value(decline_body).expect(
-                                    "decline body should always serialize into serde_json::Value",
-                                )))
+                            Err(decline_response(Some(SwapDeclineReason::UnsupportedSwap)))
                         }
                     }
                 }
@@ -898,6 +1249,58 @@ async fn handle_request(
     }
 }
 
+/// Checks the expiries of an incoming request and consults the policy, if
+/// there is one.
+///
+/// Returns whether the policy accepted the request or the reason to decline
+/// it with. Without a policy, requests are left for the user to decide on.
+async fn check_request(
+    policy: Option<&Policy>,
+    expiry_validator: &ExpiryValidator,
+    counterparty: &PeerId,
+    terms: SwapTerms,
+) -> Result<bool, Option<SwapDeclineReason>> {
+    if let Err(e) = expiry_validator
+        .validate_rfc003(
+            terms.alpha_ledger,
+            terms.alpha_expiry,
+            terms.beta_ledger,
+            terms.beta_expiry,
+        )
+        .await
+    {
+        tracing::info!("declining request of {}: {}", counterparty, e);
+        return Err(None);
+    }
+
+    match policy.map(|policy| policy.evaluate(counterparty, &terms)) {
+        Some(Verdict::Accept) => Ok(true),
+        Some(Verdict::Decline(reason)) => Err(reason),
+        Some(Verdict::Undecided) | None => Ok(false),
+    }
+}
+
+fn decline_response(reason: Option<SwapDeclineReason>) -> libp2p_comit::frame::Response {
+    let decline_body = DeclineResponseBody { reason };
+
+    libp2p_comit::frame::Response::empty()
+        .with_header(
+            "decision",
+            Decision::Declined
+                .to_header()
+                .expect("Decision should not fail to serialize"),
+        )
+        .with_body(
+            serde_json::to_value(decline_body)
+                .expect("decline body should always serialize into serde_json::Value"),
+        )
+}
+
+/// Saves an incoming request together with the verdict on it, declined
+/// requests are saved as well so they show up in the HTTP API.
+///
+/// Returns whether the request was accepted or the response to decline it
+/// with.
 #[allow(clippy::type_complexity)]
 async fn insert_state_for_bob<AL, BL, AA, BA, AH, BH, AI, BI, AT, BT, DB>(
     db: DB,
@@ -906,7 +1309,8 @@ async fn insert_state_for_bob<AL, BL, AA, BA, AH, BH, AI, BI, AT, BT, DB>(
     beta_ledger_state: Arc<rfc003::LedgerStates>,
     counterparty: PeerId,
     swap_request: Request<AL, BL, AA, BA, AI, BI>,
-) -> anyhow::Result<()>
+    verdict: Result<bool, Option<SwapDeclineReason>>,
+) -> anyhow::Result<Result<bool, libp2p_comit::frame::Response>>
 where
     AL: Send + 'static,
     BL: Send + 'static,
@@ -918,7 +1322,7 @@ where
     BI: Send + 'static,
     AT: Send + 'static,
     BT: Send + 'static,
-    DB: Save<Request<AL, BL, AA, BA, AI, BI>> + Save<Swap>,
+    DB: Save<Request<AL, BL, AA, BA, AI, BI>> + Save<Swap> + Save<rfc003::Decline>,
     Request<AL, BL, AA, BA, AI, BI>: Clone,
 {
     let id = swap_request.swap_id;
@@ -926,6 +1330,26 @@ where
     Save::save(&db, Swap::new(id, Role::Bob, counterparty)).await?;
     Save::save(&db, swap_request.clone()).await?;
 
+    let accepted = match verdict {
+        Ok(accepted) => accepted,
+        Err(reason) => {
+            let decline = rfc003::Decline {
+                swap_id: id,
+                reason,
+            };
+            Save::save(&db, decline).await?;
+
+            swap_communication_states
+                .insert(id, SwapCommunication::Declined {
+                    request: swap_request,
+                    response: decline,
+                })
+                .await;
+
+            return Ok(Err(decline_response(reason)));
+        }
+    };
+
     swap_communication_states
         .insert(id, SwapCommunication::Proposed {
             request: swap_request,
@@ -939,7 +1363,7 @@ where
         .insert(id, LedgerState::<BA, BH, BT>::NotDeployed)
         .await;
 
-    Ok(())
+    Ok(Ok(accepted))
 }
 
 /// Get the `PeerId` of this node.
@@ -1129,6 +1553,8 @@ impl libp2p::swarm::NetworkBehaviourEventProcess<BehaviourOutEvent> for ComitNod
                 let swap_communication_states = self.swap_communication_states.clone();
                 let alpha_ledger_state = self.rfc003_alpha_ledger_states.clone();
                 let beta_ledger_state = self.rfc003_beta_ledger_states.clone();
+                let expiry_validator = self.expiry_validator.clone();
+                let policy = self.policy.clone();
 
                 self.task_executor.spawn(async move {
                     match handle_request(
@@ -1136,14 +1562,22 @@ impl libp2p::swarm::NetworkBehaviourEventProcess<BehaviourOutEvent> for ComitNod
                         swap_communication_states,
                         alpha_ledger_state,
                         beta_ledger_state,
+                        expiry_validator,
+                        policy.clone(),
                         peer_id,
                         request,
                     )
                     .await
                     {
-                        Ok(id) => {
-                            let mut response_channels = response_channels.lock().await;
-                            response_channels.insert(id, channel);
+                        Ok((id, accepted)) => {
+                            {
+                                let mut response_channels = response_channels.lock().await;
+                                response_channels.insert(id, channel);
+                            }
+
+                            if let (true, Some(policy)) = (accepted, policy) {
+                                policy.accept(id);
+                            }
                         }
                         Err(response) => channel.send(response).unwrap_or_else(|_| {
                             tracing::debug!("failed to send response through channel")
@@ -1168,87 +1602,92 @@ impl libp2p::swarm::NetworkBehaviourEventProcess<comit_ln::BehaviourOutEvent> fo
         match event {
             comit_ln::BehaviourOutEvent::SwapFinalized {
                 local_swap_id,
+                shared_swap_id,
                 swap_params: create_swap_params,
                 secret_hash,
                 ethereum_identity,
+                lightning_identity,
             } => {
-                let role = create_swap_params.role;
-
-                match self.lnd_connector_params {
-                    None => { tracing::error!("Internal Failure: lnd connectors are not initialised, no action has been taken. This should be unreachable.") }
-                    Some(ref lnd_connector_params) => {
-                        match role {
-                            Role::Alice => {
-                                tokio::task::spawn({
-                                    let lnd_connector: LndConnectorAsReceiver = (**lnd_connector_params).clone().into();
-                                    halight::new_halight_swap(local_swap_id, secret_hash, self.halight_states.clone(), lnd_connector)
-                                        .instrument(
-                                            tracing::error_span!("beta_ledger", swap_id = %local_swap_id, role = %role),
-                                        )
-                                });
-
-                                tokio::task::spawn({
-                                    let connector = self.ethereum_connector.clone();
-                                    let alice_ethereum_identity = create_swap_params.ethereum_identity;
-                                    let bob_ethereum_identity = ethereum_identity;
-
-                                    let asset = create_swap_params.ethereum_amount.clone();
-                                    let ledger = ledger::Ethereum::default();
-                                    let expiry = create_swap_params.ethereum_absolute_expiry;
-
-                                    han::new_han_ethereum_ether_swap(
-                                        local_swap_id,
-                                        connector,
-                                        self.alpha_ledger_states.clone(),
-                                        HtlcParams {
-                                            asset,
-                                            ledger,
-                                            redeem_identity: bob_ethereum_identity,
-                                            refund_identity: alice_ethereum_identity.into(),
-                                            expiry,
-                                            secret_hash,
-                                        },
-                                        role,
-                                    )
-                                });
-                            }
-
-                            Role::Bob => {
-                                tokio::task::spawn({
-                                    let lnd_connector: LndConnectorAsSender = (**lnd_connector_params).clone().into();
-                                    self::halight::new_halight_swap(local_swap_id, secret_hash, self.halight_states.clone(), lnd_connector)
-                                        .instrument(
-                                            tracing::error_span!("beta_ledger", swap_id = %local_swap_id, role = %role),
-                                        )
-                                });
-
-                                tokio::task::spawn({
-                                    let connector = self.ethereum_connector.clone();
-                                    let alice_ethereum_identity = ethereum_identity;
-                                    let bob_ethereum_identity = create_swap_params.ethereum_identity;
-
-                                    let asset = create_swap_params.ethereum_amount.clone();
-                                    let ledger = ledger::Ethereum::default();
-                                    let expiry = create_swap_params.ethereum_absolute_expiry;
-
-                                    self::han::new_han_ethereum_ether_swap(
-                                        local_swap_id,
-                                        connector,
-                                        self.alpha_ledger_states.clone(),
-                                        HtlcParams {
-                                            asset,
-                                            ledger,
-                                            redeem_identity: bob_ethereum_identity.into(),
-                                            refund_identity: alice_ethereum_identity,
-                                            expiry,
-                                            secret_hash,
-                                        },
-                                        role,
-                                    )
-                                });
-                            }
+                let finalized = FinalizedSwapParams {
+                    swap_id: local_swap_id,
+                    shared_swap_id,
+                    counterparty_ethereum_identity: ethereum_identity,
+                    counterparty_lightning_identity: lightning_identity,
+                    secret_hash,
+                };
+
+                let watchers = match self.han_halight_watchers(
+                    create_swap_params,
+                    finalized,
+                    Utc::now().naive_local(),
+                ) {
+                    Ok(watchers) => watchers,
+                    Err(e) => {
+                        tracing::error!(
+                            "Internal Failure: {:?}, no action has been taken. This should be unreachable.",
+                            e
+                        );
+                        return;
+                    }
+                };
+
+                tokio::task::spawn({
+                    let db = self.db.clone();
+
+                    async move {
+                        // The ledgers are only watched once the swap is persisted, otherwise
+                        // cnd would not be able to resume watching them after a restart.
+                        if let Err(e) = db.save(finalized).await {
+                            tracing::error!(
+                                "failed to save finalized swap {}, its ledgers are not watched: {:?}",
+                                local_swap_id,
+                                e
+                            );
+                            return;
                         }
+
+                        watchers.await
                     }
+                });
+            }
+        }
+    }
+}
+
+impl libp2p::swarm::NetworkBehaviourEventProcess<orderbook::BehaviourOutEvent> for ComitNode {
+    fn inject_event(&mut self, event: orderbook::BehaviourOutEvent) {
+        match event {
+            orderbook::BehaviourOutEvent::OfferTaken {
+                local_swap_id,
+                swap_params,
+                take,
+            } => {
+                let taker = swap_params.peer.peer_id.clone();
+
+                if let Err(e) = self.supports_halight() {
+                    tracing::warn!("rejecting take of offer {}: {:#}", take.offer_id, e);
+                    self.orderbook
+                        .respond_to_take(taker, take, TakeDecision::Rejected);
+                    return;
+                }
+
+                // The swap has to be saved before we accept the take, which
+                // cannot be awaited here.
+                if self
+                    .taken_offers
+                    .send(TakenOffer {
+                        local_swap_id,
+                        swap_params,
+                        take,
+                    })
+                    .is_err()
+                {
+                    tracing::error!(
+                        "no longer accepting takes, rejecting take of offer {}",
+                        take.offer_id
+                    );
+                    self.orderbook
+                        .respond_to_take(taker, take, TakeDecision::Rejected);
                 }
             }
         }
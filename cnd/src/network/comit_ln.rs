@@ -1,5 +1,7 @@
 use crate::{
-    asset, identity,
+    asset,
+    db::FinalizedSwapParams,
+    identity,
     network::{
         oneshot_behaviour,
         protocols::{
@@ -36,9 +38,11 @@ use std::{
 pub enum BehaviourOutEvent {
     SwapFinalized {
         local_swap_id: LocalSwapId,
+        shared_swap_id: SharedSwapId,
         swap_params: HanEtherereumHalightBitcoinCreateSwapParams,
         secret_hash: SecretHash,
         ethereum_identity: identity::Ethereum,
+        lightning_identity: identity::Lightning,
     },
 }
 
@@ -129,6 +133,38 @@ impl ComitLN {
         Ok(())
     }
 
+    /// Restores the state of a swap that was finalized before cnd was
+    /// restarted, without talking to the counterparty again.
+    pub fn restore_finalized_swap(
+        &mut self,
+        create_swap_params: HanEtherereumHalightBitcoinCreateSwapParams,
+        finalized: FinalizedSwapParams,
+    ) {
+        let FinalizedSwapParams {
+            swap_id: local_swap_id,
+            shared_swap_id,
+            counterparty_ethereum_identity,
+            counterparty_lightning_identity,
+            secret_hash,
+        } = finalized;
+
+        self.swaps.insert(local_swap_id, create_swap_params);
+        self.swap_ids.insert(local_swap_id, shared_swap_id);
+        self.ethereum_identities
+            .insert(shared_swap_id, counterparty_ethereum_identity);
+        self.lightning_identities
+            .insert(shared_swap_id, counterparty_lightning_identity);
+        self.secret_hashes.insert(shared_swap_id, secret_hash);
+        self.communication_state
+            .insert(shared_swap_id, CommunicationState {
+                ethereum_identity_sent: true,
+                lightning_identity_sent: true,
+                received_finalized: true,
+                sent_finalized: true,
+                secret_hash_sent_or_received: true,
+            });
+    }
+
     pub fn get_finalized_swap(&self, swap_id: LocalSwapId) -> Option<FinalizedSwap> {
         let create_swap_params = match self.swaps.get(&swap_id) {
             Some(body) => body,
@@ -604,15 +640,18 @@ impl NetworkBehaviourEventProcess<oneshot_behaviour::OutEvent<finalize::Message>
                 .expect("must exist");
 
             let ethereum_identity = self.ethereum_identities.get(&swap_id).copied().unwrap();
+            let lightning_identity = self.lightning_identities.get(&swap_id).copied().unwrap();
 
             self.swaps_waiting_for_announcement
                 .retain(|_, id| *id != local_swap_id);
 
             self.events.push_back(BehaviourOutEvent::SwapFinalized {
                 local_swap_id,
+                shared_swap_id: swap_id,
                 swap_params: create_swap_params,
                 secret_hash,
                 ethereum_identity,
+                lightning_identity,
             });
         }
     }
@@ -723,16 +762,12 @@ mod tests {
         match (alice_event, bob_event) {
             (
                 BehaviourOutEvent::SwapFinalized {
-                    local_swap_id: _alice_local_swap_id,
                     swap_params: alice_swap_params,
-                    secret_hash: _alice_secret_hash,
-                    ethereum_identity: _alice_eth_id,
+                    ..
                 },
                 BehaviourOutEvent::SwapFinalized {
-                    local_swap_id: _bob_local_swap_id,
                     swap_params: bob_swap_params,
-                    secret_hash: _bob_secret_hash,
-                    ethereum_identity: _bob_eth_id,
+                    ..
                 },
             ) => {
                 assert_eq!(bob_swap_params.digest(), alice_swap_params.digest());
@@ -8,7 +8,7 @@ use libp2p::{
 };
 use serde::{de::DeserializeOwned, Serialize};
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
     fmt::Debug,
     task::{Context, Poll},
 };
@@ -20,10 +20,34 @@ pub struct Behaviour<M> {
     /// Events that need to be yielded to the outside when polling.
     events: VecDeque<NetworkBehaviourAction<oneshot_protocol::OutboundConfig<M>, OutEvent<M>>>,
     address_book: HashMap<PeerId, Vec<Multiaddr>>,
+    connected_peers: HashSet<PeerId>,
+    /// Messages to peers we are not connected to, they are sent as soon as
+    /// the connection is established.
+    pending_messages: HashMap<PeerId, Vec<M>>,
 }
 
 impl<M> Behaviour<M> {
+    /// Sends the message to the given peer, dialing it first if we are not
+    /// connected to it yet.
     pub fn send(&mut self, peer_id: PeerId, message: M) {
+        if self.connected_peers.contains(&peer_id) {
+            self.notify_handler(peer_id, message);
+            return;
+        }
+
+        match self.pending_messages.entry(peer_id.clone()) {
+            Entry::Occupied(mut entry) => entry.get_mut().push(message),
+            Entry::Vacant(entry) => {
+                entry.insert(vec![message]);
+                self.events.push_back(NetworkBehaviourAction::DialPeer {
+                    peer_id,
+                    condition: Default::default(),
+                });
+            }
+        }
+    }
+
+    fn notify_handler(&mut self, peer_id: PeerId, message: M) {
         self.events
             .push_back(NetworkBehaviourAction::NotifyHandler {
                 peer_id,
@@ -32,10 +56,6 @@ impl<M> Behaviour<M> {
             })
     }
 
-    // If we decide to keep these different network behaviour (and not use a
-    // multi-protocols handler or something) then we should do our own
-    // connection handling in these as-well by extracting the one from the
-    // announce protocol in a reusable-manner.
     pub fn register_addresses(&mut self, peer_id: PeerId, addresses: Vec<Multiaddr>) {
         self.address_book.insert(peer_id, addresses);
     }
@@ -46,6 +66,8 @@ impl<M> Default for Behaviour<M> {
         Behaviour {
             events: VecDeque::new(),
             address_book: HashMap::default(),
+            connected_peers: HashSet::default(),
+            pending_messages: HashMap::default(),
         }
     }
 }
@@ -78,12 +100,27 @@ where
         self.address_book.get(peer).cloned().unwrap_or_default()
     }
 
-    fn inject_connected(&mut self, _: &PeerId) {
-        // Do nothing, announce protocol is going to take care of connections.
+    fn inject_connected(&mut self, peer: &PeerId) {
+        self.connected_peers.insert(peer.clone());
+
+        for message in self.pending_messages.remove(peer).unwrap_or_default() {
+            self.notify_handler(peer.clone(), message);
+        }
     }
 
-    fn inject_disconnected(&mut self, _: &PeerId) {
-        // Do nothing, announce protocol is going to take care of connections.
+    fn inject_disconnected(&mut self, peer: &PeerId) {
+        self.connected_peers.remove(peer);
+    }
+
+    fn inject_dial_failure(&mut self, peer: &PeerId) {
+        if let Some(messages) = self.pending_messages.remove(peer) {
+            tracing::warn!(
+                "failed to dial {}, dropping {} message(s) on protocol {}",
+                peer,
+                messages.len(),
+                M::INFO
+            );
+        }
     }
 
     fn inject_event(
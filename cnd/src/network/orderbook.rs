@@ -0,0 +1,587 @@
+use crate::{
+    asset, identity,
+    network::{
+        oneshot_behaviour,
+        protocols::orderbook::{
+            GetOffers, Offer, OfferId, Offers, TakeDecision, TakeOffer, TakeOfferResponse,
+        },
+        DialInformation,
+    },
+    swap_protocols::{HanEtherereumHalightBitcoinCreateSwapParams, LocalSwapId, Role},
+    timestamp::Timestamp,
+};
+use futures::channel::oneshot;
+use libp2p::{
+    swarm::{NetworkBehaviourAction, NetworkBehaviourEventProcess, PollParameters},
+    NetworkBehaviour, PeerId,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    task::{Context, Poll},
+};
+
+/// How far the Ethereum expiry chosen by the taker may deviate from the one
+/// the maker expects, this accounts for clock drift and latency.
+const EXPIRY_TOLERANCE_SECS: u32 = 600;
+
+/// Event emitted by the `Orderbook` behaviour.
+#[derive(Debug)]
+pub enum BehaviourOutEvent {
+    /// A peer took one of our offers, the take has to be answered with
+    /// `respond_to_take`.
+    OfferTaken {
+        local_swap_id: LocalSwapId,
+        swap_params: HanEtherereumHalightBitcoinCreateSwapParams,
+        take: TakeOffer,
+    },
+}
+
+/// Network behaviour that lets makers publish standing offers and takers
+/// discover and take them.
+#[derive(NetworkBehaviour, Debug)]
+#[behaviour(out_event = "BehaviourOutEvent", poll_method = "poll")]
+pub struct Orderbook {
+    get_offers: oneshot_behaviour::Behaviour<GetOffers>,
+    offers: oneshot_behaviour::Behaviour<Offers>,
+    take_offer: oneshot_behaviour::Behaviour<TakeOffer>,
+    take_offer_response: oneshot_behaviour::Behaviour<TakeOfferResponse>,
+
+    #[behaviour(ignore)]
+    events: VecDeque<BehaviourOutEvent>,
+
+    #[behaviour(ignore)]
+    own_offers: HashMap<OfferId, OwnOffer>,
+    #[behaviour(ignore)]
+    makers: HashMap<PeerId, DialInformation>,
+    #[behaviour(ignore)]
+    discovered_offers: HashMap<PeerId, Vec<Offer>>,
+    /// Takes the maker did not respond to yet. An offer can be taken several
+    /// times, hence they are keyed by the swap they result in.
+    #[behaviour(ignore)]
+    pending_takes: HashMap<LocalSwapId, PendingTake>,
+}
+
+#[derive(Debug)]
+struct PendingTake {
+    maker: PeerId,
+    take: TakeOffer,
+    decision: oneshot::Sender<TakeDecision>,
+}
+
+/// An offer we published together with the identities we use in swaps
+/// resulting from it.
+#[derive(Clone, Debug)]
+pub struct OwnOffer {
+    pub offer: Offer,
+    pub ethereum_identity: identity::Ethereum,
+    pub lightning_identity: identity::Lightning,
+}
+
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("offer {0} was not found")]
+pub struct OfferNotFound(pub OfferId);
+
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("maker rejected the take of offer {0}")]
+pub struct TakeRejected(pub OfferId);
+
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("{amount} is not within the limits of offer {offer_id}")]
+pub struct AmountNotWithinLimits {
+    pub offer_id: OfferId,
+    pub amount: asset::Bitcoin,
+}
+
+impl Default for Orderbook {
+    fn default() -> Self {
+        Self {
+            get_offers: Default::default(),
+            offers: Default::default(),
+            take_offer: Default::default(),
+            take_offer_response: Default::default(),
+            events: VecDeque::new(),
+            own_offers: Default::default(),
+            makers: Default::default(),
+            discovered_offers: Default::default(),
+            pending_takes: Default::default(),
+        }
+    }
+}
+
+impl Orderbook {
+    pub fn publish_offer(&mut self, offer: OwnOffer) {
+        tracing::info!("Publishing offer {}", offer.offer.id);
+
+        self.own_offers.insert(offer.offer.id, offer);
+    }
+
+    pub fn own_offers(&self) -> Vec<Offer> {
+        self.own_offers
+            .values()
+            .map(|own| own.offer.clone())
+            .collect()
+    }
+
+    pub fn discovered_offers(&self) -> Vec<(PeerId, Offer)> {
+        self.discovered_offers
+            .iter()
+            .flat_map(|(maker, offers)| {
+                offers
+                    .iter()
+                    .map(move |offer| (maker.clone(), offer.clone()))
+            })
+            .collect()
+    }
+
+    /// Asks the given maker for their offers, they are available through
+    /// `discovered_offers` once the maker replied.
+    pub fn fetch_offers(&mut self, maker: DialInformation) {
+        if let Some(address) = maker.address_hint.clone() {
+            self.get_offers
+                .register_addresses(maker.peer_id.clone(), vec![address]);
+        }
+
+        self.get_offers.send(maker.peer_id.clone(), GetOffers {});
+        self.makers.insert(maker.peer_id.clone(), maker);
+    }
+
+    /// Takes a previously discovered offer and returns the parameters of the
+    /// resulting swap in which we are Alice, together with the decision of
+    /// the maker.
+    ///
+    /// The swap must only be saved and announced once the maker accepted the
+    /// take.
+    pub fn take_offer(
+        &mut self,
+        local_swap_id: LocalSwapId,
+        offer_id: OfferId,
+        lightning_amount: asset::Bitcoin,
+        ethereum_identity: identity::Ethereum,
+        lightning_identity: identity::Lightning,
+    ) -> anyhow::Result<(
+        HanEtherereumHalightBitcoinCreateSwapParams,
+        oneshot::Receiver<TakeDecision>,
+    )> {
+        let (maker, offer) = self
+            .discovered_offers()
+            .into_iter()
+            .find(|(_, offer)| offer.id == offer_id)
+            .ok_or(OfferNotFound(offer_id))?;
+        let dial_information =
+            self.makers
+                .get(&maker)
+                .cloned()
+                .unwrap_or_else(|| DialInformation {
+                    peer_id: maker.clone(),
+                    address_hint: None,
+                });
+
+        let ethereum_absolute_expiry = Timestamp::now().plus(offer.ethereum_expiry_offset);
+        let swap_params = swap_params(
+            &offer,
+            Role::Alice,
+            dial_information,
+            ethereum_identity,
+            lightning_identity,
+            lightning_amount,
+            ethereum_absolute_expiry,
+        )?;
+
+        let take = TakeOffer {
+            offer_id,
+            lightning_amount: lightning_amount.as_sat(),
+            ethereum_absolute_expiry: ethereum_absolute_expiry.into(),
+        };
+
+        // Nobody waits for the takes that timed out anymore.
+        self.pending_takes
+            .retain(|_, pending| !pending.decision.is_canceled());
+
+        let (sender, receiver) = oneshot::channel();
+        self.pending_takes.insert(local_swap_id, PendingTake {
+            maker: maker.clone(),
+            take,
+            decision: sender,
+        });
+        self.take_offer.send(maker, take);
+
+        Ok((swap_params, receiver))
+    }
+
+    /// Tells the taker whether we accept their take of one of our offers.
+    pub fn respond_to_take(&mut self, taker: PeerId, take: TakeOffer, decision: TakeDecision) {
+        tracing::debug!(
+            "Responding to take of offer {} from {}: {:?}",
+            take.offer_id,
+            taker,
+            decision
+        );
+
+        self.take_offer_response
+            .send(taker, TakeOfferResponse { take, decision });
+    }
+
+    fn handle_take(&self, taker: PeerId, take: TakeOffer) -> anyhow::Result<BehaviourOutEvent> {
+        let TakeOffer {
+            offer_id,
+            lightning_amount,
+            ethereum_absolute_expiry,
+        } = take;
+
+        let own = self
+            .own_offers
+            .get(&offer_id)
+            .ok_or(OfferNotFound(offer_id))?;
+
+        let expected_expiry = Timestamp::now().plus(own.offer.ethereum_expiry_offset);
+        let earliest_expiry = u32::from(expected_expiry).saturating_sub(EXPIRY_TOLERANCE_SECS);
+        let latest_expiry = expected_expiry.plus(EXPIRY_TOLERANCE_SECS);
+        if ethereum_absolute_expiry < earliest_expiry
+            || Timestamp::from(ethereum_absolute_expiry) > latest_expiry
+        {
+            anyhow::bail!(
+                "ethereum expiry {} of take is too far from the expected {}",
+                ethereum_absolute_expiry,
+                u32::from(expected_expiry)
+            )
+        }
+
+        let swap_params = swap_params(
+            &own.offer,
+            Role::Bob,
+            DialInformation {
+                peer_id: taker,
+                address_hint: None,
+            },
+            own.ethereum_identity,
+            own.lightning_identity,
+            asset::Bitcoin::from_sat(lightning_amount),
+            Timestamp::from(ethereum_absolute_expiry),
+        )?;
+
+        Ok(BehaviourOutEvent::OfferTaken {
+            local_swap_id: LocalSwapId::default(),
+            swap_params,
+            take,
+        })
+    }
+
+    fn poll<BIE>(
+        &mut self,
+        _cx: &mut Context<'_>,
+        _params: &mut impl PollParameters,
+    ) -> Poll<NetworkBehaviourAction<BIE, BehaviourOutEvent>> {
+        if let Some(event) = self.events.pop_front() {
+            return Poll::Ready(NetworkBehaviourAction::GenerateEvent(event));
+        }
+
+        // We trust in libp2p to poll us.
+        Poll::Pending
+    }
+}
+
+/// Derives the parameters of the swap resulting from taking an offer, maker
+/// and taker need to arrive at the same swap digest.
+fn swap_params(
+    offer: &Offer,
+    role: Role,
+    peer: DialInformation,
+    ethereum_identity: identity::Ethereum,
+    lightning_identity: identity::Lightning,
+    lightning_amount: asset::Bitcoin,
+    ethereum_absolute_expiry: Timestamp,
+) -> anyhow::Result<HanEtherereumHalightBitcoinCreateSwapParams> {
+    if !offer.is_within_limits(lightning_amount) {
+        anyhow::bail!(AmountNotWithinLimits {
+            offer_id: offer.id,
+            amount: lightning_amount,
+        })
+    }
+
+    let ethereum_amount = offer
+        .ether_amount(lightning_amount)
+        .ok_or_else(|| anyhow::anyhow!("ether amount of offer {} overflows", offer.id))?;
+
+    Ok(HanEtherereumHalightBitcoinCreateSwapParams {
+        role,
+        peer,
+        ethereum_identity: ethereum_identity.into(),
+        ethereum_absolute_expiry,
+        ethereum_amount,
+        lightning_identity,
+        lightning_cltv_expiry: offer.lightning_cltv_expiry.into(),
+        lightning_amount,
+    })
+}
+
+impl NetworkBehaviourEventProcess<oneshot_behaviour::OutEvent<GetOffers>> for Orderbook {
+    fn inject_event(&mut self, event: oneshot_behaviour::OutEvent<GetOffers>) {
+        if let oneshot_behaviour::OutEvent::Received { peer, .. } = event {
+            let offers = self.own_offers();
+
+            tracing::debug!("Sending {} offer(s) to {}", offers.len(), peer);
+            self.offers.send(peer, Offers { offers });
+        }
+    }
+}
+
+impl NetworkBehaviourEventProcess<oneshot_behaviour::OutEvent<Offers>> for Orderbook {
+    fn inject_event(&mut self, event: oneshot_behaviour::OutEvent<Offers>) {
+        if let oneshot_behaviour::OutEvent::Received {
+            peer,
+            message: Offers { offers },
+        } = event
+        {
+            tracing::info!("Discovered {} offer(s) of {}", offers.len(), peer);
+            self.discovered_offers.insert(peer, offers);
+        }
+    }
+}
+
+impl NetworkBehaviourEventProcess<oneshot_behaviour::OutEvent<TakeOffer>> for Orderbook {
+    fn inject_event(&mut self, event: oneshot_behaviour::OutEvent<TakeOffer>) {
+        if let oneshot_behaviour::OutEvent::Received { peer, message } = event {
+            match self.handle_take(peer.clone(), message) {
+                Ok(event) => self.events.push_back(event),
+                Err(e) => {
+                    tracing::warn!(
+                        "rejecting take of offer {} from {}: {:#}",
+                        message.offer_id,
+                        peer,
+                        e
+                    );
+                    self.respond_to_take(peer, message, TakeDecision::Rejected);
+                }
+            }
+        }
+    }
+}
+
+impl NetworkBehaviourEventProcess<oneshot_behaviour::OutEvent<TakeOfferResponse>> for Orderbook {
+    fn inject_event(&mut self, event: oneshot_behaviour::OutEvent<TakeOfferResponse>) {
+        if let oneshot_behaviour::OutEvent::Received {
+            peer,
+            message: TakeOfferResponse { take, decision },
+        } = event
+        {
+            // Takes with identical messages are interchangeable, the maker
+            // cannot tell them apart either.
+            let local_swap_id = self
+                .pending_takes
+                .iter()
+                .find(|(_, pending)| pending.maker == peer && pending.take == take)
+                .map(|(local_swap_id, _)| *local_swap_id);
+
+            match local_swap_id.and_then(|id| self.pending_takes.remove(&id)) {
+                Some(pending) => {
+                    let _ = pending.decision.send(decision);
+                }
+                None => tracing::warn!(
+                    "ignoring response to unknown take of offer {} from {}",
+                    take.offer_id,
+                    peer
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asset::ethereum::FromWei,
+        lightning,
+        network::{test_swarm, DialInformation},
+    };
+    use digest::Digest;
+    use futures::future;
+    use std::time::Duration;
+
+    fn offer() -> Offer {
+        Offer {
+            id: OfferId::default(),
+            ether_per_bitcoin: asset::Ether::from_wei(40_000_000_000_000_000_000u128),
+            min_lightning_amount: 10_000,
+            max_lightning_amount: 1_000_000,
+            ethereum_expiry_offset: 86_400,
+            lightning_cltv_expiry: 350,
+        }
+    }
+
+    #[tokio::test]
+    async fn taker_and_maker_derive_same_swap() {
+        let (mut maker, maker_address, maker_peer_id) = test_swarm::new(Orderbook::default());
+        let (mut taker, ..) = test_swarm::new(Orderbook::default());
+
+        let offer = offer();
+        maker.publish_offer(OwnOffer {
+            offer: offer.clone(),
+            ethereum_identity: identity::Ethereum::random(),
+            lightning_identity: lightning::PublicKey::random(),
+        });
+        taker.fetch_offers(DialInformation {
+            peer_id: maker_peer_id,
+            address_hint: Some(maker_address),
+        });
+
+        // Fetching offers does not emit events, hence we poll for a while and
+        // look at the discovered offers in between.
+        while taker.discovered_offers().is_empty() {
+            let _ = tokio::time::timeout(
+                Duration::from_millis(100),
+                future::select(Box::pin(maker.next()), Box::pin(taker.next())),
+            )
+            .await;
+        }
+        assert_eq!(taker.discovered_offers()[0].1, offer);
+
+        let amount = asset::Bitcoin::from_sat(100_000);
+        let (taker_params, mut decision) = taker
+            .take_offer(
+                LocalSwapId::default(),
+                offer.id,
+                amount,
+                identity::Ethereum::random(),
+                lightning::PublicKey::random(),
+            )
+            .unwrap();
+
+        let (maker_params, take) = match maker_event(&mut maker, &mut taker).await {
+            BehaviourOutEvent::OfferTaken {
+                swap_params, take, ..
+            } => (swap_params, take),
+        };
+        maker.respond_to_take(
+            maker_params.peer.peer_id.clone(),
+            take,
+            TakeDecision::Accepted,
+        );
+
+        assert_eq!(
+            wait_for_decision(&mut maker, &mut taker, &mut decision).await,
+            TakeDecision::Accepted
+        );
+        assert_eq!(maker_params.role, Role::Bob);
+        assert_eq!(taker_params.role, Role::Alice);
+        assert_eq!(maker_params.lightning_amount, amount);
+        assert_eq!(maker_params.digest(), taker_params.digest());
+    }
+
+    #[tokio::test]
+    async fn offer_can_be_taken_twice() {
+        let (mut maker, maker_address, maker_peer_id) = test_swarm::new(Orderbook::default());
+        let (mut taker, ..) = test_swarm::new(Orderbook::default());
+
+        let offer = offer();
+        maker.publish_offer(OwnOffer {
+            offer: offer.clone(),
+            ethereum_identity: identity::Ethereum::random(),
+            lightning_identity: lightning::PublicKey::random(),
+        });
+        taker.fetch_offers(DialInformation {
+            peer_id: maker_peer_id,
+            address_hint: Some(maker_address),
+        });
+
+        while taker.discovered_offers().is_empty() {
+            let _ = tokio::time::timeout(
+                Duration::from_millis(100),
+                future::select(Box::pin(maker.next()), Box::pin(taker.next())),
+            )
+            .await;
+        }
+
+        let mut decisions = vec![];
+        for amount in &[100_000, 200_000] {
+            let amount = asset::Bitcoin::from_sat(*amount);
+
+            let (_, decision) = taker
+                .take_offer(
+                    LocalSwapId::default(),
+                    offer.id,
+                    amount,
+                    identity::Ethereum::random(),
+                    lightning::PublicKey::random(),
+                )
+                .unwrap();
+            decisions.push((amount, decision));
+        }
+
+        // Only the larger take is accepted.
+        for _ in 0..2 {
+            let (swap_params, take) = match maker_event(&mut maker, &mut taker).await {
+                BehaviourOutEvent::OfferTaken {
+                    swap_params, take, ..
+                } => (swap_params, take),
+            };
+            let decision = if swap_params.lightning_amount == asset::Bitcoin::from_sat(200_000) {
+                TakeDecision::Accepted
+            } else {
+                TakeDecision::Rejected
+            };
+
+            maker.respond_to_take(swap_params.peer.peer_id.clone(), take, decision);
+        }
+
+        for (amount, mut decision) in decisions {
+            let decision = wait_for_decision(&mut maker, &mut taker, &mut decision).await;
+
+            if amount == asset::Bitcoin::from_sat(200_000) {
+                assert_eq!(decision, TakeDecision::Accepted);
+            } else {
+                assert_eq!(decision, TakeDecision::Rejected);
+            }
+        }
+    }
+
+    /// The taker does not emit events while it waits for the maker.
+    async fn maker_event(
+        maker: &mut libp2p::Swarm<Orderbook>,
+        taker: &mut libp2p::Swarm<Orderbook>,
+    ) -> BehaviourOutEvent {
+        match future::select(Box::pin(maker.next()), Box::pin(taker.next())).await {
+            future::Either::Left((event, _)) => event,
+            future::Either::Right((event, _)) => panic!("unexpected taker event {:?}", event),
+        }
+    }
+
+    /// The decision of the maker does not emit events either, hence we poll
+    /// for a while and look at the decision in between.
+    async fn wait_for_decision(
+        maker: &mut libp2p::Swarm<Orderbook>,
+        taker: &mut libp2p::Swarm<Orderbook>,
+        decision: &mut oneshot::Receiver<TakeDecision>,
+    ) -> TakeDecision {
+        loop {
+            if let Some(decision) = decision.try_recv().expect("take not to be dropped") {
+                return decision;
+            }
+
+            let _ = tokio::time::timeout(
+                Duration::from_millis(100),
+                future::select(Box::pin(maker.next()), Box::pin(taker.next())),
+            )
+            .await;
+        }
+    }
+
+    #[test]
+    fn cannot_take_amount_outside_of_limits() {
+        let result = swap_params(
+            &offer(),
+            Role::Alice,
+            DialInformation {
+                peer_id: PeerId::random(),
+                address_hint: None,
+            },
+            identity::Ethereum::random(),
+            lightning::PublicKey::random(),
+            asset::Bitcoin::from_sat(1),
+            Timestamp::now(),
+        );
+
+        let error = result.unwrap_err();
+        assert!(error.is::<AmountNotWithinLimits>());
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{asset, asset::ethereum::FromWei, ethereum::U256, network::oneshot_protocol};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+use uuid::Uuid;
+
+/// Identifies an offer across nodes, it is created by the maker when
+/// publishing the offer.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct OfferId(Uuid);
+
+impl Default for OfferId {
+    fn default() -> Self {
+        OfferId(Uuid::new_v4())
+    }
+}
+
+impl FromStr for OfferId {
+    type Err = uuid::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Uuid::from_str(s).map(OfferId)
+    }
+}
+
+impl fmt::Display for OfferId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        self.0.fmt(f)
+    }
+}
+
+/// A standing offer of a maker to sell lightning bitcoin for ether.
+///
+/// The maker is Bob in the resulting han-ether/halight swap and the taker,
+/// who announces the swap, is Alice.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Offer {
+    pub id: OfferId,
+    /// The price of one bitcoin in wei.
+    pub ether_per_bitcoin: asset::Ether,
+    /// The smallest amount of bitcoin, in satoshi, that can be taken.
+    pub min_lightning_amount: u64,
+    /// The largest amount of bitcoin, in satoshi, that can be taken.
+    pub max_lightning_amount: u64,
+    /// The number of seconds between taking the offer and the expiry of the
+    /// Ethereum HTLC.
+    pub ethereum_expiry_offset: u32,
+    pub lightning_cltv_expiry: u32,
+}
+
+impl Offer {
+    /// The ether the taker has to pay for the given amount of bitcoin,
+    /// rounded down to the wei.
+    pub fn ether_amount(&self, lightning_amount: asset::Bitcoin) -> Option<asset::Ether> {
+        const SATS_IN_BITCOIN: u64 = 100_000_000;
+
+        let wei = self
+            .ether_per_bitcoin
+            .to_u256()
+            .checked_mul(U256::from(lightning_amount.as_sat()))?
+            / U256::from(SATS_IN_BITCOIN);
+
+        Some(asset::Ether::from_wei(wei))
+    }
+
+    pub fn is_within_limits(&self, lightning_amount: asset::Bitcoin) -> bool {
+        let sat = lightning_amount.as_sat();
+
+        sat >= self.min_lightning_amount && sat <= self.max_lightning_amount
+    }
+}
+
+/// The message a taker sends to ask a maker for their offers.
+#[derive(Clone, Copy, Deserialize, Debug, Serialize)]
+pub struct GetOffers {}
+
+impl oneshot_protocol::Message for GetOffers {
+    const INFO: &'static str = "/comit/orderbook/get_offers/1.0.0";
+}
+
+/// The message a maker sends to announce their current offers.
+#[derive(Clone, Deserialize, Debug, Serialize)]
+pub struct Offers {
+    pub offers: Vec<Offer>,
+}
+
+impl oneshot_protocol::Message for Offers {
+    const INFO: &'static str = "/comit/orderbook/offers/1.0.0";
+}
+
+/// The message a taker sends to take an offer of a maker.
+///
+/// Both nodes derive the same swap parameters from the offer and this
+/// message. Once the maker accepted the take, the taker announces the swap as
+/// usual.
+#[derive(Clone, Copy, Deserialize, Debug, PartialEq, Serialize)]
+pub struct TakeOffer {
+    pub offer_id: OfferId,
+    /// The amount of bitcoin, in satoshi, the taker wants to buy.
+    pub lightning_amount: u64,
+    pub ethereum_absolute_expiry: u32,
+}
+
+impl oneshot_protocol::Message for TakeOffer {
+    const INFO: &'static str = "/comit/orderbook/take_offer/1.0.0";
+}
+
+/// The message a maker sends in response to a take.
+///
+/// The maker only accepts once it saved the resulting swap and waits for its
+/// announcement. The take is echoed, the taker can tell its takes apart by it.
+#[derive(Clone, Copy, Deserialize, Debug, PartialEq, Serialize)]
+pub struct TakeOfferResponse {
+    pub take: TakeOffer,
+    pub decision: TakeDecision,
+}
+
+#[derive(Clone, Copy, Deserialize, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TakeDecision {
+    Accepted,
+    Rejected,
+}
+
+impl oneshot_protocol::Message for TakeOfferResponse {
+    const INFO: &'static str = "/comit/orderbook/take_offer_response/1.0.0";
+}
+
+#[cfg(test)]
+impl OfferId {
+    pub fn nil() -> Self {
+        OfferId(Uuid::nil())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spectral::prelude::*;
+
+    fn offer() -> Offer {
+        Offer {
+            id: OfferId::nil(),
+            ether_per_bitcoin: asset::Ether::from_wei(40_000_000_000_000_000_000u128),
+            min_lightning_amount: 10_000,
+            max_lightning_amount: 1_000_000,
+            ethereum_expiry_offset: 86_400,
+            lightning_cltv_expiry: 350,
+        }
+    }
+
+    #[test]
+    fn serialization_format_stability_test() {
+        let given = Offers {
+            offers: vec![offer()],
+        };
+
+        let actual = serde_json::to_string(&given);
+
+        assert_that(&actual).is_ok_containing(r#"{"offers":[{"id":"00000000-0000-0000-0000-000000000000","ether_per_bitcoin":"40000000000000000000","min_lightning_amount":10000,"max_lightning_amount":1000000,"ethereum_expiry_offset":86400,"lightning_cltv_expiry":350}]}"#.to_owned())
+    }
+
+    #[test]
+    fn take_offer_serialization_format_stability_test() {
+        let given = TakeOffer {
+            offer_id: OfferId::nil(),
+            lightning_amount: 100_000,
+            ethereum_absolute_expiry: 1_000_000,
+        };
+
+        let actual = serde_json::to_string(&given);
+
+        assert_that(&actual).is_ok_containing(r#"{"offer_id":"00000000-0000-0000-0000-000000000000","lightning_amount":100000,"ethereum_absolute_expiry":1000000}"#.to_owned())
+    }
+
+    #[test]
+    fn take_offer_response_serialization_format_stability_test() {
+        let given = TakeOfferResponse {
+            take: TakeOffer {
+                offer_id: OfferId::nil(),
+                lightning_amount: 100_000,
+                ethereum_absolute_expiry: 1_000_000,
+            },
+            decision: TakeDecision::Accepted,
+        };
+
+        let actual = serde_json::to_string(&given);
+
+        assert_that(&actual).is_ok_containing(r#"{"take":{"offer_id":"00000000-0000-0000-0000-000000000000","lightning_amount":100000,"ethereum_absolute_expiry":1000000},"decision":"accepted"}"#.to_owned())
+    }
+
+    #[test]
+    fn ether_amount_is_proportional_to_bitcoin_amount() {
+        let ether = offer().ether_amount(asset::Bitcoin::from_sat(50_000_000));
+
+        assert_eq!(
+            ether,
+            Some(asset::Ether::from_wei(20_000_000_000_000_000_000u128))
+        );
+    }
+
+    #[test]
+    fn amount_must_be_within_limits() {
+        let offer = offer();
+
+        assert!(!offer.is_within_limits(asset::Bitcoin::from_sat(9_999)));
+        assert!(offer.is_within_limits(asset::Bitcoin::from_sat(10_000)));
+        assert!(offer.is_within_limits(asset::Bitcoin::from_sat(1_000_000)));
+        assert!(!offer.is_within_limits(asset::Bitcoin::from_sat(1_000_001)));
+    }
+}
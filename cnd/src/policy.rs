@@ -0,0 +1,446 @@
+//! The policy accepts or declines incoming rfc003 swap requests on behalf of
+//! the user.
+//!
+//! Without it, every request stays pending until a client accepts or declines
+//! it over the HTTP API. The policy looks at the peer, the ledgers, the
+//! amounts, the expiries and the rate of a request as soon as it comes in.
+//! Requests within the configured limits are accepted with the configured
+//! Ethereum identity and the Bitcoin identity derived from the swap seed, all
+//! others are declined. Limits and rates cannot be configured for ERC20
+//! tokens, such requests are left for the user to decide on.
+
+use crate::{
+    asset::{self, AssetKind},
+    comit_api::LedgerKind,
+    config,
+    ethereum::U256,
+    http_api::action::ActionExecutionParameters,
+    identity,
+    swap_protocols::{
+        rfc003::{actions::ActionKind, messages::SwapDeclineReason, Request, SwapId},
+        Rfc003Facade,
+    },
+    timestamp::Timestamp,
+};
+use libp2p::PeerId;
+use std::cmp::Ordering;
+use tokio::sync::mpsc;
+use warp::http;
+
+const SATS_IN_BITCOIN: u64 = 100_000_000;
+
+#[derive(Clone, Debug)]
+pub struct Policy {
+    settings: config::Policy,
+    accepted_swaps: mpsc::UnboundedSender<SwapId>,
+}
+
+/// Accepts the swaps the policy decided to accept, this needs the facade
+/// which is only available once the network is set up.
+#[derive(Debug)]
+pub struct AcceptedSwaps {
+    ethereum_identity: identity::Ethereum,
+    receiver: mpsc::UnboundedReceiver<SwapId>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Verdict {
+    Accept,
+    Decline(Option<SwapDeclineReason>),
+    /// The policy does not cover the request, the user has to decide on it.
+    Undecided,
+}
+
+/// The parts of a swap request the policy decides on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapTerms {
+    pub alpha_ledger: LedgerKind,
+    pub beta_ledger: LedgerKind,
+    pub alpha_asset: AssetKind,
+    pub beta_asset: AssetKind,
+    pub alpha_expiry: Timestamp,
+    pub beta_expiry: Timestamp,
+}
+
+impl<AL, BL, AA, BA, AI, BI> From<&Request<AL, BL, AA, BA, AI, BI>> for SwapTerms
+where
+    AL: Clone,
+    BL: Clone,
+    AA: Clone,
+    BA: Clone,
+    LedgerKind: From<AL> + From<BL>,
+    AssetKind: From<AA> + From<BA>,
+{
+    fn from(request: &Request<AL, BL, AA, BA, AI, BI>) -> Self {
+        Self {
+            alpha_ledger: request.alpha_ledger.clone().into(),
+            beta_ledger: request.beta_ledger.clone().into(),
+            alpha_asset: request.alpha_asset.clone().into(),
+            beta_asset: request.beta_asset.clone().into(),
+            alpha_expiry: request.alpha_expiry,
+            beta_expiry: request.beta_expiry,
+        }
+    }
+}
+
+impl Policy {
+    pub fn new(settings: config::Policy) -> (Self, AcceptedSwaps) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let accepted_swaps = AcceptedSwaps {
+            ethereum_identity: settings.ethereum_identity,
+            receiver,
+        };
+
+        (
+            Self {
+                settings,
+                accepted_swaps: sender,
+            },
+            accepted_swaps,
+        )
+    }
+
+    pub fn evaluate(&self, peer: &PeerId, terms: &SwapTerms) -> Verdict {
+        let allowed_peers = &self.settings.allowed_peers;
+        if !allowed_peers.is_empty() && !allowed_peers.contains(peer) {
+            tracing::info!("declining request of {} which is not allowed", peer);
+            return Verdict::Decline(None);
+        }
+
+        if is_erc20(&terms.alpha_asset) || is_erc20(&terms.beta_asset) {
+            tracing::info!(
+                "leaving request of {} involving ERC20 tokens to the user",
+                peer
+            );
+            return Verdict::Undecided;
+        }
+
+        match self.check(terms) {
+            Ok(()) => Verdict::Accept,
+            Err(reason) => Verdict::Decline(reason),
+        }
+    }
+
+    /// Hands the swap over to be accepted, the response channel of the
+    /// request has to be registered already.
+    pub fn accept(&self, swap_id: SwapId) {
+        if self.accepted_swaps.send(swap_id).is_err() {
+            tracing::error!(
+                "policy accepted swap {} but nobody is there to accept it",
+                swap_id
+            );
+        }
+    }
+
+    fn check(&self, terms: &SwapTerms) -> Result<(), Option<SwapDeclineReason>> {
+        let settings = &self.settings;
+
+        self.check_ledger_and_asset(&terms.alpha_ledger, &terms.alpha_asset)?;
+        self.check_ledger_and_asset(&terms.beta_ledger, &terms.beta_asset)?;
+
+        let alpha_expiry = u32::from(terms.alpha_expiry);
+        let beta_expiry = u32::from(terms.beta_expiry);
+        if alpha_expiry < beta_expiry.saturating_add(settings.min_expiry_gap) {
+            tracing::info!(
+                "declining request because the expiries {} and {} are less than {} seconds apart",
+                alpha_expiry,
+                beta_expiry,
+                settings.min_expiry_gap
+            );
+            return Err(None);
+        }
+
+        self.check_rate(&terms.alpha_asset, &terms.beta_asset)
+    }
+
+    fn check_ledger_and_asset(
+        &self,
+        ledger: &LedgerKind,
+        asset: &AssetKind,
+    ) -> Result<(), Option<SwapDeclineReason>> {
+        let within_limits = match (ledger, asset) {
+            (LedgerKind::Ethereum(ethereum), AssetKind::Ether(ether)) => {
+                match &self.settings.ethereum {
+                    Some(config::PolicyEthereum {
+                        chain_id,
+                        min_amount,
+                        max_amount,
+                    }) if *chain_id == ethereum.chain_id => {
+                        ether >= min_amount && ether <= max_amount
+                    }
+                    _ => return Err(Some(SwapDeclineReason::UnsupportedSwap)),
+                }
+            }
+            (ledger, AssetKind::Bitcoin(bitcoin)) => match &self.settings.bitcoin {
+                Some(config::PolicyBitcoin {
+                    network,
+                    min_amount,
+                    max_amount,
+                }) if bitcoin_network(ledger) == Some(*network) => {
+                    let sat = bitcoin.as_sat();
+
+                    sat >= *min_amount && sat <= *max_amount
+                }
+                _ => return Err(Some(SwapDeclineReason::UnsupportedSwap)),
+            },
+            _ => return Err(Some(SwapDeclineReason::UnsupportedSwap)),
+        };
+
+        if !within_limits {
+            tracing::info!("declining request because {:?} is not within limits", asset);
+            return Err(Some(SwapDeclineReason::UnsupportedSwap));
+        }
+
+        Ok(())
+    }
+
+    fn check_rate(
+        &self,
+        alpha_asset: &AssetKind,
+        beta_asset: &AssetKind,
+    ) -> Result<(), Option<SwapDeclineReason>> {
+        let rates = &self.settings.rates;
+
+        // As Bob, we receive the alpha asset and give the beta asset.
+        let satisfactory = match (alpha_asset, beta_asset) {
+            (AssetKind::Bitcoin(bitcoin), AssetKind::Ether(ether)) => {
+                let max_price = rates
+                    .buy_bitcoin
+                    .as_ref()
+                    .ok_or(Some(SwapDeclineReason::UnsupportedSwap))?;
+
+                compare_to_price(ether, *bitcoin, max_price)
+                    .map_or(false, |ordering| ordering != Ordering::Greater)
+            }
+            (AssetKind::Ether(ether), AssetKind::Bitcoin(bitcoin)) => {
+                let min_price = rates
+                    .sell_bitcoin
+                    .as_ref()
+                    .ok_or(Some(SwapDeclineReason::UnsupportedSwap))?;
+
+                compare_to_price(ether, *bitcoin, min_price)
+                    .map_or(false, |ordering| ordering != Ordering::Less)
+            }
+            _ => return Err(Some(SwapDeclineReason::UnsupportedSwap)),
+        };
+
+        if !satisfactory {
+            tracing::info!(
+                "declining request because the rate of {:?} for {:?} is unsatisfactory",
+                beta_asset,
+                alpha_asset
+            );
+            return Err(Some(SwapDeclineReason::UnsatisfactoryRate));
+        }
+
+        Ok(())
+    }
+}
+
+/// Compares the price of `bitcoin` in `ether` with the given price in wei
+/// per bitcoin, without dividing to not lose precision.
+fn compare_to_price(
+    ether: &asset::Ether,
+    bitcoin: asset::Bitcoin,
+    price: &asset::Ether,
+) -> Option<Ordering> {
+    let ether = ether.to_u256().checked_mul(U256::from(SATS_IN_BITCOIN))?;
+    let at_price = price.to_u256().checked_mul(U256::from(bitcoin.as_sat()))?;
+
+    Some(ether.cmp(&at_price))
+}
+
+fn is_erc20(asset: &AssetKind) -> bool {
+    match asset {
+        AssetKind::Erc20(_) => true,
+        AssetKind::Bitcoin(_) | AssetKind::Ether(_) => false,
+    }
+}
+
+fn bitcoin_network(ledger: &LedgerKind) -> Option<bitcoin::Network> {
+    match ledger {
+        LedgerKind::BitcoinMainnet => Some(bitcoin::Network::Bitcoin),
+        LedgerKind::BitcoinTestnet => Some(bitcoin::Network::Testnet),
+        LedgerKind::BitcoinRegtest => Some(bitcoin::Network::Regtest),
+        LedgerKind::Ethereum(_) => None,
+    }
+}
+
+impl AcceptedSwaps {
+    pub async fn run(mut self, facade: Rfc003Facade) {
+        tracing::info!("Starting to accept swaps by policy ...");
+
+        // Only one of the identities is needed depending on which ledger is
+        // Ethereum, the other one is ignored when deserializing the body.
+        let body = serde_json::json!({
+            "alpha_ledger_redeem_identity": self.ethereum_identity,
+            "beta_ledger_refund_identity": self.ethereum_identity,
+        });
+
+        while let Some(swap_id) = self.receiver.recv().await {
+            match crate::http_api::routes::rfc003::handlers::handle_action(
+                http::Method::POST,
+                swap_id,
+                ActionKind::Accept,
+                body.clone(),
+                ActionExecutionParameters::None {},
+                facade.clone(),
+            )
+            .await
+            {
+                Ok(_) => tracing::info!("accepted swap {} by policy", swap_id),
+                Err(e) => tracing::error!("failed to accept swap {}: {:?}", swap_id, e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asset::ethereum::FromWei,
+        swap_protocols::ledger::{self, ethereum::ChainId},
+    };
+
+    fn settings() -> config::Policy {
+        config::Policy {
+            ethereum_identity: identity::Ethereum::random(),
+            allowed_peers: vec![],
+            min_expiry_gap: 3600,
+            bitcoin: Some(config::PolicyBitcoin {
+                network: bitcoin::Network::Regtest,
+                min_amount: 10_000,
+                max_amount: 100_000_000,
+            }),
+            ethereum: Some(config::PolicyEthereum {
+                chain_id: ChainId::regtest(),
+                min_amount: asset::Ether::from_wei(1_000u32),
+                max_amount: asset::Ether::from_wei(100_000_000_000_000_000_000u128),
+            }),
+            rates: config::Rates {
+                // 40 ether per bitcoin
+                sell_bitcoin: Some(asset::Ether::from_wei(40_000_000_000_000_000_000u128)),
+                // 30 ether per bitcoin
+                buy_bitcoin: Some(asset::Ether::from_wei(30_000_000_000_000_000_000u128)),
+            },
+        }
+    }
+
+    /// Terms of a request in which we sell 1 bitcoin for the given ether.
+    fn selling_bitcoin(ether: u128) -> SwapTerms {
+        SwapTerms {
+            alpha_ledger: LedgerKind::Ethereum(ledger::Ethereum::new(ChainId::regtest())),
+            beta_ledger: LedgerKind::BitcoinRegtest,
+            alpha_asset: AssetKind::Ether(asset::Ether::from_wei(ether)),
+            beta_asset: AssetKind::Bitcoin(asset::Bitcoin::from_sat(100_000_000)),
+            alpha_expiry: Timestamp::from(20_000),
+            beta_expiry: Timestamp::from(10_000),
+        }
+    }
+
+    fn evaluate(settings: config::Policy, terms: &SwapTerms) -> Verdict {
+        let (policy, _) = Policy::new(settings);
+
+        policy.evaluate(&PeerId::random(), terms)
+    }
+
+    #[test]
+    fn accepts_request_at_configured_rate() {
+        let verdict = evaluate(settings(), &selling_bitcoin(40_000_000_000_000_000_000));
+
+        assert_eq!(verdict, Verdict::Accept);
+    }
+
+    #[test]
+    fn declines_request_below_selling_rate() {
+        let verdict = evaluate(settings(), &selling_bitcoin(39_999_999_999_999_999_999));
+
+        assert_eq!(
+            verdict,
+            Verdict::Decline(Some(SwapDeclineReason::UnsatisfactoryRate))
+        );
+    }
+
+    #[test]
+    fn declines_request_above_buying_rate() {
+        let terms = SwapTerms {
+            alpha_ledger: LedgerKind::BitcoinRegtest,
+            beta_ledger: LedgerKind::Ethereum(ledger::Ethereum::new(ChainId::regtest())),
+            alpha_asset: AssetKind::Bitcoin(asset::Bitcoin::from_sat(50_000_000)),
+            beta_asset: AssetKind::Ether(asset::Ether::from_wei(15_000_000_000_000_000_001u128)),
+            alpha_expiry: Timestamp::from(20_000),
+            beta_expiry: Timestamp::from(10_000),
+        };
+
+        let verdict = evaluate(settings(), &terms);
+
+        assert_eq!(
+            verdict,
+            Verdict::Decline(Some(SwapDeclineReason::UnsatisfactoryRate))
+        );
+    }
+
+    #[test]
+    fn declines_request_on_other_network() {
+        let mut terms = selling_bitcoin(40_000_000_000_000_000_000);
+        terms.beta_ledger = LedgerKind::BitcoinMainnet;
+
+        let verdict = evaluate(settings(), &terms);
+
+        assert_eq!(
+            verdict,
+            Verdict::Decline(Some(SwapDeclineReason::UnsupportedSwap))
+        );
+    }
+
+    #[test]
+    fn declines_request_with_amount_out_of_limits() {
+        let mut settings = settings();
+        settings.bitcoin = Some(config::PolicyBitcoin {
+            network: bitcoin::Network::Regtest,
+            min_amount: 10_000,
+            max_amount: 99_999_999,
+        });
+
+        let verdict = evaluate(settings, &selling_bitcoin(40_000_000_000_000_000_000));
+
+        assert_eq!(
+            verdict,
+            Verdict::Decline(Some(SwapDeclineReason::UnsupportedSwap))
+        );
+    }
+
+    #[test]
+    fn declines_request_with_expiries_too_close() {
+        let mut terms = selling_bitcoin(40_000_000_000_000_000_000);
+        terms.alpha_expiry = Timestamp::from(13_599);
+
+        let verdict = evaluate(settings(), &terms);
+
+        assert_eq!(verdict, Verdict::Decline(None));
+    }
+
+    #[test]
+    fn declines_request_of_peer_not_allowed() {
+        let mut settings = settings();
+        settings.allowed_peers = vec![PeerId::random()];
+
+        let verdict = evaluate(settings, &selling_bitcoin(40_000_000_000_000_000_000));
+
+        assert_eq!(verdict, Verdict::Decline(None));
+    }
+
+    #[test]
+    fn leaves_erc20_request_undecided() {
+        let mut terms = selling_bitcoin(40_000_000_000_000_000_000);
+        terms.alpha_asset = AssetKind::Erc20(asset::Erc20::new(
+            identity::Ethereum::random(),
+            asset::Erc20Quantity::from_wei(1_000u32),
+        ));
+
+        let verdict = evaluate(settings(), &terms);
+
+        assert_eq!(verdict, Verdict::Undecided);
+    }
+}
@@ -17,6 +17,7 @@ use ::bitcoin::{
     hashes::{sha256d, Hash},
     secp256k1,
 };
+use chrono::NaiveDateTime;
 use impl_template::impl_template;
 use libp2p::PeerId;
 use quickcheck::{Arbitrary, Gen};
@@ -297,6 +298,7 @@ impl Arbitrary for Quickcheck<Swap> {
             swap_id: *Quickcheck::<SwapId>::arbitrary(g),
             role: *Quickcheck::<Role>::arbitrary(g),
             counterparty: Quickcheck::<PeerId>::arbitrary(g).0,
+            created_at: NaiveDateTime::from_timestamp(i64::from(g.next_u32()), 0),
         })
     }
 }
@@ -1,14 +1,21 @@
 use crate::swap_protocols::{rfc003::SwapId, LocalSwapId};
+use bip39::{Language, Mnemonic};
+use chacha20poly1305::{
+    aead::{Aead, NewAead},
+    ChaCha20Poly1305, Key, Nonce,
+};
 use pem::{encode, Pem};
 use rand::Rng;
+use scrypt::ScryptParams;
 use sha2::{Digest, Sha256};
 use std::{
     ffi::OsStr,
     fmt,
-    fs::{self, File},
+    fs::{self, OpenOptions},
     io::{self, Write},
     path::{Path, PathBuf},
 };
+use zeroize::{Zeroize, Zeroizing};
 
 /// We create a `RootSeed` either randomly or by reading in the PEM file from
 /// disk.  This `RootSeed` is used to generate a per swap `SwapSeed` which is
@@ -44,6 +51,27 @@ impl DeriveSwapSeed for RootSeed {
 
 const SEED_LENGTH: usize = 32;
 
+const PLAINTEXT_TAG: &str = "SEED";
+const ENCRYPTED_TAG: &str = "ENCRYPTED SEED";
+
+/// An encrypted seed file contains the scrypt cost parameter `log_n`, the
+/// salt, the nonce and the seed encrypted with ChaCha20-Poly1305 under the
+/// key derived from the passphrase, in this order.
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+const TAG_LENGTH: usize = 16;
+const ENCRYPTED_LENGTH: usize = 1 + SALT_LENGTH + NONCE_LENGTH + SEED_LENGTH + TAG_LENGTH;
+
+/// The scrypt parameters recommended for interactive logins, deriving the key
+/// takes less than a second and 32 MiB of memory.
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+/// The highest cost we accept from a seed file, deriving the key takes 256 MiB
+/// of memory. A corrupted file must not make us allocate more.
+const MAX_SCRYPT_LOG_N: u8 = 18;
+
 #[derive(Clone, Copy, PartialEq)]
 struct Seed([u8; SEED_LENGTH]);
 
@@ -133,37 +161,170 @@ impl RootSeed {
 
     /// Read the seed from the directory if it exists, otherwise
     /// generate a random seed and write it to that location.
+    ///
+    /// Only works with seed files which are not encrypted.
     pub fn from_dir_or_generate<D, R>(data_dir: D, rand: R) -> Result<RootSeed, Error>
     where
         D: AsRef<OsStr>,
         R: Rng,
     {
-        let dir = Path::new(&data_dir);
-        let path = seed_path_from_dir(dir);
+        match Self::from_dir(&data_dir, || Err(Error::PassphraseRequired))? {
+            Some(seed) => Ok(seed),
+            None => Self::generate_in_dir(&data_dir, None, rand),
+        }
+    }
+
+    /// Read the seed from the directory, `None` if there is no seed file.
+    ///
+    /// The passphrase is only asked for if the seed file is encrypted.
+    pub fn from_dir<D, P>(data_dir: D, passphrase: P) -> Result<Option<RootSeed>, Error>
+    where
+        D: AsRef<OsStr>,
+        P: FnOnce() -> Result<String, Error>,
+    {
+        let path = seed_path_from_dir(Path::new(&data_dir));
 
-        if path.exists() {
-            return Self::from_file(&path);
+        if !path.exists() {
+            return Ok(None);
         }
 
-        let random_seed = RootSeed::new_random(rand)?;
-        random_seed.write_to(path.clone())?;
+        Self::from_file(&path, passphrase).map(Some)
+    }
+
+    /// Generate a random seed and write it to the directory, which must not
+    /// contain a seed yet. The seed file is encrypted if a passphrase is
+    /// given.
+    pub fn generate_in_dir<D, R>(
+        data_dir: D,
+        passphrase: Option<&str>,
+        mut rand: R,
+    ) -> Result<RootSeed, Error>
+    where
+        D: AsRef<OsStr>,
+        R: Rng,
+    {
+        let path = seed_path_from_dir(Path::new(&data_dir));
+
+        let random_seed = RootSeed::new_random(&mut rand)?;
+        random_seed.write_to_new(path.clone(), passphrase, rand)?;
 
         tracing::info!("No seed file found, creating at: {}", path.display());
 
         Ok(random_seed)
     }
 
-    fn from_file<D>(seed_file: D) -> Result<RootSeed, Error>
+    /// Recreate the seed from its mnemonic and write it to the directory,
+    /// which must not contain a seed yet. The seed file is encrypted if a
+    /// passphrase is given.
+    pub fn restore_in_dir<D, R>(
+        data_dir: D,
+        mnemonic: &str,
+        passphrase: Option<&str>,
+        rand: R,
+    ) -> Result<RootSeed, Error>
+    where
+        D: AsRef<OsStr>,
+        R: Rng,
+    {
+        let path = seed_path_from_dir(Path::new(&data_dir));
+
+        let seed = RootSeed::from_mnemonic(mnemonic)?;
+        seed.write_to_new(path.clone(), passphrase, rand)?;
+
+        tracing::info!("Restored seed from mnemonic at: {}", path.display());
+
+        Ok(seed)
+    }
+
+    /// Replace the plaintext seed file in the directory with one encrypted
+    /// with the passphrase.
+    pub fn encrypt_in_dir<D, R>(data_dir: D, passphrase: &str, rand: R) -> Result<RootSeed, Error>
+    where
+        D: AsRef<OsStr>,
+        R: Rng,
+    {
+        let path = seed_path_from_dir(Path::new(&data_dir));
+
+        let pem = read_pem(&path)?;
+        if pem.tag == ENCRYPTED_TAG {
+            return Err(Error::AlreadyEncrypted(path));
+        }
+        let seed = RootSeed::from_pem(pem)?;
+
+        // Write to a temporary file first, so we never end up without a seed
+        // file if something goes wrong.
+        let encrypted = path.with_extension("pem.encrypted");
+        seed._write_encrypted_to(encrypted.clone(), passphrase, SCRYPT_LOG_N, rand)?;
+        fs::rename(&encrypted, &path)?;
+
+        tracing::info!("Encrypted seed file: {}", path.display());
+
+        Ok(seed)
+    }
+
+    /// The BIP39 mnemonic of the seed, 24 english words encoding its bytes.
+    ///
+    /// Unlike a BIP39 wallet, we use the words as the seed directly instead of
+    /// stretching them, hence there is no passphrase.
+    pub fn to_mnemonic(&self) -> String {
+        Mnemonic::from_entropy(&(self.0).0, Language::English)
+            .expect("32 bytes are a valid entropy length")
+            .into_phrase()
+    }
+
+    pub fn from_mnemonic(mnemonic: &str) -> Result<RootSeed, Error> {
+        let mnemonic = Mnemonic::from_phrase(mnemonic, Language::English)
+            .map_err(|e| Error::Mnemonic(e.to_string()))?;
+        let entropy = mnemonic.entropy();
+
+        if entropy.len() != SEED_LENGTH {
+            return Err(Error::IncorrectLength(entropy.len()));
+        }
+
+        let mut array = [0; SEED_LENGTH];
+        array.copy_from_slice(entropy);
+
+        Ok(RootSeed::from(array))
+    }
+
+    fn from_file<D, P>(seed_file: D, passphrase: P) -> Result<RootSeed, Error>
     where
         D: AsRef<OsStr>,
+        P: FnOnce() -> Result<String, Error>,
     {
         let file = Path::new(&seed_file);
-        let contents = fs::read_to_string(file)?;
-        let pem = pem::parse(contents)?;
+        let pem = read_pem(file)?;
+
+        let seed = if pem.tag == ENCRYPTED_TAG {
+            RootSeed::from_encrypted_pem(pem, &passphrase()?)?
+        } else {
+            RootSeed::from_pem(pem)?
+        };
 
         tracing::info!("Read in seed from file: {}", file.display());
 
-        RootSeed::from_pem(pem)
+        Ok(seed)
+    }
+
+    fn from_encrypted_pem(pem: pem::Pem, passphrase: &str) -> Result<RootSeed, Error> {
+        let contents = pem.contents;
+        if contents.len() != ENCRYPTED_LENGTH {
+            return Err(Error::IncorrectLength(contents.len()));
+        }
+
+        let (log_n, rest) = contents.split_at(1);
+        let (salt, rest) = rest.split_at(SALT_LENGTH);
+        let (nonce, ciphertext) = rest.split_at(NONCE_LENGTH);
+
+        let plaintext = cipher(passphrase, salt, log_n[0])?
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map(Zeroizing::new)
+            .map_err(|_| Error::WrongPassphrase)?;
+
+        let mut array = [0; SEED_LENGTH];
+        array.copy_from_slice(&plaintext);
+
+        Ok(RootSeed::from(array))
     }
 
     fn from_pem(pem: pem::Pem) -> Result<RootSeed, Error> {
@@ -179,26 +340,114 @@ impl RootSeed {
         }
     }
 
-    fn write_to(&self, seed_file: PathBuf) -> Result<(), Error> {
+    fn write_to_new<R>(
+        &self,
+        seed_file: PathBuf,
+        passphrase: Option<&str>,
+        rand: R,
+    ) -> Result<(), Error>
+    where
+        R: Rng,
+    {
+        if seed_file.exists() {
+            return Err(Error::AlreadyExists(seed_file));
+        }
+
         ensure_directory_exists(seed_file.clone())?;
-        self._write_to(seed_file)?;
+        match passphrase {
+            Some(passphrase) => {
+                self._write_encrypted_to(seed_file, passphrase, SCRYPT_LOG_N, rand)?
+            }
+            None => self._write_to(seed_file)?,
+        }
         Ok(())
     }
 
     fn _write_to(&self, path: PathBuf) -> Result<(), Error> {
         let data = (self.0).0;
         let pem = Pem {
-            tag: String::from("SEED"),
+            tag: String::from(PLAINTEXT_TAG),
             contents: data.to_vec(),
         };
 
-        let pem_string = encode(&pem);
+        write_pem(path, &pem)
+    }
 
-        let mut file = File::create(path)?;
-        file.write_all(pem_string.as_bytes())?;
+    fn _write_encrypted_to<R>(
+        &self,
+        path: PathBuf,
+        passphrase: &str,
+        log_n: u8,
+        mut rand: R,
+    ) -> Result<(), Error>
+    where
+        R: Rng,
+    {
+        if passphrase.is_empty() {
+            return Err(Error::EmptyPassphrase);
+        }
 
-        Ok(())
+        let mut salt = [0u8; SALT_LENGTH];
+        rand.try_fill(&mut salt[..])?;
+        let mut nonce = [0u8; NONCE_LENGTH];
+        rand.try_fill(&mut nonce[..])?;
+
+        let ciphertext = cipher(passphrase, &salt, log_n)?
+            .encrypt(Nonce::from_slice(&nonce), &(self.0).0[..])
+            .expect("a seed is not too long to be encrypted");
+
+        let pem = Pem {
+            tag: String::from(ENCRYPTED_TAG),
+            contents: [&[log_n][..], &salt[..], &nonce[..], &ciphertext[..]].concat(),
+        };
+
+        write_pem(path, &pem)
+    }
+}
+
+/// Derives the key from the passphrase, this is deliberately slow.
+fn cipher(passphrase: &str, salt: &[u8], log_n: u8) -> Result<ChaCha20Poly1305, Error> {
+    if log_n > MAX_SCRYPT_LOG_N {
+        return Err(Error::InvalidScryptParams);
+    }
+    let params =
+        ScryptParams::new(log_n, SCRYPT_R, SCRYPT_P).map_err(|_| Error::InvalidScryptParams)?;
+
+    let mut key = [0u8; 32];
+    scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut key)
+        .expect("32 bytes are a valid key length");
+
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+    key.zeroize();
+
+    Ok(cipher)
+}
+
+fn read_pem(path: &Path) -> Result<Pem, Error> {
+    let contents = fs::read_to_string(path)?;
+    let pem = pem::parse(contents)?;
+
+    Ok(pem)
+}
+
+/// Writes the seed file readable only by us and flushes it to disk, so it is
+/// complete before it is renamed into place.
+fn write_pem(path: PathBuf, pem: &Pem) -> Result<(), Error> {
+    let pem_string = encode(pem);
+
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
     }
+
+    let mut file = options.open(path)?;
+    file.write_all(pem_string.as_bytes())?;
+    file.sync_all()?;
+
+    Ok(())
 }
 
 impl SwapSeed {
@@ -242,6 +491,20 @@ pub enum Error {
     Rand(#[from] rand::Error),
     #[error("no default path")]
     NoDefaultPath,
+    #[error("invalid mnemonic: {0}")]
+    Mnemonic(String),
+    #[error("seed file {} already exists", .0.display())]
+    AlreadyExists(PathBuf),
+    #[error("seed file {} is already encrypted", .0.display())]
+    AlreadyEncrypted(PathBuf),
+    #[error("the seed file is encrypted, a passphrase is required")]
+    PassphraseRequired,
+    #[error("refusing to encrypt the seed with an empty passphrase")]
+    EmptyPassphrase,
+    #[error("wrong passphrase or corrupted seed file")]
+    WrongPassphrase,
+    #[error("invalid scrypt parameters in seed file")]
+    InvalidScryptParams,
 }
 
 impl From<[u8; SEED_LENGTH]> for RootSeed {
@@ -357,7 +620,119 @@ mbKANv2qKGmNVg1qtquj6Hx1pFPelpqOfE2JaJJAMEg1FlFhNRNlFlE=
         seed._write_to(path.clone())
             .expect("Write seed to temp file");
 
-        let rinsed = RootSeed::from_file(path).expect("Read from temp file");
+        let rinsed = RootSeed::from_file(path, || Err(Error::PassphraseRequired))
+            .expect("Read from temp file");
         assert_eq!(seed.0, rinsed.0);
     }
+
+    #[test]
+    fn mnemonic_of_zero_seed_is_the_bip39_test_vector() {
+        let seed = RootSeed::from([0u8; SEED_LENGTH]);
+
+        let mnemonic = seed.to_mnemonic();
+
+        assert_eq!(mnemonic, format!("{}art", "abandon ".repeat(23)));
+    }
+
+    #[test]
+    fn round_trip_through_mnemonic() {
+        let seed = RootSeed::new_random(OsRng).unwrap();
+
+        let restored = RootSeed::from_mnemonic(&seed.to_mnemonic()).unwrap();
+
+        assert_eq!(seed, restored);
+    }
+
+    #[test]
+    fn mnemonic_with_wrong_checksum_is_rejected() {
+        let mnemonic = "abandon ".repeat(24);
+
+        match RootSeed::from_mnemonic(mnemonic.trim()) {
+            Err(Error::Mnemonic(_)) => {}
+            other => panic!("expected invalid mnemonic, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn restore_does_not_overwrite_an_existing_seed() {
+        let dir = tempfile::tempdir().unwrap();
+        let seed = RootSeed::generate_in_dir(dir.path(), None, OsRng).unwrap();
+
+        let restored = RootSeed::restore_in_dir(dir.path(), &seed.to_mnemonic(), None, OsRng);
+
+        assert!(matches!(restored, Err(Error::AlreadyExists(_))));
+        assert_eq!(
+            RootSeed::from_dir(dir.path(), || Err(Error::PassphraseRequired)).unwrap(),
+            Some(seed)
+        );
+    }
+
+    // A low scrypt cost keeps the tests fast, it is read back from the file.
+    const TEST_LOG_N: u8 = 4;
+
+    fn write_encrypted(seed: RootSeed, passphrase: &str) -> tempfile::NamedTempFile {
+        let tmpfile = tempfile::NamedTempFile::new().unwrap();
+        seed._write_encrypted_to(tmpfile.path().to_path_buf(), passphrase, TEST_LOG_N, OsRng)
+            .unwrap();
+
+        tmpfile
+    }
+
+    #[test]
+    fn round_trip_through_encrypted_file() {
+        let seed = RootSeed::new_random(OsRng).unwrap();
+        let tmpfile = write_encrypted(seed, "correct horse");
+
+        let contents = fs::read_to_string(tmpfile.path()).unwrap();
+        let rinsed =
+            RootSeed::from_file(tmpfile.path(), || Ok("correct horse".to_owned())).unwrap();
+
+        assert!(contents.starts_with("-----BEGIN ENCRYPTED SEED-----"));
+        assert_eq!(seed, rinsed);
+    }
+
+    #[test]
+    fn encrypted_file_requires_the_right_passphrase() {
+        let seed = RootSeed::new_random(OsRng).unwrap();
+        let tmpfile = write_encrypted(seed, "correct horse");
+
+        let wrong = RootSeed::from_file(tmpfile.path(), || Ok("battery staple".to_owned()));
+        let missing = RootSeed::from_file(tmpfile.path(), || Err(Error::PassphraseRequired));
+
+        assert!(matches!(wrong, Err(Error::WrongPassphrase)));
+        assert!(matches!(missing, Err(Error::PassphraseRequired)));
+    }
+
+    #[test]
+    fn refuses_to_encrypt_with_an_empty_passphrase() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let generated = RootSeed::generate_in_dir(dir.path(), Some(""), OsRng);
+
+        assert!(matches!(generated, Err(Error::EmptyPassphrase)));
+        assert!(!seed_path_from_dir(dir.path()).exists());
+    }
+    #[test]
+    fn rejects_an_excessive_scrypt_cost() {
+        assert!(matches!(
+            cipher("correct horse", &[0; SALT_LENGTH], MAX_SCRYPT_LOG_N + 1),
+            Err(Error::InvalidScryptParams)
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn seed_file_is_only_readable_by_us() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        RootSeed::generate_in_dir(dir.path(), None, OsRng).unwrap();
+
+        let mode = fs::metadata(seed_path_from_dir(dir.path()))
+            .unwrap()
+            .permissions()
+            .mode();
+
+        assert_eq!(mode & 0o777, 0o600);
+    }
 }
@@ -2,12 +2,14 @@ pub mod actions;
 mod facade;
 pub mod halight;
 pub mod han;
+pub mod hbit;
 pub mod herc20;
 pub mod ledger;
 pub mod ledger_states;
 pub mod rfc003;
 mod rfc003_facade;
 pub mod state;
+pub mod state_changes;
 mod swap_error_states;
 mod swap_id;
 
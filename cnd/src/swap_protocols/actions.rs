@@ -11,7 +11,7 @@ pub trait Actions {
 }
 
 pub mod bitcoin {
-    use crate::asset;
+    use crate::{asset, timestamp::Timestamp};
     use bitcoin::Address;
     use blockchain_contracts::bitcoin::witness::{PrimedInput, PrimedTransaction};
 
@@ -27,6 +27,8 @@ pub mod bitcoin {
         // Remember: One man's input is another man's output!
         pub output: PrimedInput,
         pub network: bitcoin::Network,
+        /// The expiry of the HTLC the output is locked in.
+        pub htlc_expiry: Timestamp,
     }
 
     impl SpendOutput {
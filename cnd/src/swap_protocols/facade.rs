@@ -1,10 +1,22 @@
 use crate::{
-    asset, identity,
-    network::{comit_ln, protocols::announce::SwapDigest, DialInformation, Swarm},
+    asset,
+    db::{CreatedSwap, Save, Sqlite},
+    expiries::ExpiryValidator,
+    identity,
+    network::{
+        comit_ln,
+        orderbook::OwnOffer,
+        protocols::{
+            announce::SwapDigest,
+            orderbook::{Offer, OfferId},
+        },
+        DialInformation, Swarm,
+    },
     swap_protocols::{halight, LedgerStates, LocalSwapId, Role},
     timestamp::Timestamp,
 };
 use digest::{Digest, IntoDigestInput};
+use libp2p::PeerId;
 use std::sync::Arc;
 
 /// This represent the information available on a swap
@@ -72,10 +84,23 @@ pub struct Facade {
     // We currently only support Han-HALight, therefor 'alpha' is Ethereum and 'beta' is Lightning.
     pub alpha_ledger_states: Arc<LedgerStates>,
     pub beta_ledger_states: Arc<halight::States>,
+    pub db: Sqlite,
+    pub expiry_validator: ExpiryValidator,
 }
 
 impl Facade {
-    pub async fn save(&self, _id: LocalSwapId, _swap_params: ()) {}
+    pub async fn save(
+        &self,
+        id: LocalSwapId,
+        swap_params: HanEtherereumHalightBitcoinCreateSwapParams,
+    ) -> anyhow::Result<()> {
+        self.db
+            .save(CreatedSwap {
+                swap_id: id,
+                params: swap_params,
+            })
+            .await
+    }
 
     pub async fn initiate_communication(
         &self,
@@ -88,4 +113,35 @@ impl Facade {
     pub async fn get_finalized_swap(&self, id: LocalSwapId) -> Option<comit_ln::FinalizedSwap> {
         self.swarm.get_finalized_swap(id).await
     }
+
+    pub async fn publish_offer(&self, offer: OwnOffer) {
+        self.swarm.publish_offer(offer).await
+    }
+
+    pub async fn offers(&self) -> Vec<(PeerId, Offer)> {
+        self.swarm.offers().await
+    }
+
+    pub async fn fetch_offers(&self, maker: DialInformation) {
+        self.swarm.fetch_offers(maker).await
+    }
+
+    pub async fn take_offer(
+        &self,
+        id: LocalSwapId,
+        offer_id: OfferId,
+        lightning_amount: asset::Bitcoin,
+        ethereum_identity: identity::Ethereum,
+        lightning_identity: identity::Lightning,
+    ) -> anyhow::Result<HanEtherereumHalightBitcoinCreateSwapParams> {
+        self.swarm
+            .take_offer(
+                id,
+                offer_id,
+                lightning_amount,
+                ethereum_identity,
+                lightning_identity,
+            )
+            .await
+    }
 }
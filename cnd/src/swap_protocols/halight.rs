@@ -2,6 +2,7 @@ use crate::swap_protocols::{
     rfc003::{Secret, SecretHash},
     state,
     state::Update,
+    state_changes::Publisher,
     LocalSwapId,
 };
 use futures::{
@@ -9,6 +10,7 @@ use futures::{
     Stream, TryFutureExt, TryStreamExt,
 };
 use genawaiter::sync::Gen;
+use serde::Serialize;
 use std::{
     collections::{hash_map::Entry, HashMap},
     sync::Arc,
@@ -65,7 +67,12 @@ pub trait WaitForCancelled {
 }
 
 /// Represents states that an invoice can be in.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, strum_macros::EnumDiscriminants)]
+#[strum_discriminants(
+    name(Status),
+    derive(Serialize),
+    serde(rename_all = "SCREAMING_SNAKE_CASE")
+)]
 pub enum State {
     None,
     Opened(Opened),
@@ -120,7 +127,19 @@ pub struct Settled {
 pub struct Cancelled;
 
 #[derive(Default, Debug)]
-pub struct States(Mutex<HashMap<LocalSwapId, State>>);
+pub struct States {
+    states: Mutex<HashMap<LocalSwapId, State>>,
+    publisher: Publisher,
+}
+
+impl States {
+    pub fn new(publisher: Publisher) -> Self {
+        Self {
+            states: Mutex::default(),
+            publisher,
+        }
+    }
+}
 
 impl State {
     pub fn transition_to_opened(&mut self, opened: Opened) {
@@ -158,7 +177,7 @@ impl State {
 #[async_trait::async_trait]
 impl state::Get<State> for States {
     async fn get(&self, key: &LocalSwapId) -> anyhow::Result<Option<State>> {
-        let states = self.0.lock().await;
+        let states = self.states.lock().await;
         let state = states.get(key).copied();
 
         Ok(state)
@@ -168,35 +187,45 @@ impl state::Get<State> for States {
 #[async_trait::async_trait]
 impl state::Update<Event> for States {
     async fn update(&self, key: &LocalSwapId, event: Event) {
-        let mut states = self.0.lock().await;
+        let mut states = self.states.lock().await;
         let entry = states.entry(*key);
 
-        match (event, entry) {
-            (Event::Started, Entry::Vacant(vacant)) => {
-                vacant.insert(State::None);
-            }
-            (Event::Opened(opened), Entry::Occupied(mut state)) => {
-                state.get_mut().transition_to_opened(opened)
+        let state = match (event, entry) {
+            (Event::Started, Entry::Vacant(vacant)) => vacant.insert(State::None),
+            (Event::Opened(opened), Entry::Occupied(state)) => {
+                let state = state.into_mut();
+                state.transition_to_opened(opened);
+                state
             }
-            (Event::Accepted(accepted), Entry::Occupied(mut state)) => {
-                state.get_mut().transition_to_accepted(accepted)
+            (Event::Accepted(accepted), Entry::Occupied(state)) => {
+                let state = state.into_mut();
+                state.transition_to_accepted(accepted);
+                state
             }
-            (Event::Settled(settled), Entry::Occupied(mut state)) => {
-                state.get_mut().transition_to_settled(settled)
+            (Event::Settled(settled), Entry::Occupied(state)) => {
+                let state = state.into_mut();
+                state.transition_to_settled(settled);
+                state
             }
-            (Event::Cancelled(cancelled), Entry::Occupied(mut state)) => {
-                state.get_mut().transition_to_cancelled(cancelled)
+            (Event::Cancelled(cancelled), Entry::Occupied(state)) => {
+                let state = state.into_mut();
+                state.transition_to_cancelled(cancelled);
+                state
             }
             (Event::Started, Entry::Occupied(_)) => {
                 tracing::warn!(
                     "Received Started event for {} although state is already present",
                     key
                 );
+                return;
             }
             (_, Entry::Vacant(_)) => {
                 tracing::warn!("State not found for {}", key);
+                return;
             }
-        }
+        };
+
+        self.publisher.publish(key, Status::from(&*state));
     }
 }
 
@@ -5,9 +5,10 @@ use crate::{
     swap_protocols::{
         han, ledger,
         rfc003::{
-            create_swap::{HtlcParams, SwapEvent},
+            create_swap::{unless_retracted, HtlcParams, SwapEvent},
             events::{
                 Deployed, HtlcDeployed, HtlcFunded, HtlcRedeemed, HtlcRefunded, Redeemed, Refunded,
+                TransactionRetracted,
             },
             LedgerState,
         },
@@ -15,8 +16,11 @@ use crate::{
     },
     transaction,
 };
-use chrono::{NaiveDateTime, Utc};
-use futures::future::{self, Either};
+use chrono::NaiveDateTime;
+use futures::{
+    future::{self, Either},
+    TryFutureExt,
+};
 use genawaiter::{
     sync::{Co, Gen},
     GeneratorState,
@@ -30,13 +34,14 @@ pub async fn new_han_ethereum_ether_swap(
     ethereum_ledger_state: Arc<LedgerStates>,
     htlc_params: HtlcParams<ledger::Ethereum, asset::Ether, identity::Ethereum>,
     role: Role,
+    start_of_swap: NaiveDateTime,
 ) {
     han::create_watcher::<_, _, _, _, htlc_location::Ethereum, _, transaction::Ethereum>(
         connector.as_ref(),
         ethereum_ledger_state,
         swap_id,
         htlc_params,
-        Utc::now().naive_local(),
+        start_of_swap,
     )
     .instrument(tracing::error_span!("alpha_ledger", swap_id = %swap_id, role = %role))
     .await
@@ -63,7 +68,8 @@ async fn create_watcher<C, S, L, A, H, I, T>(
     C: HtlcFunded<L, A, H, I, T>
         + HtlcDeployed<L, A, H, I, T>
         + HtlcRedeemed<L, A, H, I, T>
-        + HtlcRefunded<L, A, H, I, T>,
+        + HtlcRefunded<L, A, H, I, T>
+        + TransactionRetracted<T>,
     S: state::Update<SwapEvent<A, H, T>> + state::Insert<LedgerState<A, H, T>>,
     L: Clone,
     A: Ord + Clone,
@@ -108,6 +114,8 @@ async fn create_watcher<C, S, L, A, H, I, T>(
 /// Returns a future that waits for events to happen on a ledger.
 ///
 /// Each event is yielded through the controller handle (co) of the coroutine.
+/// If a reorg drops the deploy or fund transaction before the HTLC is redeemed
+/// or refunded, we yield `Retracted` and start over.
 async fn watch_ledger<C, L, A, H, I, T>(
     ethereum_connector: &C,
     co: Co<SwapEvent<A, H, T>>,
@@ -118,38 +126,65 @@ where
     C: HtlcFunded<L, A, H, I, T>
         + HtlcDeployed<L, A, H, I, T>
         + HtlcRedeemed<L, A, H, I, T>
-        + HtlcRefunded<L, A, H, I, T>,
+        + HtlcRefunded<L, A, H, I, T>
+        + TransactionRetracted<T>,
     Deployed<H, T>: Clone,
     Redeemed<T>: Clone,
     Refunded<T>: Clone,
+    T: Clone,
 {
-    let deployed = ethereum_connector
-        .htlc_deployed(&htlc_params, start_of_swap)
-        .await?;
-    co.yield_(SwapEvent::Deployed(deployed.clone())).await;
+    loop {
+        let deployed = ethereum_connector
+            .htlc_deployed(&htlc_params, start_of_swap)
+            .await?;
+        co.yield_(SwapEvent::Deployed(deployed.clone())).await;
 
-    let funded = ethereum_connector
-        .htlc_funded(&htlc_params, &deployed, start_of_swap)
-        .await?;
-    co.yield_(SwapEvent::Funded(funded)).await;
+        let funded = ethereum_connector.htlc_funded(&htlc_params, &deployed, start_of_swap);
 
-    let redeemed = ethereum_connector.htlc_redeemed(&htlc_params, &deployed, start_of_swap);
+        let funded = match unless_retracted(
+            ethereum_connector,
+            &deployed.transaction,
+            start_of_swap,
+            funded,
+        )
+        .await?
+        {
+            Ok(funded) => funded,
+            Err(retracted) => {
+                co.yield_(SwapEvent::Retracted(retracted)).await;
+                continue;
+            }
+        };
+        let fund_transaction = funded.transaction().clone();
+        co.yield_(SwapEvent::Funded(funded)).await;
 
-    let refunded = ethereum_connector.htlc_refunded(&htlc_params, &deployed, start_of_swap);
+        let redeemed = ethereum_connector.htlc_redeemed(&htlc_params, &deployed, start_of_swap);
 
-    match future::try_select(redeemed, refunded).await {
-        Ok(Either::Left((redeemed, _))) => {
-            co.yield_(SwapEvent::Redeemed(redeemed.clone())).await;
-        }
-        Ok(Either::Right((refunded, _))) => {
-            co.yield_(SwapEvent::Refunded(refunded.clone())).await;
-        }
-        Err(either) => {
-            let (error, _other_future) = either.factor_first();
+        let refunded = ethereum_connector.htlc_refunded(&htlc_params, &deployed, start_of_swap);
 
-            return Err(error);
+        let redeemed_or_refunded =
+            future::try_select(redeemed, refunded).map_err(|either| either.factor_first().0);
+
+        match unless_retracted(
+            ethereum_connector,
+            &fund_transaction,
+            start_of_swap,
+            redeemed_or_refunded,
+        )
+        .await?
+        {
+            Ok(Either::Left((redeemed, _))) => {
+                co.yield_(SwapEvent::Redeemed(redeemed.clone())).await;
+            }
+            Ok(Either::Right((refunded, _))) => {
+                co.yield_(SwapEvent::Refunded(refunded.clone())).await;
+            }
+            Err(retracted) => {
+                co.yield_(SwapEvent::Retracted(retracted)).await;
+                continue;
+            }
         }
-    }
 
-    Ok(())
+        return Ok(());
+    }
 }
@@ -0,0 +1,327 @@
+use crate::{
+    asset, htlc_location, identity,
+    swap_protocols::{
+        rfc003::{Secret, SecretHash},
+        state,
+        state::Update,
+        LocalSwapId,
+    },
+    transaction,
+};
+use chrono::NaiveDateTime;
+use futures::{
+    future::{self, Either},
+    Stream, TryStreamExt,
+};
+use genawaiter::sync::{Co, Gen};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    sync::Arc,
+};
+use tokio::sync::Mutex;
+
+mod connector_impls;
+
+use crate::timestamp::Timestamp;
+use ::bitcoin::{
+    hashes::{hash160, Hash},
+    Address,
+};
+use blockchain_contracts::bitcoin::rfc003::bitcoin_htlc::BitcoinHtlc;
+pub use connector_impls::*;
+
+/// Watches the Bitcoin HTLC of an hbit swap and records its state until it is
+/// either redeemed or refunded.
+pub async fn new_hbit_swap<C>(
+    id: LocalSwapId,
+    params: Params,
+    state_store: Arc<States>,
+    connector: Arc<C>,
+) where
+    C: WaitForFunded + WaitForRedeemed + WaitForRefunded,
+{
+    let mut events = new(connector.as_ref(), params)
+        .inspect_ok(|event| tracing::info!("yielded event {}", event))
+        .inspect_err(|error| tracing::error!("swap failed with {:?}", error));
+
+    while let Ok(Some(event)) = events.try_next().await {
+        state_store.update(&id, event).await;
+    }
+
+    tracing::info!("swap finished");
+}
+
+/// Resolves when said event has occured.
+#[async_trait::async_trait]
+pub trait WaitForFunded {
+    async fn wait_for_funded(&self, params: Params) -> anyhow::Result<Funded>;
+}
+
+#[async_trait::async_trait]
+pub trait WaitForRedeemed {
+    async fn wait_for_redeemed(&self, params: Params, funded: Funded) -> anyhow::Result<Redeemed>;
+}
+
+#[async_trait::async_trait]
+pub trait WaitForRefunded {
+    async fn wait_for_refunded(&self, params: Params, funded: Funded) -> anyhow::Result<Refunded>;
+}
+
+/// Represents states that a Bitcoin HTLC can be in.
+///
+/// In contrast to Ethereum, there is no separate deploy step, the HTLC comes
+/// into existence with the transaction that funds it.
+#[derive(Debug, Clone)]
+pub enum State {
+    None,
+    Funded(Funded),
+    Redeemed(Redeemed),
+    Refunded(Refunded),
+}
+
+/// Represents the events in the hbit protocol.
+#[derive(Debug, Clone, PartialEq, strum_macros::Display)]
+pub enum Event {
+    /// The protocol was started.
+    Started,
+
+    /// The HTLC has been funded with bitcoin.
+    Funded(Funded),
+
+    /// The HTLC has been spent via the redeem path, bitcoin have been sent to
+    /// the redeemer.
+    Redeemed(Redeemed),
+
+    /// The HTLC has been spent via the refund path, bitcoin have been sent
+    /// back to the funder.
+    Refunded(Refunded),
+}
+
+/// Represents the data available at said state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Funded {
+    Correctly {
+        transaction: transaction::Bitcoin,
+        location: htlc_location::Bitcoin,
+        asset: asset::Bitcoin,
+    },
+    Incorrectly {
+        transaction: transaction::Bitcoin,
+        location: htlc_location::Bitcoin,
+        asset: asset::Bitcoin,
+    },
+}
+
+impl Funded {
+    pub fn location(&self) -> htlc_location::Bitcoin {
+        match self {
+            Funded::Correctly { location, .. } | Funded::Incorrectly { location, .. } => *location,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redeemed {
+    pub transaction: transaction::Bitcoin,
+    pub secret: Secret,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Refunded {
+    pub transaction: transaction::Bitcoin,
+}
+
+#[derive(Default, Debug)]
+pub struct States(Mutex<HashMap<LocalSwapId, State>>);
+
+impl State {
+    pub fn transition_to_funded(&mut self, funded: Funded) {
+        match std::mem::replace(self, State::None) {
+            State::None => *self = State::Funded(funded),
+            other => panic!("expected state None, got {:?}", other),
+        }
+    }
+
+    pub fn transition_to_redeemed(&mut self, redeemed: Redeemed) {
+        match std::mem::replace(self, State::None) {
+            State::Funded(_) => *self = State::Redeemed(redeemed),
+            other => panic!("expected state Funded, got {:?}", other),
+        }
+    }
+
+    pub fn transition_to_refunded(&mut self, refunded: Refunded) {
+        match std::mem::replace(self, State::None) {
+            State::Funded(_) => *self = State::Refunded(refunded),
+            other => panic!("expected state Funded, got {:?}", other),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl state::Get<State> for States {
+    async fn get(&self, key: &LocalSwapId) -> anyhow::Result<Option<State>> {
+        let states = self.0.lock().await;
+        let state = states.get(key).cloned();
+
+        Ok(state)
+    }
+}
+
+#[async_trait::async_trait]
+impl state::Update<Event> for States {
+    async fn update(&self, key: &LocalSwapId, event: Event) {
+        let mut states = self.0.lock().await;
+        let entry = states.entry(*key);
+
+        match (event, entry) {
+            (Event::Started, Entry::Vacant(vacant)) => {
+                vacant.insert(State::None);
+            }
+            (Event::Funded(funded), Entry::Occupied(mut state)) => {
+                state.get_mut().transition_to_funded(funded)
+            }
+            (Event::Redeemed(redeemed), Entry::Occupied(mut state)) => {
+                state.get_mut().transition_to_redeemed(redeemed)
+            }
+            (Event::Refunded(refunded), Entry::Occupied(mut state)) => {
+                state.get_mut().transition_to_refunded(refunded)
+            }
+            (Event::Started, Entry::Occupied(_)) => {
+                tracing::warn!(
+                    "Received Started event for {} although state is already present",
+                    key
+                );
+            }
+            (_, Entry::Vacant(_)) => {
+                tracing::warn!("State not found for {}", key);
+            }
+        }
+    }
+}
+
+/// Creates a new instance of the hbit protocol.
+///
+/// Returns a stream of events happening during the execution.
+pub fn new<'a, C>(
+    connector: &'a C,
+    params: Params,
+) -> impl Stream<Item = anyhow::Result<Event>> + 'a
+where
+    C: WaitForFunded + WaitForRedeemed + WaitForRefunded,
+{
+    Gen::new({
+        |co| async move {
+            if let Err(error) = watch_ledger(connector, params, &co).await {
+                co.yield_(Err(error)).await;
+            }
+        }
+    })
+}
+
+async fn watch_ledger<C, R>(
+    connector: &C,
+    params: Params,
+    co: &Co<anyhow::Result<Event>, R>,
+) -> anyhow::Result<()>
+where
+    C: WaitForFunded + WaitForRedeemed + WaitForRefunded,
+{
+    co.yield_(Ok(Event::Started)).await;
+
+    let funded = connector.wait_for_funded(params).await?;
+    co.yield_(Ok(Event::Funded(funded.clone()))).await;
+
+    let redeemed = connector.wait_for_redeemed(params, funded.clone());
+    let refunded = connector.wait_for_refunded(params, funded);
+
+    match future::try_select(redeemed, refunded).await {
+        Ok(Either::Left((redeemed, _))) => {
+            co.yield_(Ok(Event::Redeemed(redeemed))).await;
+        }
+        Ok(Either::Right((refunded, _))) => {
+            co.yield_(Ok(Event::Refunded(refunded))).await;
+        }
+        Err(either) => {
+            let (error, _other_future) = either.factor_first();
+            return Err(error);
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Params {
+    pub network: ::bitcoin::Network,
+    pub asset: asset::Bitcoin,
+    pub redeem_identity: identity::Bitcoin,
+    pub refund_identity: identity::Bitcoin,
+    pub expiry: Timestamp,
+    pub start_of_swap: NaiveDateTime,
+    pub secret_hash: SecretHash,
+}
+
+impl Params {
+    pub fn compute_address(&self) -> Address {
+        BitcoinHtlc::from(*self).compute_address(self.network)
+    }
+}
+
+impl From<Params> for BitcoinHtlc {
+    fn from(params: Params) -> Self {
+        let refund_public_key = ::bitcoin::PublicKey::from(params.refund_identity);
+        let redeem_public_key = ::bitcoin::PublicKey::from(params.redeem_identity);
+
+        let refund_identity = hash160::Hash::hash(&refund_public_key.key.serialize());
+        let redeem_identity = hash160::Hash::hash(&redeem_public_key.key.serialize());
+
+        BitcoinHtlc::new(
+            params.expiry.into(),
+            refund_identity,
+            redeem_identity,
+            params.secret_hash.into_raw(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_protocols::{ledger, rfc003::create_swap::HtlcParams};
+    use ::bitcoin::secp256k1;
+
+    fn identity(byte: u8) -> identity::Bitcoin {
+        let secret_key = secp256k1::SecretKey::from_slice(&[byte; 32]).unwrap();
+
+        identity::Bitcoin::from_secret_key(&*crate::SECP, &secret_key)
+    }
+
+    #[test]
+    fn hbit_htlc_address_matches_rfc003_htlc_address() {
+        let asset = asset::Bitcoin::from_sat(100_000_000);
+        let redeem_identity = identity(1);
+        let refund_identity = identity(2);
+        let expiry = Timestamp::from(1_000_000);
+        let secret_hash = SecretHash::from([3u8; 32]);
+
+        let params = Params {
+            network: ::bitcoin::Network::Regtest,
+            asset,
+            redeem_identity,
+            refund_identity,
+            expiry,
+            start_of_swap: NaiveDateTime::from_timestamp(0, 0),
+            secret_hash,
+        };
+        let rfc003_params = HtlcParams {
+            asset,
+            ledger: ledger::bitcoin::Regtest,
+            redeem_identity,
+            refund_identity,
+            expiry,
+            secret_hash,
+        };
+
+        assert_eq!(params.compute_address(), rfc003_params.compute_address());
+    }
+}
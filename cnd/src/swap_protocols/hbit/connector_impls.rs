@@ -0,0 +1,81 @@
+use crate::{
+    asset,
+    btsieve::bitcoin::{watch_for_created_outpoint, watch_for_spent_outpoint, Cache, Connector},
+    swap_protocols::{
+        hbit::{
+            Funded, Params, Redeemed, Refunded, WaitForFunded, WaitForRedeemed, WaitForRefunded,
+        },
+        rfc003::bitcoin::extract_secret,
+    },
+};
+use std::cmp::Ordering;
+use tracing_futures::Instrument;
+
+#[async_trait::async_trait]
+impl WaitForFunded for Cache<Connector> {
+    async fn wait_for_funded(&self, params: Params) -> anyhow::Result<Funded> {
+        let expected_asset = params.asset;
+
+        let (transaction, location) =
+            watch_for_created_outpoint(self, params.start_of_swap, params.compute_address())
+                .instrument(tracing::info_span!("funded"))
+                .await?;
+
+        let asset = asset::Bitcoin::from_sat(transaction.output[location.vout as usize].value);
+
+        let event = match expected_asset.cmp(&asset) {
+            Ordering::Equal => Funded::Correctly {
+                transaction,
+                location,
+                asset,
+            },
+            _ => Funded::Incorrectly {
+                transaction,
+                location,
+                asset,
+            },
+        };
+
+        Ok(event)
+    }
+}
+
+#[async_trait::async_trait]
+impl WaitForRedeemed for Cache<Connector> {
+    async fn wait_for_redeemed(&self, params: Params, funded: Funded) -> anyhow::Result<Redeemed> {
+        let (transaction, _) = watch_for_spent_outpoint(
+            self,
+            params.start_of_swap,
+            &params.compute_address(),
+            funded.location(),
+            params.redeem_identity,
+        )
+        .instrument(tracing::info_span!("redeemed"))
+        .await?;
+
+        let secret = extract_secret(&transaction, &params.secret_hash)
+            .ok_or_else(|| anyhow::anyhow!("redeem transaction does not contain secret"))?;
+
+        Ok(Redeemed {
+            transaction,
+            secret,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl WaitForRefunded for Cache<Connector> {
+    async fn wait_for_refunded(&self, params: Params, funded: Funded) -> anyhow::Result<Refunded> {
+        let (transaction, _) = watch_for_spent_outpoint(
+            self,
+            params.start_of_swap,
+            &params.compute_address(),
+            funded.location(),
+            params.refund_identity,
+        )
+        .instrument(tracing::info_span!("refunded"))
+        .await?;
+
+        Ok(Refunded { transaction })
+    }
+}
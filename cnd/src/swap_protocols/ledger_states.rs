@@ -1,6 +1,7 @@
 use crate::swap_protocols::{
-    rfc003::{create_swap::SwapEvent, LedgerState},
+    rfc003::{create_swap::SwapEvent, HtlcState, LedgerState},
     state::{Get, Insert, Update},
+    state_changes::Publisher,
     LocalSwapId,
 };
 use async_trait::async_trait;
@@ -10,6 +11,16 @@ use tokio::sync::Mutex;
 #[derive(Default, Debug)]
 pub struct LedgerStates {
     states: Mutex<HashMap<LocalSwapId, Box<dyn Any + Send>>>,
+    publisher: Publisher,
+}
+
+impl LedgerStates {
+    pub fn new(publisher: Publisher) -> Self {
+        Self {
+            states: Mutex::default(),
+            publisher,
+        }
+    }
 }
 
 #[async_trait]
@@ -64,20 +75,33 @@ where
         match event {
             SwapEvent::Deployed(deployed) => ledger_state.transition_to_deployed(deployed),
             SwapEvent::Funded(funded) => ledger_state.transition_to_funded(funded),
+            SwapEvent::RedeemPending(redeem_pending) => {
+                ledger_state.transition_to_redeem_pending(redeem_pending)
+            }
             SwapEvent::Redeemed(redeemed) => {
                 // what if redeemed.secret.hash() != secret_hash in request ??
 
                 ledger_state.transition_to_redeemed(redeemed);
             }
             SwapEvent::Refunded(refunded) => ledger_state.transition_to_refunded(refunded),
+            SwapEvent::Retracted(retracted) => ledger_state.transition_to_not_deployed(retracted),
         }
+
+        self.publisher.publish(key, HtlcState::from(&*ledger_state));
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{asset, htlc_location, transaction};
+    use crate::{
+        asset, htlc_location,
+        swap_protocols::{
+            rfc003::events::Deployed,
+            state_changes::{StateChange, StateChanges, Subject},
+        },
+        transaction,
+    };
     use spectral::prelude::*;
 
     #[tokio::test]
@@ -91,4 +115,35 @@ mod tests {
             ledger_states.get(&id).await.unwrap();
         assert_that(&res).contains_value(&LedgerState::NotDeployed);
     }
+
+    #[tokio::test]
+    async fn update_publishes_new_status() {
+        let state_changes = StateChanges::default();
+        let mut receiver = state_changes.subscribe();
+        let ledger_states = LedgerStates::new(state_changes.publisher(Subject::AlphaLedger));
+        let id = LocalSwapId::default();
+
+        ledger_states.insert(id, LedgerState::<asset::Bitcoin, htlc_location::Bitcoin, transaction::Bitcoin>::NotDeployed).await;
+        ledger_states
+            .update(
+                &id,
+                SwapEvent::<asset::Bitcoin, _, _>::Deployed(Deployed {
+                    location: htlc_location::Bitcoin::null(),
+                    transaction: transaction::Bitcoin {
+                        version: 2,
+                        lock_time: 0,
+                        input: vec![],
+                        output: vec![],
+                    },
+                }),
+            )
+            .await;
+
+        let change = receiver.recv().await.unwrap();
+        assert_that(&change).is_equal_to(StateChange {
+            swap_id: id.to_string(),
+            subject: Subject::AlphaLedger,
+            status: serde_json::json!("DEPLOYED"),
+        });
+    }
 }
@@ -9,7 +9,7 @@ use std::marker::PhantomData;
 #[derive(Debug, Clone, PartialEq, strum_macros::EnumDiscriminants)]
 #[strum_discriminants(
     name(ActionKind),
-    derive(Display, EnumString),
+    derive(Display, EnumString, Hash),
     strum(serialize_all = "snake_case")
 )]
 pub enum Action<Accept, Decline, Deploy, Fund, Redeem, Refund> {
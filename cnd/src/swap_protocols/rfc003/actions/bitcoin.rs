@@ -55,6 +55,7 @@ where
                 htlc.unlock_after_timeout(&*crate::SECP, secret_source.derive_refund_identity()),
             ),
             network: B::network(),
+            htlc_expiry: htlc_params.expiry,
         }
     }
 }
@@ -86,6 +87,7 @@ where
                 ),
             ),
             network: B::network(),
+            htlc_expiry: htlc_params.expiry,
         }
     }
 }
@@ -70,11 +70,13 @@ where
                 request.alpha_asset.token_contract,
                 *htlc_location,
             ))],
-            Funded { htlc_location, .. } => vec![Action::Refund(erc20::refund_action(
-                request.alpha_ledger.chain_id,
-                request.alpha_expiry,
-                *htlc_location,
-            ))],
+            Funded { htlc_location, .. } | RedeemPending { htlc_location, .. } => {
+                vec![Action::Refund(erc20::refund_action(
+                    request.alpha_ledger.chain_id,
+                    request.alpha_expiry,
+                    *htlc_location,
+                ))]
+            }
             _ => vec![],
         };
 
@@ -147,6 +149,11 @@ where
                 htlc_location,
                 fund_transaction,
                 ..
+            }
+            | RedeemPending {
+                htlc_location,
+                fund_transaction,
+                ..
             } => vec![Action::Refund(<(AL, AA)>::make_refund_action(
                 HtlcParams::new_alpha_params(request, response),
                 *htlc_location,
@@ -65,6 +65,11 @@ where
                 htlc_location,
                 fund_transaction,
                 ..
+            }
+            | RedeemPending {
+                htlc_location,
+                fund_transaction,
+                ..
             } => vec![Action::Refund(<(AL, AA)>::make_refund_action(
                 HtlcParams::new_alpha_params(request, response),
                 htlc_location.clone(),
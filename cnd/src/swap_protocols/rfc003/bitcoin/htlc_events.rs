@@ -1,7 +1,11 @@
 use crate::{
     asset,
-    btsieve::bitcoin::{
-        watch_for_created_outpoint, watch_for_spent_outpoint, BitcoindConnector, Cache,
+    btsieve::{
+        bitcoin::{
+            processed_block, watch_for_created_outpoint, watch_for_spent_outpoint,
+            watch_mempool_for_spent_outpoint, Cache, Connector,
+        },
+        watch_for_retraction, ProcessedBlock,
     },
     htlc_location, identity,
     swap_protocols::{
@@ -11,20 +15,21 @@ use crate::{
             create_swap::HtlcParams,
             events::{
                 Deployed, Funded, HtlcDeployed, HtlcFunded, HtlcRedeemed, HtlcRefunded, Redeemed,
-                Refunded,
+                Refunded, Retracted, TransactionProcessed, TransactionRetracted,
             },
         },
     },
     transaction,
 };
 use chrono::NaiveDateTime;
+use futures::future;
 use std::cmp::Ordering;
 use tracing_futures::Instrument;
 
 #[async_trait::async_trait]
 impl<B>
     HtlcFunded<B, asset::Bitcoin, htlc_location::Bitcoin, identity::Bitcoin, transaction::Bitcoin>
-    for Cache<BitcoindConnector>
+    for Cache<Connector>
 where
     B: bitcoin::Bitcoin + bitcoin::Network,
 {
@@ -58,7 +63,7 @@ where
 #[async_trait::async_trait]
 impl<B>
     HtlcDeployed<B, asset::Bitcoin, htlc_location::Bitcoin, identity::Bitcoin, transaction::Bitcoin>
-    for Cache<BitcoindConnector>
+    for Cache<Connector>
 where
     B: bitcoin::Bitcoin + bitcoin::Network,
 {
@@ -82,7 +87,7 @@ where
 #[async_trait::async_trait]
 impl<B>
     HtlcRedeemed<B, asset::Bitcoin, htlc_location::Bitcoin, identity::Bitcoin, transaction::Bitcoin>
-    for Cache<BitcoindConnector>
+    for Cache<Connector>
 where
     B: bitcoin::Bitcoin + bitcoin::Network,
 {
@@ -95,6 +100,7 @@ where
         let (transaction, _) = watch_for_spent_outpoint(
             self,
             start_of_swap,
+            &htlc_params.compute_address(),
             htlc_deployment.location,
             htlc_params.redeem_identity,
         )
@@ -109,12 +115,38 @@ where
             secret,
         })
     }
+
+    async fn htlc_redeem_pending(
+        &self,
+        htlc_params: &HtlcParams<B, asset::Bitcoin, identity::Bitcoin>,
+        htlc_deployment: &Deployed<htlc_location::Bitcoin, transaction::Bitcoin>,
+    ) -> anyhow::Result<Redeemed<transaction::Bitcoin>> {
+        if !self.watch_mempool {
+            return future::pending().await;
+        }
+
+        let (transaction, _) = watch_mempool_for_spent_outpoint(
+            self,
+            htlc_deployment.location,
+            htlc_params.redeem_identity,
+        )
+        .instrument(tracing::info_span!("htlc_redeem_pending"))
+        .await?;
+
+        let secret = extract_secret(&transaction, &htlc_params.secret_hash)
+            .expect("Redeem transaction must contain secret");
+
+        Ok(Redeemed {
+            transaction,
+            secret,
+        })
+    }
 }
 
 #[async_trait::async_trait]
 impl<B>
     HtlcRefunded<B, asset::Bitcoin, htlc_location::Bitcoin, identity::Bitcoin, transaction::Bitcoin>
-    for Cache<BitcoindConnector>
+    for Cache<Connector>
 where
     B: bitcoin::Bitcoin + bitcoin::Network,
 {
@@ -127,6 +159,7 @@ where
         let (transaction, _) = watch_for_spent_outpoint(
             self,
             start_of_swap,
+            &htlc_params.compute_address(),
             htlc_deployment.location,
             htlc_params.refund_identity,
         )
@@ -136,3 +169,33 @@ where
         Ok(Refunded { transaction })
     }
 }
+
+#[async_trait::async_trait]
+impl TransactionRetracted<transaction::Bitcoin> for Cache<Connector> {
+    async fn transaction_retracted(
+        &self,
+        transaction: &transaction::Bitcoin,
+        start_of_swap: NaiveDateTime,
+    ) -> anyhow::Result<Retracted<transaction::Bitcoin>> {
+        watch_for_retraction(self, start_of_swap, &transaction.txid())
+            .instrument(tracing::info_span!("transaction_retracted"))
+            .await?;
+
+        Ok(Retracted {
+            transaction: transaction.clone(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl TransactionProcessed<transaction::Bitcoin> for Cache<Connector> {
+    async fn transaction_processed(
+        &self,
+        transaction: &transaction::Bitcoin,
+        start_of_swap: NaiveDateTime,
+    ) -> anyhow::Result<Option<ProcessedBlock>> {
+        processed_block(self, start_of_swap, transaction.txid())
+            .instrument(tracing::info_span!("transaction_processed"))
+            .await
+    }
+}
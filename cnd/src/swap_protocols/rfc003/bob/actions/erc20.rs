@@ -69,7 +69,8 @@ where
         use self::LedgerState::*;
 
         let mut actions = match (alpha_state, beta_state) {
-            (Funded { htlc_location, .. }, Redeemed { secret, .. }) => {
+            (Funded { htlc_location, .. }, Redeemed { secret, .. })
+            | (Funded { htlc_location, .. }, RedeemPending { secret, .. }) => {
                 vec![Action::Redeem(<(AL, AA)>::make_redeem_action(
                     HtlcParams::new_alpha_params(request, response),
                     htlc_location.clone(),
@@ -91,7 +92,7 @@ where
             _ => vec![],
         };
 
-        if let Funded { htlc_location, .. } = beta_state {
+        if let Funded { htlc_location, .. } | RedeemPending { htlc_location, .. } = beta_state {
             actions.push(Action::Refund(erc20::refund_action(
                 request.beta_ledger.chain_id,
                 request.beta_expiry,
@@ -158,7 +159,8 @@ where
 
         use self::LedgerState::*;
         let mut actions = match (alpha_state, beta_state) {
-            (Funded { htlc_location, .. }, Redeemed { secret, .. }) => vec![Action::Redeem(
+            (Funded { htlc_location, .. }, Redeemed { secret, .. })
+            | (Funded { htlc_location, .. }, RedeemPending { secret, .. }) => vec![Action::Redeem(
                 erc20::redeem_action(*htlc_location, *secret, request.alpha_ledger.chain_id),
             )],
             (Funded { .. }, NotDeployed) => vec![Action::Fund(<(BL, BA)>::make_fund_action(
@@ -171,6 +173,11 @@ where
             htlc_location,
             fund_transaction,
             ..
+        }
+        | RedeemPending {
+            htlc_location,
+            fund_transaction,
+            ..
         } = beta_state
         {
             actions.push(Action::Refund(<(BL, BA)>::make_refund_action(
@@ -60,7 +60,8 @@ where
 
         use self::LedgerState::*;
         let mut actions = match (alpha_state, beta_state) {
-            (Funded { htlc_location, .. }, Redeemed { secret, .. }) => {
+            (Funded { htlc_location, .. }, Redeemed { secret, .. })
+            | (Funded { htlc_location, .. }, RedeemPending { secret, .. }) => {
                 vec![Action::Redeem(<(AL, AA)>::make_redeem_action(
                     HtlcParams::new_alpha_params(request, response),
                     htlc_location.clone(),
@@ -84,6 +85,11 @@ where
             htlc_location,
             fund_transaction,
             ..
+        }
+        | RedeemPending {
+            htlc_location,
+            fund_transaction,
+            ..
         } = beta_state
         {
             actions.push(Action::Refund(<(BL, BA)>::make_refund_action(
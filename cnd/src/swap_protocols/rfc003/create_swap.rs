@@ -1,10 +1,12 @@
 use crate::{
+    btsieve::ProcessedBlock,
+    db::RecordedEvent,
     swap_protocols::{
         rfc003::{
             self,
             events::{
                 Deployed, Funded, HtlcDeployed, HtlcFunded, HtlcRedeemed, HtlcRefunded, Redeemed,
-                Refunded,
+                Refunded, Retracted, TransactionProcessed, TransactionRetracted,
             },
             state, Accept, LedgerState, Request, SecretHash, SwapId,
         },
@@ -13,11 +15,15 @@ use crate::{
     timestamp::Timestamp,
 };
 use chrono::NaiveDateTime;
-use futures::future::{self, Either};
+use futures::{
+    future::{self, Either},
+    Future, TryFutureExt,
+};
 use genawaiter::{
     sync::{Co, Gen},
     GeneratorState,
 };
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 /// Returns a future that tracks the swap negotiated from the given request and
@@ -31,33 +37,52 @@ use std::sync::Arc;
 ///
 /// It is highly unlikely for Bob to fund the HTLC now, yet the current
 /// implementation is still waiting for that.
+///
+/// The events recorded before a restart restore the state of the ledger and
+/// are not watched for again.
 pub async fn create_watcher<D, S, L, A, H, I, T>(
     dependencies: D,
     ledger_states: Arc<S>,
     id: SwapId,
     htlc_params: HtlcParams<L, A, I>,
     accepted_at: NaiveDateTime,
+    recorded_events: Vec<RecordedEvent<SwapEvent<A, H, T>>>,
 ) where
     D: InsertFailedSwap
         + HtlcFunded<L, A, H, I, T>
         + HtlcDeployed<L, A, H, I, T>
         + HtlcRedeemed<L, A, H, I, T>
-        + HtlcRefunded<L, A, H, I, T>,
-    S: state::Update<SwapEvent<A, H, T>> + state::Insert<LedgerState<A, H, T>>,
+        + HtlcRefunded<L, A, H, I, T>
+        + TransactionRetracted<T>
+        + TransactionProcessed<T>,
+    S: state::Update<RecordedEvent<SwapEvent<A, H, T>>> + state::Insert<LedgerState<A, H, T>>,
     L: Clone,
     A: Ord + Clone,
     H: Clone,
     I: Clone,
     T: Clone,
 {
-    ledger_states
-        .insert(id, LedgerState::<A, H, T>::NotDeployed)
-        .await;
+    let resume_from = resume_from(accepted_at, &recorded_events);
+
+    let mut known_state = LedgerState::<A, H, T>::NotDeployed;
+    for recorded in recorded_events {
+        known_state.transition(recorded.event);
+    }
+
+    ledger_states.insert(id, known_state.clone()).await;
 
     // construct a generator that watches alpha and beta ledger concurrently
     let mut generator = Gen::new({
         |co| async {
-            watch_ledger::<D, L, A, H, I, T>(&dependencies, co, htlc_params, accepted_at).await
+            watch_ledger::<D, L, A, H, I, T>(
+                &dependencies,
+                co,
+                htlc_params,
+                accepted_at,
+                known_state,
+                resume_from,
+            )
+            .await
         }
     });
 
@@ -67,7 +92,13 @@ pub async fn create_watcher<D, S, L, A, H, I, T>(
             // every event that is yielded is passed on
             GeneratorState::Yielded(event) => {
                 tracing::info!("swap {} yielded event {}", id, event);
-                ledger_states.update(&id, event).await;
+                let processed_block = processed_block(&dependencies, &event, accepted_at).await;
+                ledger_states
+                    .update(&id, RecordedEvent {
+                        event,
+                        processed_block,
+                    })
+                    .await;
             }
             // the generator stopped executing, this means there are no more events that can be
             // watched.
@@ -84,53 +115,282 @@ pub async fn create_watcher<D, S, L, A, H, I, T>(
     }
 }
 
+/// Looks up the block that includes the transaction of the event, so we can
+/// resume from it after a restart.
+///
+/// Pending and retracted transactions are not part of any block.
+async fn processed_block<D, A, H, T>(
+    dependencies: &D,
+    event: &SwapEvent<A, H, T>,
+    start_of_swap: NaiveDateTime,
+) -> Option<ProcessedBlock>
+where
+    D: TransactionProcessed<T>,
+{
+    let transaction = match event {
+        SwapEvent::Deployed(Deployed { transaction, .. })
+        | SwapEvent::Redeemed(Redeemed { transaction, .. })
+        | SwapEvent::Refunded(Refunded { transaction }) => transaction,
+        SwapEvent::Funded(funded) => funded.transaction(),
+        SwapEvent::RedeemPending(_) | SwapEvent::Retracted(_) => return None,
+    };
+
+    match dependencies
+        .transaction_processed(transaction, start_of_swap)
+        .await
+    {
+        Ok(processed_block) => processed_block,
+        Err(e) => {
+            tracing::warn!("failed to look up the block of the transaction: {:#}", e);
+            None
+        }
+    }
+}
+
+/// Where to start looking for the next event, the blocks before the one of
+/// the last recorded event don't have to be looked at again.
+///
+/// A retraction invalidates the blocks recorded before it.
+fn resume_from<A, H, T>(
+    start_of_swap: NaiveDateTime,
+    recorded_events: &[RecordedEvent<SwapEvent<A, H, T>>],
+) -> NaiveDateTime {
+    let last_processed_block =
+        recorded_events
+            .iter()
+            .fold(None, |last_processed_block, recorded| {
+                match (&recorded.event, &recorded.processed_block) {
+                    (SwapEvent::Retracted(_), _) => None,
+                    (_, Some(processed_block)) => Some(processed_block),
+                    (_, None) => last_processed_block,
+                }
+            });
+
+    match last_processed_block {
+        Some(processed_block) => std::cmp::max(start_of_swap, processed_block.resume_from),
+        None => start_of_swap,
+    }
+}
+
 /// Returns a future that waits for events to happen on a ledger.
 ///
 /// Each event is yielded through the controller handle (co) of the coroutine.
+/// If a reorg drops the deploy or fund transaction before the HTLC is redeemed
+/// or refunded, we yield `Retracted` and start over. A pending redeem
+/// transaction is yielded as `RedeemPending`, we keep watching until it is
+/// confirmed.
+///
+/// Events already known from `known_state` are not watched for again, the next
+/// one is looked for from `resume_from` on. Transactions can be retracted
+/// all the way back to the start of the swap though.
 async fn watch_ledger<D, L, A, H, I, T>(
     dependencies: &D,
     co: Co<SwapEvent<A, H, T>>,
     htlc_params: HtlcParams<L, A, I>,
     start_of_swap: NaiveDateTime,
+    mut known_state: LedgerState<A, H, T>,
+    resume_from: NaiveDateTime,
 ) -> anyhow::Result<()>
 where
     D: HtlcFunded<L, A, H, I, T>
         + HtlcDeployed<L, A, H, I, T>
         + HtlcRedeemed<L, A, H, I, T>
-        + HtlcRefunded<L, A, H, I, T>,
+        + HtlcRefunded<L, A, H, I, T>
+        + TransactionRetracted<T>,
     Deployed<H, T>: Clone,
+    Funded<A, T>: Clone,
     Redeemed<T>: Clone,
     Refunded<T>: Clone,
+    T: Clone,
 {
-    let deployed = dependencies
-        .htlc_deployed(&htlc_params, start_of_swap)
-        .await?;
-    co.yield_(SwapEvent::Deployed(deployed.clone())).await;
+    let mut look_from = resume_from;
+
+    loop {
+        let (deployed, known_funded, known_redeem_pending) =
+            match std::mem::replace(&mut known_state, LedgerState::NotDeployed) {
+                LedgerState::NotDeployed => {
+                    let deployed = dependencies.htlc_deployed(&htlc_params, look_from).await?;
+                    co.yield_(SwapEvent::Deployed(deployed.clone())).await;
+
+                    (deployed, None, false)
+                }
+                LedgerState::Deployed {
+                    htlc_location,
+                    deploy_transaction,
+                } => (
+                    Deployed {
+                        location: htlc_location,
+                        transaction: deploy_transaction,
+                    },
+                    None,
+                    false,
+                ),
+                LedgerState::Funded {
+                    htlc_location,
+                    deploy_transaction,
+                    fund_transaction,
+                    asset,
+                } => (
+                    Deployed {
+                        location: htlc_location,
+                        transaction: deploy_transaction,
+                    },
+                    Some(Funded::Correctly {
+                        asset,
+                        transaction: fund_transaction,
+                    }),
+                    false,
+                ),
+                LedgerState::IncorrectlyFunded {
+                    htlc_location,
+                    deploy_transaction,
+                    fund_transaction,
+                    asset,
+                } => (
+                    Deployed {
+                        location: htlc_location,
+                        transaction: deploy_transaction,
+                    },
+                    Some(Funded::Incorrectly {
+                        asset,
+                        transaction: fund_transaction,
+                    }),
+                    false,
+                ),
+                LedgerState::RedeemPending {
+                    htlc_location,
+                    deploy_transaction,
+                    fund_transaction,
+                    asset,
+                    ..
+                } => (
+                    Deployed {
+                        location: htlc_location,
+                        transaction: deploy_transaction,
+                    },
+                    Some(Funded::Correctly {
+                        asset,
+                        transaction: fund_transaction,
+                    }),
+                    true,
+                ),
+                LedgerState::Redeemed { .. } | LedgerState::Refunded { .. } => return Ok(()),
+            };
+
+        let funded = match known_funded {
+            Some(funded) => funded,
+            None => {
+                let funded = dependencies.htlc_funded(&htlc_params, &deployed, look_from);
 
-    let funded = dependencies
-        .htlc_funded(&htlc_params, &deployed, start_of_swap)
-        .await?;
-    co.yield_(SwapEvent::Funded(funded)).await;
+                match unless_retracted(dependencies, &deployed.transaction, start_of_swap, funded)
+                    .await?
+                {
+                    Ok(funded) => {
+                        co.yield_(SwapEvent::Funded(funded.clone())).await;
+                        funded
+                    }
+                    Err(retracted) => {
+                        co.yield_(SwapEvent::Retracted(retracted)).await;
+                        // The reorg may reach back further than the recorded
+                        // events.
+                        look_from = start_of_swap;
+                        continue;
+                    }
+                }
+            }
+        };
+        let fund_transaction = funded.transaction().clone();
+
+        let redeemed = dependencies.htlc_redeemed(&htlc_params, &deployed, look_from);
+
+        let refunded = dependencies.htlc_refunded(&htlc_params, &deployed, look_from);
 
-    let redeemed = dependencies.htlc_redeemed(&htlc_params, &deployed, start_of_swap);
+        let redeemed_or_refunded =
+            future::try_select(redeemed, refunded).map_err(|either| either.factor_first().0);
 
-    let refunded = dependencies.htlc_refunded(&htlc_params, &deployed, start_of_swap);
+        let outcome = unless_retracted(
+            dependencies,
+            &fund_transaction,
+            start_of_swap,
+            redeemed_or_refunded,
+        );
 
-    match future::try_select(redeemed, refunded).await {
-        Ok(Either::Left((redeemed, _))) => {
-            co.yield_(SwapEvent::Redeemed(redeemed.clone())).await;
+        // The secret of a pending redeem transaction is all we need to act on
+        // it, the HTLC is only redeemed once the transaction is confirmed.
+        let outcome = if known_redeem_pending {
+            outcome.await?
+        } else {
+            let redeem_pending = dependencies.htlc_redeem_pending(&htlc_params, &deployed);
+            while_redeem_pending(&co, redeem_pending, outcome).await?
+        };
+
+        match outcome {
+            Ok(Either::Left((redeemed, _))) => {
+                co.yield_(SwapEvent::Redeemed(redeemed.clone())).await;
+            }
+            Ok(Either::Right((refunded, _))) => {
+                co.yield_(SwapEvent::Refunded(refunded.clone())).await;
+            }
+            Err(retracted) => {
+                co.yield_(SwapEvent::Retracted(retracted)).await;
+                look_from = start_of_swap;
+                continue;
+            }
         }
-        Ok(Either::Right((refunded, _))) => {
-            co.yield_(SwapEvent::Refunded(refunded.clone())).await;
+
+        return Ok(());
+    }
+}
+
+/// Waits for `next` and yields `RedeemPending` if a pending redeem transaction
+/// shows up in the meantime.
+///
+/// Watching pending transactions is best effort. If it fails, we keep waiting
+/// for `next`.
+async fn while_redeem_pending<A, H, T, P, F, R>(
+    co: &Co<SwapEvent<A, H, T>>,
+    redeem_pending: P,
+    next: F,
+) -> R
+where
+    P: Future<Output = anyhow::Result<Redeemed<T>>>,
+    F: Future<Output = R>,
+{
+    futures::pin_mut!(redeem_pending, next);
+
+    match future::select(next, redeem_pending).await {
+        Either::Left((next, _)) => next,
+        Either::Right((Ok(redeem_pending), next)) => {
+            tracing::info!("found pending redeem transaction");
+            co.yield_(SwapEvent::RedeemPending(redeem_pending)).await;
+
+            next.await
         }
-        Err(either) => {
-            let (error, _other_future) = either.factor_first();
+        Either::Right((Err(e), next)) => {
+            tracing::warn!("failed to watch pending transactions: {:#}", e);
 
-            return Err(error);
+            next.await
         }
     }
+}
+
+/// Waits for `next` unless a reorg drops the given transaction first.
+pub async fn unless_retracted<D, T, F, R>(
+    dependencies: &D,
+    transaction: &T,
+    start_of_swap: NaiveDateTime,
+    next: F,
+) -> anyhow::Result<Result<R, Retracted<T>>>
+where
+    D: TransactionRetracted<T>,
+    F: Future<Output = anyhow::Result<R>> + Unpin,
+{
+    let retracted = dependencies.transaction_retracted(transaction, start_of_swap);
 
-    Ok(())
+    match future::select(next, retracted).await {
+        Either::Left((next, _)) => next.map(Ok),
+        Either::Right((retracted, _)) => retracted.map(Err),
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -249,18 +509,21 @@ where
     }
 }
 
-#[derive(Debug, Clone, PartialEq, strum_macros::Display)]
+#[derive(Debug, Clone, PartialEq, strum_macros::Display, Serialize, Deserialize)]
 pub enum SwapEvent<A, H, T> {
     Deployed(Deployed<H, T>),
     Funded(Funded<A, T>),
+    RedeemPending(Redeemed<T>),
     Redeemed(Redeemed<T>),
     Refunded(Refunded<T>),
+    Retracted(Retracted<T>),
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{asset, htlc_location, transaction};
+    use chrono::Duration;
 
     #[test]
     fn swap_event_should_render_to_nice_string() {
@@ -276,4 +539,69 @@ mod tests {
 
         assert_eq!(formatted, "Deployed")
     }
+
+    type Event = SwapEvent<asset::Ether, htlc_location::Ethereum, transaction::Ethereum>;
+
+    fn deployed(processed_block: Option<ProcessedBlock>) -> RecordedEvent<Event> {
+        RecordedEvent {
+            event: SwapEvent::Deployed(Deployed {
+                location: htlc_location::Ethereum::default(),
+                transaction: transaction::Ethereum::default(),
+            }),
+            processed_block,
+        }
+    }
+
+    fn retracted() -> RecordedEvent<Event> {
+        RecordedEvent {
+            event: SwapEvent::Retracted(Retracted {
+                transaction: transaction::Ethereum::default(),
+            }),
+            processed_block: None,
+        }
+    }
+
+    fn block_at(resume_from: NaiveDateTime) -> Option<ProcessedBlock> {
+        Some(ProcessedBlock {
+            hash: "0a".to_owned(),
+            resume_from,
+        })
+    }
+
+    #[test]
+    fn resumes_from_the_block_of_the_last_recorded_event() {
+        let start_of_swap = NaiveDateTime::from_timestamp(1_500_000_000, 0);
+        let resume_at = start_of_swap + Duration::days(2);
+        let recorded_events = vec![
+            deployed(block_at(start_of_swap + Duration::hours(1))),
+            deployed(block_at(resume_at)),
+            deployed(None),
+        ];
+
+        assert_eq!(resume_from(start_of_swap, &recorded_events), resume_at)
+    }
+
+    #[test]
+    fn resumes_from_the_start_of_the_swap_after_a_retraction() {
+        let start_of_swap = NaiveDateTime::from_timestamp(1_500_000_000, 0);
+        let recorded_events = vec![
+            deployed(block_at(start_of_swap + Duration::days(2))),
+            retracted(),
+        ];
+
+        assert_eq!(resume_from(start_of_swap, &recorded_events), start_of_swap)
+    }
+
+    #[test]
+    fn never_resumes_before_the_start_of_the_swap() {
+        let start_of_swap = NaiveDateTime::from_timestamp(1_500_000_000, 0);
+
+        assert_eq!(resume_from::<(), (), ()>(start_of_swap, &[]), start_of_swap);
+        assert_eq!(
+            resume_from(start_of_swap, &[deployed(block_at(
+                start_of_swap - Duration::hours(1)
+            ))]),
+            start_of_swap
+        );
+    }
 }
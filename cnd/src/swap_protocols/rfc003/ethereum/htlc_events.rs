@@ -1,8 +1,12 @@
 use crate::{
     asset,
     asset::{ethereum::FromWei, Erc20, Erc20Quantity, Ether},
-    btsieve::ethereum::{
-        watch_for_contract_creation, watch_for_event, Cache, Event, Topic, Web3Connector,
+    btsieve::{
+        ethereum::{
+            processed_block, watch_for_contract_creation, watch_for_event, watch_for_pending_call,
+            Cache, Event, Topic, Web3Connector,
+        },
+        watch_for_retraction, ProcessedBlock,
     },
     ethereum::{Hash, U256},
     htlc_location, identity,
@@ -12,14 +16,15 @@ use crate::{
             create_swap::HtlcParams,
             events::{
                 Deployed, Funded, HtlcDeployed, HtlcFunded, HtlcRedeemed, HtlcRefunded, Redeemed,
-                Refunded,
+                Refunded, Retracted, TransactionProcessed, TransactionRetracted,
             },
-            Secret,
+            Secret, SecretHash,
         },
     },
     transaction,
 };
 use chrono::NaiveDateTime;
+use futures::future;
 use std::cmp::Ordering;
 use tracing_futures::Instrument;
 
@@ -109,31 +114,30 @@ impl
 {
     async fn htlc_redeemed(
         &self,
-        _htlc_params: &HtlcParams<Ethereum, asset::Ether, identity::Ethereum>,
+        htlc_params: &HtlcParams<Ethereum, asset::Ether, identity::Ethereum>,
         htlc_deployment: &Deployed<htlc_location::Ethereum, transaction::Ethereum>,
         start_of_swap: NaiveDateTime,
     ) -> anyhow::Result<Redeemed<transaction::Ethereum>> {
-        let event = Event {
-            address: htlc_deployment.location,
-            topics: vec![Some(Topic(*REDEEM_LOG_MSG))],
-        };
-
-        let (transaction, log) = watch_for_event(self, start_of_swap, event)
+        htlc_redeemed(self, htlc_deployment.location, start_of_swap)
             .instrument(tracing::trace_span!(
                 "htlc_redeemed",
                 htlc = format_args!("{:x}", htlc_deployment.location),
                 topic = format_args!("{:x}", *REDEEM_LOG_MSG)
             ))
-            .await?;
-
-        let log_data = log.data.0.as_ref();
-        let secret =
-            Secret::from_vec(log_data).expect("Must be able to construct secret from log data");
+            .await
+    }
 
-        Ok(Redeemed {
-            transaction,
-            secret,
-        })
+    async fn htlc_redeem_pending(
+        &self,
+        htlc_params: &HtlcParams<Ethereum, asset::Ether, identity::Ethereum>,
+        htlc_deployment: &Deployed<htlc_location::Ethereum, transaction::Ethereum>,
+    ) -> anyhow::Result<Redeemed<transaction::Ethereum>> {
+        htlc_redeem_pending(self, htlc_deployment.location, htlc_params.secret_hash)
+            .instrument(tracing::trace_span!(
+                "htlc_redeem_pending",
+                htlc = format_args!("{:x}", htlc_deployment.location)
+            ))
+            .await
     }
 }
 
@@ -257,27 +261,23 @@ impl
 {
     async fn htlc_redeemed(
         &self,
-        _htlc_params: &HtlcParams<Ethereum, Erc20, identity::Ethereum>,
+        htlc_params: &HtlcParams<Ethereum, Erc20, identity::Ethereum>,
         htlc_deployment: &Deployed<htlc_location::Ethereum, transaction::Ethereum>,
         start_of_swap: NaiveDateTime,
     ) -> anyhow::Result<Redeemed<transaction::Ethereum>> {
-        let event = Event {
-            address: htlc_deployment.location,
-            topics: vec![Some(Topic(*REDEEM_LOG_MSG))],
-        };
-
-        let (transaction, log) = watch_for_event(self, start_of_swap, event)
+        htlc_redeemed(self, htlc_deployment.location, start_of_swap)
             .instrument(tracing::info_span!("htlc_redeemed"))
-            .await?;
-
-        let log_data = log.data.0.as_ref();
-        let secret =
-            Secret::from_vec(log_data).expect("Must be able to construct secret from log data");
+            .await
+    }
 
-        Ok(Redeemed {
-            transaction,
-            secret,
-        })
+    async fn htlc_redeem_pending(
+        &self,
+        htlc_params: &HtlcParams<Ethereum, Erc20, identity::Ethereum>,
+        htlc_deployment: &Deployed<htlc_location::Ethereum, transaction::Ethereum>,
+    ) -> anyhow::Result<Redeemed<transaction::Ethereum>> {
+        htlc_redeem_pending(self, htlc_deployment.location, htlc_params.secret_hash)
+            .instrument(tracing::info_span!("htlc_redeem_pending"))
+            .await
     }
 }
 
@@ -309,3 +309,79 @@ impl
         Ok(Refunded { transaction })
     }
 }
+
+#[async_trait::async_trait]
+impl TransactionRetracted<transaction::Ethereum> for Cache<Web3Connector> {
+    async fn transaction_retracted(
+        &self,
+        transaction: &transaction::Ethereum,
+        start_of_swap: NaiveDateTime,
+    ) -> anyhow::Result<Retracted<transaction::Ethereum>> {
+        watch_for_retraction(self, start_of_swap, &transaction.hash)
+            .instrument(tracing::info_span!("transaction_retracted"))
+            .await?;
+
+        Ok(Retracted {
+            transaction: transaction.clone(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl TransactionProcessed<transaction::Ethereum> for Cache<Web3Connector> {
+    async fn transaction_processed(
+        &self,
+        transaction: &transaction::Ethereum,
+        start_of_swap: NaiveDateTime,
+    ) -> anyhow::Result<Option<ProcessedBlock>> {
+        processed_block(self, start_of_swap, transaction.hash)
+            .instrument(tracing::info_span!("transaction_processed"))
+            .await
+    }
+}
+
+/// Watches for the redeem log of the HTLC.
+async fn htlc_redeemed(
+    connector: &Cache<Web3Connector>,
+    htlc: htlc_location::Ethereum,
+    start_of_swap: NaiveDateTime,
+) -> anyhow::Result<Redeemed<transaction::Ethereum>> {
+    let event = Event {
+        address: htlc,
+        topics: vec![Some(Topic(*REDEEM_LOG_MSG))],
+    };
+
+    let (transaction, log) = watch_for_event(connector, start_of_swap, event).await?;
+    let secret = Secret::from_vec(log.data.0.as_ref())
+        .expect("Must be able to construct secret from log data");
+
+    Ok(Redeemed {
+        transaction,
+        secret,
+    })
+}
+
+/// Watches for a pending redeem transaction, which reveals the secret before
+/// it is mined.
+async fn htlc_redeem_pending(
+    connector: &Cache<Web3Connector>,
+    htlc: htlc_location::Ethereum,
+    secret_hash: SecretHash,
+) -> anyhow::Result<Redeemed<transaction::Ethereum>> {
+    if !connector.watch_mempool {
+        return future::pending().await;
+    }
+
+    // The HTLC is redeemed by calling it with the secret as input.
+    let (transaction, secret) =
+        watch_for_pending_call(connector, htlc, |input| match Secret::from_vec(input) {
+            Ok(secret) if secret.hash() == secret_hash => Some(secret),
+            _ => None,
+        })
+        .await?;
+
+    Ok(Redeemed {
+        transaction,
+        secret,
+    })
+}
@@ -1,4 +1,7 @@
-use crate::swap_protocols::rfc003::{create_swap::HtlcParams, Secret};
+use crate::{
+    btsieve::ProcessedBlock,
+    swap_protocols::rfc003::{create_swap::HtlcParams, Secret},
+};
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 
@@ -8,6 +11,16 @@ pub enum Funded<A, T> {
     Incorrectly { asset: A, transaction: T },
 }
 
+impl<A, T> Funded<A, T> {
+    pub fn transaction(&self) -> &T {
+        match self {
+            Funded::Correctly { transaction, .. } | Funded::Incorrectly { transaction, .. } => {
+                transaction
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Redeemed<T> {
     pub transaction: T,
@@ -25,6 +38,12 @@ pub struct Refunded<T> {
     pub transaction: T,
 }
 
+/// A transaction we reported earlier is no longer part of the chain.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Retracted<T> {
+    pub transaction: T,
+}
+
 #[async_trait::async_trait]
 pub trait HtlcFunded<L, A, H, I, T>: Send + Sync + Sized + 'static {
     async fn htlc_funded(
@@ -52,6 +71,16 @@ pub trait HtlcRedeemed<L, A, H, I, T>: Send + Sync + Sized + 'static {
         htlc_deployment: &Deployed<H, T>,
         start_of_swap: NaiveDateTime,
     ) -> anyhow::Result<Redeemed<T>>;
+
+    /// Resolves once a transaction redeeming the HTLC shows up among the
+    /// pending transactions, which reveals the secret before it is confirmed.
+    ///
+    /// Never resolves if pending transactions are not watched on this ledger.
+    async fn htlc_redeem_pending(
+        &self,
+        htlc_params: &HtlcParams<L, A, I>,
+        htlc_deployment: &Deployed<H, T>,
+    ) -> anyhow::Result<Redeemed<T>>;
 }
 
 #[async_trait::async_trait]
@@ -63,3 +92,23 @@ pub trait HtlcRefunded<L, A, H, I, T>: Send + Sync + Sized + 'static {
         start_of_swap: NaiveDateTime,
     ) -> anyhow::Result<Refunded<T>>;
 }
+
+#[async_trait::async_trait]
+pub trait TransactionRetracted<T>: Send + Sync + Sized + 'static {
+    async fn transaction_retracted(
+        &self,
+        transaction: &T,
+        start_of_swap: NaiveDateTime,
+    ) -> anyhow::Result<Retracted<T>>;
+}
+
+#[async_trait::async_trait]
+pub trait TransactionProcessed<T>: Send + Sync + Sized + 'static {
+    /// Returns the block that includes the given transaction, `None` if it is
+    /// not part of the chain anymore.
+    async fn transaction_processed(
+        &self,
+        transaction: &T,
+        start_of_swap: NaiveDateTime,
+    ) -> anyhow::Result<Option<ProcessedBlock>>;
+}
@@ -1,5 +1,6 @@
 use crate::swap_protocols::rfc003::{
-    events::{Deployed, Funded, Redeemed, Refunded},
+    create_swap::SwapEvent,
+    events::{Deployed, Funded, Redeemed, Refunded, Retracted},
     Secret,
 };
 use serde::Serialize;
@@ -29,6 +30,16 @@ pub enum LedgerState<A, H, T> {
         fund_transaction: T,
         asset: A,
     },
+    /// A transaction redeeming the HTLC is pending, it already revealed the
+    /// secret but may never be confirmed.
+    RedeemPending {
+        htlc_location: H,
+        deploy_transaction: T,
+        fund_transaction: T,
+        redeem_transaction: T,
+        asset: A,
+        secret: Secret,
+    },
     Redeemed {
         htlc_location: H,
         deploy_transaction: T,
@@ -91,6 +102,32 @@ impl<A, H, T> LedgerState<A, H, T> {
         }
     }
 
+    pub fn transition_to_redeem_pending(&mut self, redeem_pending: Redeemed<T>) {
+        let Redeemed {
+            transaction,
+            secret,
+        } = redeem_pending;
+
+        match std::mem::replace(self, LedgerState::NotDeployed) {
+            LedgerState::Funded {
+                deploy_transaction,
+                htlc_location,
+                asset,
+                fund_transaction,
+            } => {
+                *self = LedgerState::RedeemPending {
+                    deploy_transaction,
+                    htlc_location,
+                    fund_transaction,
+                    redeem_transaction: transaction,
+                    asset,
+                    secret,
+                }
+            }
+            other => panic!("expected state Funded, got {}", HtlcState::from(other)),
+        }
+    }
+
     pub fn transition_to_redeemed(&mut self, redeemed: Redeemed<T>) {
         let Redeemed {
             transaction,
@@ -103,6 +140,13 @@ impl<A, H, T> LedgerState<A, H, T> {
                 htlc_location,
                 asset,
                 fund_transaction,
+            }
+            | LedgerState::RedeemPending {
+                deploy_transaction,
+                htlc_location,
+                asset,
+                fund_transaction,
+                ..
             } => {
                 *self = LedgerState::Redeemed {
                     deploy_transaction,
@@ -113,7 +157,10 @@ impl<A, H, T> LedgerState<A, H, T> {
                     secret,
                 }
             }
-            other => panic!("expected state Funded, got {}", HtlcState::from(other)),
+            other => panic!(
+                "expected state Funded or RedeemPending, got {}",
+                HtlcState::from(other)
+            ),
         }
     }
 
@@ -132,6 +179,13 @@ impl<A, H, T> LedgerState<A, H, T> {
                 htlc_location,
                 asset,
                 fund_transaction,
+            }
+            | LedgerState::RedeemPending {
+                deploy_transaction,
+                htlc_location,
+                asset,
+                fund_transaction,
+                ..
             } => {
                 *self = LedgerState::Refunded {
                     deploy_transaction,
@@ -142,11 +196,34 @@ impl<A, H, T> LedgerState<A, H, T> {
                 }
             }
             other => panic!(
-                "expected state Funded or IncorrectlyFunded, got {}",
+                "expected state Funded, IncorrectlyFunded or RedeemPending, got {}",
                 HtlcState::from(other)
             ),
         }
     }
+
+    /// A reorg dropped one of the transactions of the HTLC, we start over
+    /// looking for its deployment.
+    pub fn transition_to_not_deployed(&mut self, _retracted: Retracted<T>) {
+        *self = LedgerState::NotDeployed;
+    }
+
+    pub fn transition(&mut self, event: SwapEvent<A, H, T>) {
+        match event {
+            SwapEvent::Deployed(deployed) => self.transition_to_deployed(deployed),
+            SwapEvent::Funded(funded) => self.transition_to_funded(funded),
+            SwapEvent::RedeemPending(redeem_pending) => {
+                self.transition_to_redeem_pending(redeem_pending)
+            }
+            SwapEvent::Redeemed(redeemed) => {
+                // what if redeemed.secret.hash() != secret_hash in request ??
+
+                self.transition_to_redeemed(redeemed);
+            }
+            SwapEvent::Refunded(refunded) => self.transition_to_refunded(refunded),
+            SwapEvent::Retracted(retracted) => self.transition_to_not_deployed(retracted),
+        }
+    }
 }
 
 impl Default for HtlcState {
@@ -158,13 +235,14 @@ impl Default for HtlcState {
 #[cfg(test)]
 impl quickcheck::Arbitrary for HtlcState {
     fn arbitrary<G: quickcheck::Gen>(g: &mut G) -> Self {
-        match g.next_u32() % 6 {
+        match g.next_u32() % 7 {
             0 => HtlcState::NotDeployed,
             1 => HtlcState::Deployed,
             2 => HtlcState::Funded,
             3 => HtlcState::IncorrectlyFunded,
-            4 => HtlcState::Redeemed,
-            5 => HtlcState::Refunded,
+            4 => HtlcState::RedeemPending,
+            5 => HtlcState::Redeemed,
+            6 => HtlcState::Refunded,
             _ => unreachable!(),
         }
     }
@@ -1,7 +1,13 @@
-use crate::swap_protocols::rfc003::{
-    create_swap::SwapEvent,
-    state::{Get, Insert, Update},
-    LedgerState, SwapId,
+use crate::{
+    db::{LedgerEvent, LedgerSide, RecordedEvent, Save, Sqlite},
+    swap_protocols::{
+        rfc003::{
+            create_swap::SwapEvent,
+            state::{Get, Insert, Update},
+            HtlcState, LedgerState, SwapId,
+        },
+        state_changes::Publisher,
+    },
 };
 use async_trait::async_trait;
 use std::{any::Any, collections::HashMap};
@@ -10,6 +16,27 @@ use tokio::sync::Mutex;
 #[derive(Default, Debug)]
 pub struct LedgerStates {
     states: Mutex<HashMap<SwapId, Box<dyn Any + Send>>>,
+    publisher: Publisher,
+    ledger_events: Option<(Sqlite, LedgerSide)>,
+}
+
+impl LedgerStates {
+    pub fn new(publisher: Publisher) -> Self {
+        Self {
+            states: Mutex::default(),
+            publisher,
+            ledger_events: None,
+        }
+    }
+
+    /// Records every event in the database, so the state of the ledger can be
+    /// restored after a restart.
+    pub fn with_ledger_events(self, db: Sqlite, side: LedgerSide) -> Self {
+        Self {
+            ledger_events: Some((db, side)),
+            ..self
+        }
+    }
 }
 
 #[async_trait]
@@ -41,14 +68,28 @@ where
 }
 
 #[async_trait]
-impl<A, H, T> Update<SwapEvent<A, H, T>> for LedgerStates
+impl<A, H, T> Update<RecordedEvent<SwapEvent<A, H, T>>> for LedgerStates
 where
     LedgerState<A, H, T>: 'static,
+    SwapEvent<A, H, T>: Clone,
+    Sqlite: Save<LedgerEvent<SwapEvent<A, H, T>>>,
     A: Send,
     H: Send,
     T: Send,
 {
-    async fn update(&self, key: &SwapId, event: SwapEvent<A, H, T>) {
+    async fn update(&self, key: &SwapId, recorded: RecordedEvent<SwapEvent<A, H, T>>) {
+        if let Some((db, side)) = &self.ledger_events {
+            let ledger_event = LedgerEvent {
+                swap_id: *key,
+                side: *side,
+                recorded: recorded.clone(),
+            };
+
+            if let Err(e) = db.save(ledger_event).await {
+                tracing::warn!("failed to record {} event of swap {}: {:?}", side, key, e);
+            }
+        }
+
         let mut states = self.states.lock().await;
         let ledger_state = match states
             .get_mut(key)
@@ -61,16 +102,9 @@ where
             }
         };
 
-        match event {
-            SwapEvent::Deployed(deployed) => ledger_state.transition_to_deployed(deployed),
-            SwapEvent::Funded(funded) => ledger_state.transition_to_funded(funded),
-            SwapEvent::Redeemed(redeemed) => {
-                // what if redeemed.secret.hash() != secret_hash in request ??
+        ledger_state.transition(recorded.event);
 
-                ledger_state.transition_to_redeemed(redeemed);
-            }
-            SwapEvent::Refunded(refunded) => ledger_state.transition_to_refunded(refunded),
-        }
+        self.publisher.publish(key, HtlcState::from(&*ledger_state));
     }
 }
 
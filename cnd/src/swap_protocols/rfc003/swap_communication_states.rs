@@ -1,6 +1,9 @@
-use crate::swap_protocols::rfc003::{
-    state::{Get, Insert},
-    SwapId,
+use crate::swap_protocols::{
+    rfc003::{
+        state::{Get, Insert},
+        SwapCommunication, SwapId,
+    },
+    state_changes::Publisher,
 };
 use async_trait::async_trait;
 use std::{any::Any, clone::Clone, collections::HashMap};
@@ -9,16 +12,36 @@ use tokio::sync::Mutex;
 #[derive(Default, Debug)]
 pub struct SwapCommunicationStates {
     states: Mutex<HashMap<SwapId, Box<dyn Any + Send>>>,
+    publisher: Publisher,
+}
+
+impl SwapCommunicationStates {
+    pub fn new(publisher: Publisher) -> Self {
+        Self {
+            states: Mutex::default(),
+            publisher,
+        }
+    }
 }
 
 #[async_trait]
-impl<S> Insert<S> for SwapCommunicationStates
+impl<AL, BL, AA, BA, AI, BI> Insert<SwapCommunication<AL, BL, AA, BA, AI, BI>>
+    for SwapCommunicationStates
 where
-    S: Send + 'static,
+    SwapCommunication<AL, BL, AA, BA, AI, BI>: Send + 'static,
 {
-    async fn insert(&self, key: SwapId, value: S) {
+    async fn insert(&self, key: SwapId, value: SwapCommunication<AL, BL, AA, BA, AI, BI>) {
+        // Same wording as the swap communication status of the HTTP API.
+        let status = match value {
+            SwapCommunication::Proposed { .. } => "SENT",
+            SwapCommunication::Accepted { .. } => "ACCEPTED",
+            SwapCommunication::Declined { .. } => "DECLINED",
+        };
+
         let mut states = self.states.lock().await;
         states.insert(key, Box::new(value));
+
+        self.publisher.publish(&key, status);
     }
 }
 
@@ -2,10 +2,15 @@ use crate::{
     asset,
     btsieve::{
         self,
-        bitcoin::BitcoindConnector,
+        bitcoin::Connector as BitcoinConnector,
         ethereum::{self, Web3Connector},
+        ProcessedBlock,
     },
-    db::{AcceptedSwap, DetermineTypes, LoadAcceptedSwap, Retrieve, Save, Sqlite, Swap, SwapTypes},
+    db::{
+        AcceptedSwap, DetermineTypes, LedgerSide, LoadAcceptedSwap, LoadLedgerEvents,
+        RecordedEvent, Retrieve, Save, Sqlite, Swap, SwapTypes,
+    },
+    expiries::ExpiryValidator,
     htlc_location, identity,
     network::{
         ComitPeers, DialInformation, ListenAddresses, LocalPeerId, PendingRequestFor, RequestError,
@@ -19,7 +24,7 @@ use crate::{
             create_swap::HtlcParams,
             events::{
                 Deployed, Funded, HtlcDeployed, HtlcFunded, HtlcRedeemed, HtlcRefunded, Redeemed,
-                Refunded,
+                Refunded, Retracted, TransactionProcessed, TransactionRetracted,
             },
             state, LedgerStates, SwapCommunication, SwapCommunicationStates, SwapId,
         },
@@ -48,7 +53,7 @@ use std::{convert::TryInto, fmt::Debug, sync::Arc};
 #[delegate(Retrieve, target = "db")]
 #[delegate(DetermineTypes, target = "db")]
 pub struct Rfc003Facade {
-    pub bitcoin_connector: Arc<btsieve::bitcoin::Cache<BitcoindConnector>>,
+    pub bitcoin_connector: Arc<btsieve::bitcoin::Cache<BitcoinConnector>>,
     pub ethereum_connector: Arc<ethereum::Cache<Web3Connector>>,
     pub alpha_ledger_states: Arc<LedgerStates>,
     pub beta_ledger_states: Arc<LedgerStates>,
@@ -57,6 +62,7 @@ pub struct Rfc003Facade {
     pub seed: RootSeed,
     pub swarm: Swarm,
     pub db: Sqlite,
+    pub expiry_validator: ExpiryValidator,
 }
 
 #[async_trait]
@@ -121,6 +127,21 @@ where
     }
 }
 
+#[async_trait]
+impl<E> LoadLedgerEvents<E> for Rfc003Facade
+where
+    Sqlite: LoadLedgerEvents<E>,
+    E: Send + 'static,
+{
+    async fn load_ledger_events(
+        &self,
+        swap_id: &SwapId,
+        side: LedgerSide,
+    ) -> anyhow::Result<Vec<RecordedEvent<E>>> {
+        self.db.load_ledger_events(swap_id, side).await
+    }
+}
+
 #[async_trait]
 impl<T> Save<T> for Rfc003Facade
 where
@@ -198,6 +219,16 @@ impl
             .htlc_redeemed(htlc_params, htlc_deployment, start_of_swap)
             .await
     }
+
+    async fn htlc_redeem_pending(
+        &self,
+        htlc_params: &HtlcParams<__TYPE0__, asset::Bitcoin, identity::Bitcoin>,
+        htlc_deployment: &Deployed<htlc_location::Bitcoin, transaction::Bitcoin>,
+    ) -> anyhow::Result<Redeemed<transaction::Bitcoin>> {
+        self.bitcoin_connector
+            .htlc_redeem_pending(htlc_params, htlc_deployment)
+            .await
+    }
 }
 
 #[impl_template]
@@ -223,6 +254,32 @@ impl
     }
 }
 
+#[async_trait::async_trait]
+impl TransactionRetracted<transaction::Bitcoin> for Rfc003Facade {
+    async fn transaction_retracted(
+        &self,
+        transaction: &transaction::Bitcoin,
+        start_of_swap: NaiveDateTime,
+    ) -> anyhow::Result<Retracted<transaction::Bitcoin>> {
+        self.bitcoin_connector
+            .transaction_retracted(transaction, start_of_swap)
+            .await
+    }
+}
+
+#[async_trait::async_trait]
+impl TransactionProcessed<transaction::Bitcoin> for Rfc003Facade {
+    async fn transaction_processed(
+        &self,
+        transaction: &transaction::Bitcoin,
+        start_of_swap: NaiveDateTime,
+    ) -> anyhow::Result<Option<ProcessedBlock>> {
+        self.bitcoin_connector
+            .transaction_processed(transaction, start_of_swap)
+            .await
+    }
+}
+
 #[impl_template]
 #[async_trait::async_trait]
 impl
@@ -289,6 +346,16 @@ impl
             .htlc_redeemed(htlc_params, htlc_deployment, start_of_swap)
             .await
     }
+
+    async fn htlc_redeem_pending(
+        &self,
+        htlc_params: &HtlcParams<Ethereum, __TYPE0__, identity::Ethereum>,
+        htlc_deployment: &Deployed<htlc_location::Ethereum, transaction::Ethereum>,
+    ) -> anyhow::Result<Redeemed<transaction::Ethereum>> {
+        self.ethereum_connector
+            .htlc_redeem_pending(htlc_params, htlc_deployment)
+            .await
+    }
 }
 
 #[impl_template]
@@ -313,3 +380,29 @@ impl
             .await
     }
 }
+
+#[async_trait::async_trait]
+impl TransactionRetracted<transaction::Ethereum> for Rfc003Facade {
+    async fn transaction_retracted(
+        &self,
+        transaction: &transaction::Ethereum,
+        start_of_swap: NaiveDateTime,
+    ) -> anyhow::Result<Retracted<transaction::Ethereum>> {
+        self.ethereum_connector
+            .transaction_retracted(transaction, start_of_swap)
+            .await
+    }
+}
+
+#[async_trait::async_trait]
+impl TransactionProcessed<transaction::Ethereum> for Rfc003Facade {
+    async fn transaction_processed(
+        &self,
+        transaction: &transaction::Ethereum,
+        start_of_swap: NaiveDateTime,
+    ) -> anyhow::Result<Option<ProcessedBlock>> {
+        self.ethereum_connector
+            .transaction_processed(transaction, start_of_swap)
+            .await
+    }
+}
@@ -0,0 +1,115 @@
+use serde::Serialize;
+use std::fmt::Display;
+use tokio::sync::broadcast;
+
+/// The number of changes a subscriber can lag behind before it starts
+/// missing changes.
+const CAPACITY: usize = 1024;
+
+/// A change of one of the states of a swap.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct StateChange {
+    pub swap_id: String,
+    pub subject: Subject,
+    pub status: serde_json::Value,
+}
+
+/// The part of a swap whose state changed.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Subject {
+    Communication,
+    AlphaLedger,
+    BetaLedger,
+}
+
+/// Fans out the changes of all swap states to everyone who subscribed, e.g.
+/// the event stream of the HTTP API.
+#[derive(Clone, Debug)]
+pub struct StateChanges {
+    sender: broadcast::Sender<StateChange>,
+}
+
+impl Default for StateChanges {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+
+        Self { sender }
+    }
+}
+
+impl StateChanges {
+    pub fn subscribe(&self) -> broadcast::Receiver<StateChange> {
+        self.sender.subscribe()
+    }
+
+    pub fn publisher(&self, subject: Subject) -> Publisher {
+        Publisher(Some((self.sender.clone(), subject)))
+    }
+}
+
+/// Used by the state stores to publish changes of their states.
+///
+/// The default publisher does not publish anything.
+#[derive(Clone, Debug, Default)]
+pub struct Publisher(Option<(broadcast::Sender<StateChange>, Subject)>);
+
+impl Publisher {
+    pub fn publish<K, S>(&self, swap_id: &K, status: S)
+    where
+        K: Display,
+        S: Serialize,
+    {
+        let (sender, subject) = match &self.0 {
+            Some(inner) => inner,
+            None => return,
+        };
+
+        let status = match serde_json::to_value(status) {
+            Ok(status) => status,
+            Err(e) => {
+                tracing::error!("failed to serialize status of swap {}: {}", swap_id, e);
+                return;
+            }
+        };
+
+        // Sending only fails if there are no subscribers which is fine.
+        let _ = sender.send(StateChange {
+            swap_id: swap_id.to_string(),
+            subject: *subject,
+            status,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_protocols::LocalSwapId;
+
+    #[tokio::test]
+    async fn subscriber_receives_published_change() {
+        let state_changes = StateChanges::default();
+        let mut receiver = state_changes.subscribe();
+        let publisher = state_changes.publisher(Subject::AlphaLedger);
+        let swap_id = LocalSwapId::default();
+
+        publisher.publish(&swap_id, "FUNDED");
+
+        let change = receiver.recv().await.unwrap();
+        assert_eq!(change, StateChange {
+            swap_id: swap_id.to_string(),
+            subject: Subject::AlphaLedger,
+            status: serde_json::json!("FUNDED"),
+        });
+    }
+
+    #[test]
+    fn publishing_without_subscribers_does_not_fail() {
+        let state_changes = StateChanges::default();
+        let publisher = state_changes.publisher(Subject::Communication);
+
+        publisher.publish(&LocalSwapId::default(), "SENT");
+        Publisher::default().publish(&LocalSwapId::default(), "SENT");
+    }
+}
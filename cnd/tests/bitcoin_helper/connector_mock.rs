@@ -1,7 +1,10 @@
 use anyhow::Context;
 use async_trait::async_trait;
 use bitcoin::{util::hash::BitcoinHash, BlockHash};
-use cnd::btsieve::{BlockByHash, LatestBlock};
+use cnd::btsieve::{
+    bitcoin::TransactionsByAddress, BlockByHash, LatestBlock, RequiredConfirmations,
+    SubscribeLatestBlocks,
+};
 use futures::{stream::BoxStream, StreamExt};
 use std::{collections::HashMap, time::Duration};
 use tokio::{stream, sync::Mutex, time::throttle};
@@ -31,6 +34,12 @@ impl BitcoinConnectorMock {
 #[error("there are no more blocks in this blockchain, either your implementation is buggy or you need a better test setup")]
 pub struct OutOfBlocks;
 
+impl RequiredConfirmations for BitcoinConnectorMock {}
+
+impl SubscribeLatestBlocks for BitcoinConnectorMock {}
+
+impl TransactionsByAddress for BitcoinConnectorMock {}
+
 #[async_trait]
 impl LatestBlock for BitcoinConnectorMock {
     type Block = bitcoin::Block;
@@ -1,7 +1,10 @@
 use anyhow::Context;
 use async_trait::async_trait;
 use cnd::{
-    btsieve::{ethereum::ReceiptByHash, BlockByHash, LatestBlock},
+    btsieve::{
+        ethereum::ReceiptByHash, BlockByHash, LatestBlock, RequiredConfirmations,
+        SubscribeLatestBlocks,
+    },
     ethereum::{Block, Hash, TransactionReceipt},
 };
 use futures::{stream::BoxStream, StreamExt};
@@ -41,6 +44,10 @@ impl EthereumConnectorMock {
 #[error("there are no more blocks in this blockchain, either your implementation is buggy or you need a better test setup")]
 pub struct OutOfBlocks;
 
+impl RequiredConfirmations for EthereumConnectorMock {}
+
+impl SubscribeLatestBlocks for EthereumConnectorMock {}
+
 #[async_trait]
 impl LatestBlock for EthereumConnectorMock {
     type Block = Block;